@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use cfg_if::cfg_if;
+use chrono::{DateTime, Utc};
+
+use crate::ohlcv::{next_timeframe_boundary, Ohlcv, OhlcvBar, OhlcvReader};
+
+cfg_if! {
+    if #[cfg(feature = "polars")] {
+        use polars::{error::PolarsError, frame::DataFrame, prelude::NamedFrom, series::Series};
+    }
+}
+
+/// Which open times the aligned series have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum OhlcvJoin {
+    /// Open times present in every series.
+    #[default]
+    Inner,
+    /// Open times present in any series.
+    Outer,
+    /// Open times of the first series.
+    Left,
+}
+
+impl TryFrom<&str> for OhlcvJoin {
+    type Error = String;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "inner" => Ok(OhlcvJoin::Inner),
+            "outer" => Ok(OhlcvJoin::Outer),
+            "left" => Ok(OhlcvJoin::Left),
+            _ => Err(format!(
+                "Unknown join {:?}, expected inner, outer or left",
+                value
+            )),
+        }
+    }
+}
+
+/// How bars missing from a series are filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum OhlcvFill {
+    /// NaN prices and volume.
+    None,
+    /// Flat bar at the previous close with zero volume. NaN before the first bar of the series.
+    #[default]
+    Forward,
+    /// Same as `Forward`, but at most `n` bars in a row, NaN after.
+    ForwardLimit(usize),
+}
+
+impl TryFrom<&str> for OhlcvFill {
+    type Error = String;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "none" => Ok(OhlcvFill::None),
+            "ffill" | "forward" => Ok(OhlcvFill::Forward),
+            _ => Err(format!("Unknown fill {:?}, expected none or ffill", value)),
+        }
+    }
+}
+
+/// Series sharing the same open times, bar `i` of every series opens at `open_time[i]`.
+#[derive(Debug, Clone)]
+pub struct OhlcvAlignment {
+    open_time: Vec<DateTime<Utc>>,
+    series: Vec<Ohlcv>,
+    filled: Vec<Vec<bool>>,
+}
+
+impl OhlcvAlignment {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.open_time.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.open_time.is_empty()
+    }
+
+    #[inline]
+    pub fn open_time(&self) -> &[DateTime<Utc>] {
+        &self.open_time
+    }
+
+    #[inline]
+    pub fn series(&self) -> &[Ohlcv] {
+        &self.series
+    }
+
+    #[inline]
+    pub fn into_series(self) -> Vec<Ohlcv> {
+        self.series
+    }
+
+    /// `true` for bars that were missing from the series, per series.
+    #[inline]
+    pub fn filled(&self) -> &[Vec<bool>] {
+        &self.filled
+    }
+
+    /// Wide frame with a shared `open_time` column followed by `{name}_open`, `{name}_high`, `{name}_low`,
+    /// `{name}_close` and `{name}_volume` of every series. Times are in seconds, like `Ohlcv::to_polars`.
+    #[cfg(feature = "polars")]
+    pub fn to_polars(&self, names: &[&str]) -> Result<DataFrame, PolarsError> {
+        assert_eq!(
+            names.len(),
+            self.series.len(),
+            "Expected {} names, got {}",
+            self.series.len(),
+            names.len()
+        );
+        let open_time: Vec<i64> = self.open_time.iter().map(|x| x.timestamp()).collect();
+        let mut columns = vec![Series::new("open_time", open_time)];
+        for (name, ohlcv) in names.iter().zip(self.series.iter()) {
+            let bars = ohlcv.bars();
+            let column = |suffix: &str, f: fn(&OhlcvBar) -> f64| {
+                let values: Vec<f64> = bars.iter().map(f).collect();
+                Series::new(&format!("{}_{}", name, suffix), values)
+            };
+            columns.push(column("open", |b| b.open()));
+            columns.push(column("high", |b| b.high()));
+            columns.push(column("low", |b| b.low()));
+            columns.push(column("close", |b| b.close()));
+            columns.push(column("volume", |b| b.volume()));
+        }
+        return DataFrame::new(columns);
+    }
+}
+
+/// Joins `series` on bar open time. Bars without an open time are ignored, and of bars sharing an open time the last one is kept.
+/// Aligned series keep the timeframe and time zone of their source.
+pub fn align(series: &[&dyn OhlcvReader], join: OhlcvJoin, fill: OhlcvFill) -> OhlcvAlignment {
+    let by_time: Vec<BTreeMap<DateTime<Utc>, OhlcvBar>> = series
+        .iter()
+        .map(|ohlcv| {
+            ohlcv
+                .bars()
+                .into_iter()
+                .filter_map(|bar| bar.open_time().copied().map(|time| (time, bar)))
+                .collect()
+        })
+        .collect();
+    let open_time: Vec<DateTime<Utc>> = match join {
+        OhlcvJoin::Left => by_time
+            .first()
+            .map(|bars| bars.keys().copied().collect())
+            .unwrap_or_default(),
+        OhlcvJoin::Inner => match by_time.split_first() {
+            Some((first, rest)) => first
+                .keys()
+                .filter(|time| rest.iter().all(|bars| bars.contains_key(time)))
+                .copied()
+                .collect(),
+            None => vec![],
+        },
+        OhlcvJoin::Outer => {
+            let mut times: Vec<DateTime<Utc>> = by_time
+                .iter()
+                .flat_map(|bars| bars.keys().copied())
+                .collect();
+            times.sort();
+            times.dedup();
+            times
+        }
+    };
+    let mut aligned = vec![];
+    let mut filled = vec![];
+    for (ohlcv, bars) in series.iter().zip(by_time.iter()) {
+        let mut result: Vec<OhlcvBar> = Vec::with_capacity(open_time.len());
+        let mut is_filled = Vec::with_capacity(open_time.len());
+        let mut prev_close = f64::NAN;
+        let mut streak = 0;
+        // bars between two aligned times still move the last close, e.g. with a left join
+        let mut source = bars.iter().peekable();
+        let timeframe = ohlcv.timeframe();
+        let timezone = ohlcv.timezone();
+        for (i, time) in open_time.iter().enumerate() {
+            while let Some((_, bar)) = source.next_if(|(t, _)| *t < time) {
+                prev_close = bar.close();
+            }
+            if let Some((_, bar)) = source.next_if(|(t, _)| *t == time) {
+                result.push(*bar);
+                is_filled.push(false);
+                prev_close = bar.close();
+                streak = 0;
+                continue;
+            }
+            streak += 1;
+            let price = match fill {
+                OhlcvFill::Forward => prev_close,
+                OhlcvFill::ForwardLimit(n) if streak <= n => prev_close,
+                _ => f64::NAN,
+            };
+            let volume = if price.is_nan() { f64::NAN } else { 0.0 };
+            let close_time = if timeframe.is_time_based() {
+                Some(next_timeframe_boundary(*time, &timeframe, &timezone))
+            } else {
+                open_time.get(i + 1).copied()
+            };
+            result.push(OhlcvBar::new(
+                Some(*time),
+                close_time,
+                price,
+                price,
+                price,
+                price,
+                volume,
+            ));
+            is_filled.push(true);
+        }
+        let mut aligned_ohlcv = Ohlcv::from_bars(result);
+        aligned_ohlcv.set_timeframe(timeframe);
+        aligned_ohlcv.set_timezone(timezone);
+        aligned.push(aligned_ohlcv);
+        filled.push(is_filled);
+    }
+    return OhlcvAlignment {
+        open_time,
+        series: aligned,
+        filled,
+    };
+}
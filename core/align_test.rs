@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod test {
+    use crate::{
+        align::{align, OhlcvFill, OhlcvJoin},
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        test_utils::time,
+        timeframe::Timeframe,
+    };
+
+    fn ohlcv(bars: &[(i64, f64)]) -> Ohlcv {
+        let bars = bars
+            .iter()
+            .map(|(t, close)| {
+                OhlcvBar::new(
+                    Some(time(*t)),
+                    Some(time(*t + 1)),
+                    *close,
+                    *close,
+                    *close,
+                    *close,
+                    1.0,
+                )
+            })
+            .collect();
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Minutes(1));
+        return ohlcv;
+    }
+
+    #[test]
+    pub fn joins() {
+        let a = ohlcv(&[(0, 1.0), (1, 2.0), (2, 3.0), (4, 5.0)]);
+        let b = ohlcv(&[(1, 20.0), (3, 40.0), (4, 50.0)]);
+
+        let inner = align(&[&a, &b], OhlcvJoin::Inner, OhlcvFill::Forward);
+        assert_eq!(inner.open_time(), &[time(1), time(4)]);
+        assert_eq!(inner.series()[0].get(1).unwrap().close(), 5.0);
+        assert_eq!(inner.series()[1].get(1).unwrap().close(), 50.0);
+
+        let outer = align(&[&a, &b], OhlcvJoin::Outer, OhlcvFill::Forward);
+        assert_eq!(outer.len(), 5);
+        let b = &outer.series()[1];
+        assert!(b.get(0).unwrap().close().is_nan());
+        assert_eq!(b.get(2).unwrap().close(), 20.0);
+        assert_eq!(b.get(2).unwrap().volume(), 0.0);
+        assert_eq!(b.get(2).unwrap().close_time(), Some(&time(3)));
+        assert_eq!(outer.filled()[1], vec![true, false, true, false, false]);
+        assert_eq!(outer.series()[0].get(3).unwrap().close(), 3.0);
+
+        let a = ohlcv(&[(0, 1.0), (1, 2.0), (2, 3.0), (4, 5.0)]);
+        let b = ohlcv(&[(1, 20.0), (3, 40.0), (4, 50.0)]);
+        // b at 3 is not in a, but still moves the last close
+        let left = align(&[&b, &a], OhlcvJoin::Left, OhlcvFill::ForwardLimit(0));
+        assert_eq!(left.open_time(), &[time(1), time(3), time(4)]);
+        assert!(left.series()[1].get(1).unwrap().close().is_nan());
+        let left = align(&[&b, &a], OhlcvJoin::Left, OhlcvFill::Forward);
+        assert_eq!(left.series()[1].get(1).unwrap().close(), 3.0);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    pub fn wide_polars() {
+        let a = ohlcv(&[(0, 1.0), (1, 2.0)]);
+        let b = ohlcv(&[(1, 20.0)]);
+        let df = align(&[&a, &b], OhlcvJoin::Outer, OhlcvFill::None)
+            .to_polars(&["a", "b"])
+            .unwrap();
+        assert_eq!(df.shape(), (2, 11));
+        let b_close: Vec<Option<f64>> = df
+            .column("b_close")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert!(b_close[0].unwrap().is_nan());
+        assert_eq!(b_close[1], Some(20.0));
+    }
+}
@@ -4,17 +4,19 @@ use crate::{
     ctx::{Ctx, CtxSkip},
     legacy::Float64Utils,
     metrics::{
-        annualization_factor, avg_losing_trade, avg_trade, avg_win_loss_ratio, avg_winning_trade,
+        avg_losing_trade, avg_trade, avg_win_loss_ratio, avg_winning_trade,
         expectancy, gross_loss_pct, gross_profit_pct, net_profit_pct, profit_factor,
         sharpe_ratio_from_returns, sortino_ratio_from_returns, win_rate,
     },
     orderbook::{
-        order_size_for_equity_pct, round_contracts, round_to_min_tick, validate_contracts,
-        OrderBook, OrderBookConfig, OrderBookError, OrderConfig,
+        order_size_for_equity_pct, round_contracts, round_to_min_tick, truncate_contracts,
+        validate_contracts, OrderBook, OrderBookConfig, OrderBookError, OrderConfig,
     },
+    pine::{backtest_to_pine, PineError, PineExportConfig},
+    roll::Roll,
     signal::{Signal, SignalKind},
     stats::returns,
-    sym::Sym,
+    sym::{SettlementType, Sym, SymError},
     timeframe::Timeframe,
     trade::{Trade, TradeError, TradeEvent},
     utils::with_suffix,
@@ -34,13 +36,19 @@ use comfy_table::{
 use comfy_table::{Cell, ContentArrangement, Row, Table as ComfyTable};
 use textplots::{Chart, Plot, Shape};
 }}
+#[cfg(feature = "json")]
+use thiserror::Error;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct BacktestConfig {
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     initial_capital: f64,
     process_orders_on_close: bool,
     debug: bool,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     risk_free_rate: f64,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     annualization_factor: f64,
 }
 
@@ -132,25 +140,29 @@ pub struct Backtest {
     closed_shorts: usize,
     first_entry_bar_index: Option<usize>,
     prev_equity_pct: f64,
+    rolls: VecDeque<Roll>,
 }
 
 impl Backtest {
+    /// Panics if the ctx symbol is not suitable for backtesting, see `try_new`.
     #[inline]
-    pub fn new(ctx: Rc<RefCell<Ctx>>, mut config: BacktestConfig) -> Self {
+    pub fn new(ctx: Rc<RefCell<Ctx>>, config: BacktestConfig) -> Self {
+        return Self::try_new(ctx, config)
+            .unwrap_or_else(|e| panic!("Ctx Symbol is not suitable for backtesting: {}", e));
+    }
+
+    /// Fails if the ctx symbol doesn't pass `Sym::validate`.
+    pub fn try_new(ctx: Rc<RefCell<Ctx>>, mut config: BacktestConfig) -> Result<Self, SymError> {
         let sym = ctx.borrow().sym().clone();
-        assert!(
-            !f64::is_nan(sym.min_qty()) && !f64::is_nan(sym.min_tick()),
-            "Ctx Symbol is not suitable for backtesting, min_qty is NaN or min_tick is NaN"
-        );
+        sym.validate()?;
         if config.risk_free_rate.is_nan() {
             config.risk_free_rate = 0.0;
         }
         if config.annualization_factor.is_nan() {
-            config.annualization_factor =
-                annualization_factor(ctx.borrow().ohlcv().timeframe(), sym.kind().periods());
+            config.annualization_factor = sym.annualization_factor(ctx.borrow().ohlcv().timeframe());
         }
         let initial_capital = config.initial_capital;
-        Self {
+        return Ok(Self {
             ctx,
             config,
             orderbook: Rc::new(RefCell::new(OrderBook::new(OrderBookConfig {
@@ -178,7 +190,8 @@ impl Backtest {
             open_shorts: 0,
             first_entry_bar_index: None,
             prev_equity_pct: 0.0,
-        }
+            rolls: VecDeque::new(),
+        });
     }
 
     #[inline]
@@ -418,9 +431,10 @@ impl Backtest {
         let net_equity = self.initial_capital + self.net_profit;
 
         let mut open_profit = 0.0;
+        let point_value = self.point_value();
 
         for trade in &mut self.open_trades {
-            trade.set_pnl_from_price(self.instrument_price)?;
+            trade.set_pnl_from_price(self.instrument_price, point_value)?;
             open_profit += trade.pnl();
         }
 
@@ -443,11 +457,16 @@ impl Backtest {
             fill_bar_index - 1
         };
 
+        return self.create_fill_event(order_bar_index, self.orderbook.borrow().price());
+    }
+
+    #[inline]
+    fn create_fill_event(&self, order_bar_index: usize, price: f64) -> TradeEvent {
         let mut trade = TradeEvent::default();
         trade
-            .set_fill_bar_index(fill_bar_index)
+            .set_fill_bar_index(self.ctx.borrow().bar_index())
             .set_order_bar_index(order_bar_index)
-            .set_price(self.orderbook.borrow().price());
+            .set_price(price);
 
         return trade;
     }
@@ -473,12 +492,12 @@ impl Backtest {
                     self.equity(),
                     self.position_size(),
                     self.instrument_price,
-                    1.0,
+                    self.point_value(),
                     1.0,
                 );
 
                 let order_size =
-                    round_contracts(base_order_size, ctx.sym().min_qty(), ctx.sym().qty_scale());
+                    truncate_contracts(base_order_size, ctx.sym().min_qty(), ctx.sym().qty_scale());
 
                 // if self.bar_index() == 21454 || self.bar_index() == 21293 {
                 //     println!("[{} -> compute_equity_pct]: equity_pct: {:?} | prev_equity_pct: {:?} | base_order_size: {:?} | order_size: {:?} | min_qty: {:?} | price_scale: {:?}", self.bar_index(), equity_pct, self.prev_equity_pct, base_order_size, order_size, ctx.sym().min_qty(), ctx.sym().price_scale());
@@ -512,30 +531,149 @@ impl Backtest {
         //     self.equity.values.len(),
         //     self.equity.get()
         // );
+        self.roll().unwrap();
+        // a gap in the data past expiry settles at the last close before it
+        let expired = self.is_expired(false);
+        if expired {
+            self.expire(self.settlement_price()).unwrap();
+        }
         self.set_price();
-        if !self.config.process_orders_on_close {
+        if expired {
+            self.set_metrics().unwrap();
+        } else if !self.config.process_orders_on_close {
             self.process_orderbook().unwrap();
         }
     }
 
     pub fn on_bar_close(&mut self) {
         self.set_price();
-        if self.config.process_orders_on_close {
+        if self.config.process_orders_on_close && !self.is_expired(false) {
             self.process_orderbook().unwrap();
         }
+        if self.is_expired(true) {
+            self.expire(self.settlement_price()).unwrap();
+            self.set_metrics().unwrap();
+        }
+    }
+
+    /// Contract rolls of a continuous series, see `roll_contracts`. Open trades are closed at the exit price with the `roll`
+    /// id and reopened at the entry price under their entry id when the bar of a roll opens. Rolls without finite prices
+    /// are skipped. Pending rolls are part of `BacktestState`.
+    pub fn set_rolls(&mut self, mut rolls: Vec<Roll>) {
+        rolls.sort_by_key(|roll| roll.bar_index);
+        self.rolls = rolls.into();
+    }
+
+    #[inline]
+    pub fn rolls(&self) -> &VecDeque<Roll> {
+        &self.rolls
+    }
+
+    fn roll(&mut self) -> Result<(), TradeError> {
+        let bar_index = self.bar_index();
+        while let Some(roll) = self.rolls.front() {
+            if roll.bar_index > bar_index {
+                break;
+            }
+            let roll = self.rolls.pop_front().unwrap();
+            if roll.bar_index < bar_index
+                || !roll.exit_price.is_finite()
+                || !roll.entry_price.is_finite()
+            {
+                continue;
+            }
+            let trades: Vec<Trade> = self.open_trades.drain(..).collect();
+            for mut trade in trades {
+                let size = trade.size();
+                let entry_id = trade.entry().and_then(|entry| entry.id().cloned());
+                let exit = self.create_fill_event(bar_index, roll.exit_price);
+                self.close_trade(&mut trade, Some("roll".to_string()), exit)?;
+                let entry = self.create_fill_event(bar_index, roll.entry_price);
+                self.open_trade(size, entry_id, entry)?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Whether the current bar opens (or closes) at or after the expiry of the ctx symbol.
+    fn is_expired(&self, close: bool) -> bool {
+        let ctx = self.ctx.borrow();
+        let Some(derivative) = ctx.sym().derivative() else {
+            return false;
+        };
+        let bar = ctx.bar();
+        let time = if close {
+            bar.close_time().or(bar.open_time())
+        } else {
+            bar.open_time()
+        };
+        return time.is_some_and(|time| derivative.is_expired(time));
+    }
+
+    /// Final price of an expiring contract: the settlement price of cash settled ones if known, otherwise the last close.
+    fn settlement_price(&self) -> f64 {
+        let ctx = self.ctx.borrow();
+        return match ctx.sym().derivative() {
+            Some(derivative) if derivative.settlement == SettlementType::Cash => derivative
+                .settlement_price
+                .unwrap_or(self.instrument_price),
+            _ => self.instrument_price,
+        };
+    }
+
+    /// Closes all open trades at `price` with the `expiry` exit id and drops pending orders.
+    fn expire(&mut self, price: f64) -> Result<(), TradeError> {
+        let queued: Vec<usize> = self
+            .orderbook
+            .borrow()
+            .queued_orders()
+            .iter()
+            .map(|order| order.id())
+            .collect();
+        for id in queued {
+            self.orderbook.borrow_mut().cancel(id);
+        }
+        if self.open_trades.is_empty() {
+            return Ok(());
+        }
+        let event = self.create_fill_event(self.bar_index(), price);
+        while let Some(mut trade) = self.open_trades.pop_front() {
+            self.close_trade(&mut trade, Some("expiry".to_string()), event.clone())?;
+        }
+        self.prev_equity_pct = 0.0;
+        return Ok(());
+    }
+
+    /// Re-evaluates the current bar after it was updated in place, e.g. an intrabar tick on the realtime bar.
+    /// Open profit and equity are marked to the new close, pending orders are left for `on_bar_open`/`on_bar_close`.
+    ///
+    /// `on_bar_open` and `on_bar_close` are not re-run for updates. In streaming mode call `on_bar_open` once after
+    /// `Ctx::next` moved to an appended bar, `on_bar_update` for every update of it, and `on_bar_close` once the bar is final.
+    pub fn on_bar_update(&mut self) {
+        self.set_price();
+        self.set_metrics().unwrap();
     }
 
     pub fn on_trade_open(&mut self, size: f64, entry_id: Option<String>) -> Result<(), TradeError> {
+        let event = self.create_trade_event();
+        return self.open_trade(size, entry_id, event);
+    }
+
+    fn open_trade(
+        &mut self,
+        size: f64,
+        entry_id: Option<String>,
+        mut event: TradeEvent,
+    ) -> Result<(), TradeError> {
         let mut trade = Trade::new();
         trade.set_size(size)?;
 
-        let mut event = self.create_trade_event();
-
         if self.first_entry_bar_index.is_none() {
             self.first_entry_bar_index = Some(event.fill_bar_index());
         }
 
         event.set_id(entry_id);
+        trade.set_commission(self.fill_fee(size, event.price()));
         trade.set_entry(event)?;
 
         /*
@@ -557,10 +695,20 @@ impl Backtest {
         trade: &mut Trade,
         exit_id: Option<String>,
     ) -> Result<(), TradeError> {
-        let mut event = self.create_trade_event();
+        let event = self.create_trade_event();
+        return self.close_trade(trade, exit_id, event);
+    }
+
+    fn close_trade(
+        &mut self,
+        trade: &mut Trade,
+        exit_id: Option<String>,
+        mut event: TradeEvent,
+    ) -> Result<(), TradeError> {
         event.set_id(exit_id);
 
-        trade.set_pnl_from_price(event.price())?;
+        trade.set_commission(trade.commission() + self.fill_fee(trade.size(), event.price()));
+        trade.set_pnl_from_price(event.price(), self.point_value())?;
         trade.set_exit(event)?;
 
         self.net_profit = self.net_profit + trade.pnl();
@@ -609,50 +757,103 @@ impl Backtest {
                 self.log("process_orderbook", format!("{:?}", order));
             }
 
-            let mut fill_size = order.size();
-            let mut open_trade_index = 0;
+            let mut event = self.create_trade_event();
+            event.set_comment(order.comment().clone());
+            self.fill(order.size(), order.tag(), &event)?;
+        }
 
-            loop {
-                if fill_size == 0.0 || open_trade_index >= self.open_trades.len() {
-                    break;
-                }
+        self.set_metrics()?;
 
-                let open_trade = &mut self.open_trades[open_trade_index];
+        return Ok(());
+    }
 
-                if open_trade.size().signum() != order.size().signum() {
-                    // close entire trade
-                    if open_trade.size().abs() <= fill_size.abs() {
-                        let mut closed_trade = self.open_trades.remove(open_trade_index).unwrap();
-                        fill_size += closed_trade.size();
+    /// Applies a fill that happened outside of the internal orderbook, e.g. reported by a broker.
+    /// Open trades are closed or reduced first, the rest opens a new trade, same as `process_orderbook`.
+    pub fn on_fill(
+        &mut self,
+        order_bar_index: usize,
+        size: f64,
+        price: f64,
+        tag: Option<String>,
+    ) -> Result<(), TradeError> {
+        let price = round_to_min_tick(price, self.ctx.borrow().sym().min_tick());
+        let event = self.create_fill_event(order_bar_index, price);
+        self.fill(size, &tag, &event)?;
+        self.set_metrics()?;
+        return Ok(());
+    }
 
-                        self.on_trade_close(&mut closed_trade, order.tag().clone())?;
+    /// `Sym::point_value` of the ctx sym, `1.0` if unset.
+    #[inline]
+    pub fn point_value(&self) -> f64 {
+        let point_value = self.ctx.borrow().sym().point_value();
+        return if point_value.is_nan() { 1.0 } else { point_value };
+    }
 
-                        continue;
-                    }
-                    // partially closed trade
-                    else {
-                        let open_partial_size = open_trade.size() + fill_size;
+    /// Taker fee of the ctx sym on the notional, see `SymFees`.
+    #[inline]
+    fn fill_fee(&self, size: f64, price: f64) -> f64 {
+        return self
+            .ctx
+            .borrow()
+            .sym()
+            .fees()
+            .fill_fee(size, price * self.point_value());
+    }
 
-                        open_trade.set_size(open_partial_size)?;
+    /// Trades pay the sym fees on entry and exit, see `Trade::commission`.
+    fn fill(
+        &mut self,
+        size: f64,
+        tag: &Option<String>,
+        event: &TradeEvent,
+    ) -> Result<(), TradeError> {
+        let mut fill_size = size;
+        let mut open_trade_index = 0;
 
-                        let closed_partial_size = fill_size.abs() * open_trade.size().signum();
-                        let mut closed_trade = open_trade.clone();
-                        closed_trade.set_size(closed_partial_size)?;
-                        self.on_trade_close(&mut closed_trade, order.tag().clone())?;
+        loop {
+            if fill_size == 0.0 || open_trade_index >= self.open_trades.len() {
+                break;
+            }
 
-                        fill_size = 0.0;
-                    }
-                }
+            let open_trade = &mut self.open_trades[open_trade_index];
 
-                open_trade_index += 1;
-            }
+            if open_trade.size().signum() != size.signum() {
+                // close entire trade
+                if open_trade.size().abs() <= fill_size.abs() {
+                    let mut closed_trade = self.open_trades.remove(open_trade_index).unwrap();
+                    fill_size += closed_trade.size();
+
+                    self.close_trade(&mut closed_trade, tag.clone(), event.clone())?;
 
-            if validate_contracts(fill_size, self.ctx.borrow().sym().min_qty()) {
-                self.on_trade_open(fill_size, order.tag().clone())?;
+                    continue;
+                }
+                // partially closed trade
+                else {
+                    let open_partial_size = open_trade.size() + fill_size;
+                    // the entry fee is split by size
+                    let commission = open_trade.commission();
+                    let closed_commission = commission * fill_size.abs() / open_trade.size().abs();
+
+                    open_trade.set_size(open_partial_size)?;
+                    open_trade.set_commission(commission - closed_commission);
+
+                    let closed_partial_size = fill_size.abs() * open_trade.size().signum();
+                    let mut closed_trade = open_trade.clone();
+                    closed_trade.set_size(closed_partial_size)?;
+                    closed_trade.set_commission(closed_commission);
+                    self.close_trade(&mut closed_trade, tag.clone(), event.clone())?;
+
+                    fill_size = 0.0;
+                }
             }
+
+            open_trade_index += 1;
         }
 
-        self.set_metrics()?;
+        if validate_contracts(fill_size, self.ctx.borrow().sym().min_qty()) {
+            self.open_trade(fill_size, tag.clone(), event.clone())?;
+        }
 
         return Ok(());
     }
@@ -707,6 +908,13 @@ impl Backtest {
 
     #[inline]
     pub fn signal(&mut self, signal: Signal) {
+        if let Some(order) = self.order_for_signal(signal) {
+            self.orderbook.borrow_mut().enqueue(order).unwrap();
+        }
+    }
+
+    /// Order that `signal` would enqueue, without enqueuing it. Lets the orders be routed elsewhere, e.g. to a broker.
+    pub fn order_for_signal(&mut self, signal: Signal) -> Option<OrderConfig> {
         // if self.config.debug {
         //     println!("[{} ->raw  signal]: {:?}", self.bar_index(), &signal);
         // }
@@ -722,12 +930,13 @@ impl Backtest {
         if order.is_some() && signal.id().is_some() {
             order.as_mut().unwrap().set_tag(signal.id().cloned());
         }
+        if order.is_some() && signal.comment().is_some() {
+            order.as_mut().unwrap().set_comment(signal.comment().cloned());
+        }
         if self.config.debug {
             println!("[{} -> signal]: {:?}", self.bar_index(), &order);
         }
-        if order.is_some() {
-            self.orderbook.borrow_mut().enqueue(order.unwrap()).unwrap();
-        }
+        return order;
     }
 
     #[inline]
@@ -777,81 +986,14 @@ impl Backtest {
         self.ctx.borrow().len()
     }
 
-    pub fn to_pine(&self) -> String {
-        let initial_capital = self.initial_capital;
-        let mut pine = "".to_string();
-        pine += "//@version=5";
-        pine += &format!("\nstrategy(\"Strategy export\", overlay=true, initial_capital={initial_capital}, default_qty_type = strategy.percent_of_equity, default_qty_value = 100)");
-        pine += &format!("\n// Generated at {:?}", chrono::offset::Utc::now());
-        pine += &format!("\n// by https://qpace.dev - The technical analysis framework\n\n");
-        pine += &format!(
-            "
-type Trade
-    string id
-    int entry_open_time_ms
-    int exit_open_time_ms
-    float size
-        "
-        );
-
-        fn trade_to_pine(ctx: &Ctx, trade: &Trade, id: String) -> String {
-            let entry_open_time_ms = trade
-                .entry()
-                .as_ref()
-                .map(|r| {
-                    ctx.ohlcv()
-                        .get(r.order_bar_index())
-                        .unwrap()
-                        .open_time()
-                        .unwrap()
-                        .timestamp_millis()
-                        .to_string()
-                })
-                .unwrap_or("na".to_string());
-            let exit_open_time_ms = trade
-                .exit()
-                .as_ref()
-                .map(|r| {
-                    ctx.ohlcv()
-                        .get(r.order_bar_index())
-                        .unwrap()
-                        .open_time()
-                        .unwrap()
-                        .timestamp_millis()
-                        .to_string()
-                })
-                .unwrap_or("na".to_string());
-            let size = trade.size();
-            return format!("Trade.new(id=\"{id}\", entry_open_time_ms={entry_open_time_ms}, exit_open_time_ms={exit_open_time_ms}, size={size})");
-        }
-
-        let pine_trades = self
-            .trades()
-            .iter()
-            .enumerate()
-            .map(|(i, trade)| trade_to_pine(&self.ctx.borrow(), trade, format!("{i}")))
-            .collect::<Vec<String>>();
-        pine += &format!("\ntrades = array.from<Trade>({})", pine_trades.join(","));
-
-        pine += &format!(
-            "
-open_time_ms = time
-
-for i = 0 to array.size(trades) - 1
-    item = array.get(trades, i)
-    qty = math.abs(item.size)
-
-    if item.entry_open_time_ms == open_time_ms
-        _dir = item.size > 0 ? strategy.long : strategy.short
-        strategy.order(id=item.id, direction=_dir, qty=qty)
-
-    if item.exit_open_time_ms == open_time_ms
-        _dir = item.size > 0 ? strategy.short : strategy.long
-        strategy.order(id=item.id, direction=_dir, qty=qty)
-    "
-        );
+    #[inline]
+    pub fn to_pine(&self) -> Result<String, PineError> {
+        return self.to_pine_with(&PineExportConfig::default());
+    }
 
-        return pine;
+    #[inline]
+    pub fn to_pine_with(&self, config: &PineExportConfig) -> Result<String, PineError> {
+        return backtest_to_pine(self, config);
     }
 
     #[inline]
@@ -872,6 +1014,25 @@ for i = 0 to array.size(trades) - 1
             .set_content_arrangement(ContentArrangement::DynamicFullWidth)
             .set_header(vec!["Metric", "Value"]);
 
+        let ctx = self.ctx.borrow();
+        let timezone = ctx.timezone();
+        let first = ctx.ohlcv().get(0).and_then(|bar| bar.open_time().copied());
+        let last = ctx
+            .ohlcv()
+            .get(ctx.bar_index())
+            .and_then(|bar| bar.close_time().or(bar.open_time()).copied());
+        if let (Some(first), Some(last)) = (first, last) {
+            let f_time = |time: DateTime<Utc>| {
+                time.with_timezone(&timezone)
+                    .format("%Y-%m-%d %H:%M %Z")
+                    .to_string()
+            };
+            table.add_row(Row::from(vec![
+                Cell::new("Period"),
+                Cell::new(format!("{}\n{}", f_time(first), f_time(last))),
+            ]));
+        }
+
         table.add_row(Row::from(vec![
             Cell::new("Net Profit"),
             Cell::new(f(self.net_profit, self.net_profit_pct() * 100.0)),
@@ -1054,6 +1215,265 @@ for i = 0 to array.size(trades) - 1
     }
 }
 
+#[cfg(feature = "json")]
+#[derive(Debug, Error)]
+pub enum BacktestStateError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Binary(#[from] bincode::Error),
+    #[error("Unsupported state version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("Bar index {0} is out of range of ctx with {1} bars")]
+    BarIndexOutOfRange(usize, usize),
+    #[error(transparent)]
+    Sym(#[from] SymError),
+}
+
+/// Complete engine state of a `Backtest`, used to persist a long running backtest and resume it with `Backtest::restore`.
+/// Ohlcv and symbol are not included, the `Ctx` passed to `restore` must hold the same bars.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BacktestState {
+    version: u32,
+    bar_index: Option<usize>,
+    config: BacktestConfig,
+    orderbook: OrderBook,
+    closed_trades: VecDeque<Trade>,
+    open_trades: VecDeque<Trade>,
+    #[serde(with = "crate::utils::serde_nan")]
+    instrument_price: f64,
+    #[serde(with = "crate::utils::serde_nan")]
+    initial_capital: f64,
+    #[serde(with = "crate::utils::serde_nan::vec")]
+    equity: Vec<f64>,
+    #[serde(with = "crate::utils::serde_nan::vec")]
+    net_equity: Vec<f64>,
+    #[serde(with = "crate::utils::serde_nan")]
+    open_profit: f64,
+    #[serde(with = "crate::utils::serde_nan")]
+    net_profit: f64,
+    #[serde(with = "crate::utils::serde_nan")]
+    gross_profit: f64,
+    #[serde(with = "crate::utils::serde_nan")]
+    gross_loss: f64,
+    winning_trades: usize,
+    losing_trades: usize,
+    #[serde(with = "crate::utils::serde_nan")]
+    position_size: f64,
+    open_longs: usize,
+    open_shorts: usize,
+    closed_longs: usize,
+    closed_shorts: usize,
+    first_entry_bar_index: Option<usize>,
+    #[serde(with = "crate::utils::serde_nan")]
+    prev_equity_pct: f64,
+    #[serde(default)]
+    rolls: VecDeque<Roll>,
+}
+
+#[cfg(feature = "json")]
+impl BacktestState {
+    pub const VERSION: u32 = 2;
+    /// Oldest version `restore` accepts. Version 1 has no order and trade comments, they are read as `None`.
+    pub const MIN_VERSION: u32 = 1;
+
+    #[inline]
+    pub fn bar_index(&self) -> Option<usize> {
+        self.bar_index
+    }
+
+    #[inline]
+    pub fn config(&self) -> &BacktestConfig {
+        &self.config
+    }
+
+    #[inline]
+    pub fn to_json(&self) -> Result<String, BacktestStateError> {
+        return Ok(serde_json::to_string(self)?);
+    }
+
+    #[inline]
+    pub fn from_json(json: &str) -> Result<Self, BacktestStateError> {
+        return Ok(serde_json::from_str(json)?);
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BacktestStateError> {
+        return Ok(bincode::serialize(self)?);
+    }
+
+    /// Only the current version, the binary format has no optional fields.
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BacktestStateError> {
+        let version: u32 = bincode::deserialize(bytes)?;
+        if version != Self::VERSION {
+            return Err(BacktestStateError::UnsupportedVersion(version));
+        }
+        return Ok(bincode::deserialize(bytes)?);
+    }
+}
+
+#[cfg(feature = "json")]
+impl Backtest {
+    pub fn snapshot(&self) -> BacktestState {
+        let ctx = self.ctx.borrow();
+        BacktestState {
+            version: BacktestState::VERSION,
+            bar_index: if ctx.is_initialized() {
+                Some(ctx.bar_index())
+            } else {
+                None
+            },
+            config: self.config,
+            orderbook: self.orderbook.borrow().clone(),
+            closed_trades: self.closed_trades.clone(),
+            open_trades: self.open_trades.clone(),
+            instrument_price: self.instrument_price,
+            initial_capital: self.initial_capital,
+            equity: self.equity.clone(),
+            net_equity: self.net_equity.clone(),
+            open_profit: self.open_profit,
+            net_profit: self.net_profit,
+            gross_profit: self.gross_profit,
+            gross_loss: self.gross_loss,
+            winning_trades: self.winning_trades,
+            losing_trades: self.losing_trades,
+            position_size: self.position_size,
+            open_longs: self.open_longs,
+            open_shorts: self.open_shorts,
+            closed_longs: self.closed_longs,
+            closed_shorts: self.closed_shorts,
+            first_entry_bar_index: self.first_entry_bar_index,
+            prev_equity_pct: self.prev_equity_pct,
+            rolls: self.rolls.clone(),
+        }
+    }
+
+    /// Resumes a backtest from `state`. `ctx` is moved to the bar the snapshot was taken at,
+    /// so iteration continues with the next bar exactly as if the backtest had never stopped.
+    /// `ctx` is left untouched if the state or the ctx symbol is invalid.
+    pub fn restore(
+        ctx: Rc<RefCell<Ctx>>,
+        state: BacktestState,
+    ) -> Result<Self, BacktestStateError> {
+        if !(BacktestState::MIN_VERSION..=BacktestState::VERSION).contains(&state.version) {
+            return Err(BacktestStateError::UnsupportedVersion(state.version));
+        }
+        if let Some(bar_index) = state.bar_index {
+            let len = ctx.borrow().len();
+            if bar_index >= len {
+                return Err(BacktestStateError::BarIndexOutOfRange(bar_index, len));
+            }
+        }
+        let mut bt = Backtest::try_new(ctx, state.config)?;
+        bt.ctx.borrow_mut().restore_bar_index(state.bar_index);
+        bt.orderbook = Rc::new(RefCell::new(state.orderbook));
+        bt.closed_trades = state.closed_trades;
+        bt.open_trades = state.open_trades;
+        bt.instrument_price = state.instrument_price;
+        bt.initial_capital = state.initial_capital;
+        bt.equity = state.equity;
+        bt.net_equity = state.net_equity;
+        bt.open_profit = state.open_profit;
+        bt.net_profit = state.net_profit;
+        bt.gross_profit = state.gross_profit;
+        bt.gross_loss = state.gross_loss;
+        bt.winning_trades = state.winning_trades;
+        bt.losing_trades = state.losing_trades;
+        bt.position_size = state.position_size;
+        bt.open_longs = state.open_longs;
+        bt.open_shorts = state.open_shorts;
+        bt.closed_longs = state.closed_longs;
+        bt.closed_shorts = state.closed_shorts;
+        bt.first_entry_bar_index = state.first_entry_bar_index;
+        bt.prev_equity_pct = state.prev_equity_pct;
+        bt.rolls = state.rolls;
+        return Ok(bt);
+    }
+}
+
+/// Tolerances used when comparing a `Backtest` against reference results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestTolerance {
+    /// Fill prices, in ticks of the symbol. Fills are rounded to `min_tick`, so half a tick accepts only the same price.
+    pub price: f64,
+    /// Position and trade sizes.
+    pub size: f64,
+    /// Equity, profit and loss values.
+    pub money: f64,
+}
+
+impl Default for BacktestTolerance {
+    fn default() -> Self {
+        Self {
+            price: 0.5,
+            size: 0.000001,
+            money: 0.01,
+        }
+    }
+}
+
+/// Single difference between actual and expected values. `bar_index` is `None` for values that are not tied to a bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestMismatch {
+    pub bar_index: Option<usize>,
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+    /// `actual - expected` for numeric values.
+    pub delta: Option<f64>,
+}
+
+impl BacktestMismatch {
+    #[inline]
+    pub fn new(bar_index: Option<usize>, field: &str, expected: f64, actual: f64) -> Self {
+        let delta = actual - expected;
+        Self {
+            bar_index,
+            field: field.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+            delta: if delta.is_nan() { None } else { Some(delta) },
+        }
+    }
+
+    #[inline]
+    pub fn value<T: std::fmt::Debug>(
+        bar_index: Option<usize>,
+        field: &str,
+        expected: T,
+        actual: T,
+    ) -> Self {
+        Self {
+            bar_index,
+            field: field.to_string(),
+            expected: format!("{:?}", expected),
+            actual: format!("{:?}", actual),
+            delta: None,
+        }
+    }
+}
+
+impl std::fmt::Display for BacktestMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.bar_index {
+            Some(bar_index) => write!(f, "[{}] ", bar_index)?,
+            None => write!(f, "[-] ")?,
+        }
+        write!(
+            f,
+            "{}: expected {}, actual {}",
+            self.field, self.expected, self.actual
+        )?;
+        if let Some(delta) = self.delta {
+            write!(f, " (delta {:+})", delta)?;
+        }
+        return Ok(());
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct BacktestBarDump {
     pub bar_index: usize,
     pub equity: f64,
@@ -1063,10 +1483,11 @@ pub struct BacktestBarDump {
     pub net_profit: f64,
     pub gross_profit: f64,
     pub gross_loss: f64,
-    pub open_trades: usize,
-    pub closed_trades: usize,
-    pub winning_trades: usize,
-    pub losing_trades: usize,
+    /// Trade counts are `None` when the reference has no such column, so `diff` skips them.
+    pub open_trades: Option<usize>,
+    pub closed_trades: Option<usize>,
+    pub winning_trades: Option<usize>,
+    pub losing_trades: Option<usize>,
     pub trades: Vec<Trade>,
 }
 
@@ -1081,10 +1502,10 @@ impl Default for BacktestBarDump {
             net_profit: f64::NAN,
             gross_profit: f64::NAN,
             gross_loss: f64::NAN,
-            open_trades: 0,
-            closed_trades: 0,
-            winning_trades: 0,
-            losing_trades: 0,
+            open_trades: None,
+            closed_trades: None,
+            winning_trades: None,
+            losing_trades: None,
             trades: vec![],
         }
     }
@@ -1101,14 +1522,101 @@ impl BacktestBarDump {
         dump.net_profit = bt.net_profit();
         dump.gross_profit = bt.gross_profit();
         dump.gross_loss = bt.gross_loss();
-        dump.open_trades = bt.open_trades().len();
-        dump.closed_trades = bt.closed_trades().len();
-        dump.winning_trades = bt.winning_trades_count();
-        dump.losing_trades = bt.losing_trades_count();
+        dump.open_trades = Some(bt.open_trades.len());
+        dump.closed_trades = Some(bt.closed_trades.len());
+        dump.winning_trades = Some(bt.winning_trades_count());
+        dump.losing_trades = Some(bt.losing_trades_count());
         dump.trades = bt.trades().into_iter().cloned().collect();
         return dump;
     }
 
+    /// Compares bar metrics with `expected` without panicking. `NaN` and `None` expected values are not compared,
+    /// so references that lack some of the metrics can still be used. Trades are not compared.
+    pub fn diff(
+        &self,
+        expected: &BacktestBarDump,
+        tolerance: &BacktestTolerance,
+    ) -> Vec<BacktestMismatch> {
+        let bar_index = Some(expected.bar_index);
+        let mut mismatches = vec![];
+        let mut compare = |field: &str, actual: f64, expected: f64, eps: f64| {
+            if expected.is_nan() {
+                return;
+            }
+            if !actual.compare_with_precision(expected, eps) {
+                mismatches.push(BacktestMismatch::new(bar_index, field, expected, actual));
+            }
+        };
+        compare(
+            "bar_index",
+            self.bar_index as f64,
+            expected.bar_index as f64,
+            0.5,
+        );
+        compare("equity", self.equity, expected.equity, tolerance.money);
+        compare(
+            "net_equity",
+            self.net_equity,
+            expected.net_equity,
+            tolerance.money,
+        );
+        compare(
+            "position_size",
+            self.position_size,
+            expected.position_size,
+            tolerance.size,
+        );
+        compare(
+            "open_profit",
+            self.open_profit,
+            expected.open_profit,
+            tolerance.money,
+        );
+        compare(
+            "net_profit",
+            self.net_profit,
+            expected.net_profit,
+            tolerance.money,
+        );
+        compare(
+            "gross_profit",
+            self.gross_profit,
+            expected.gross_profit,
+            tolerance.money,
+        );
+        compare(
+            "gross_loss",
+            self.gross_loss,
+            expected.gross_loss,
+            tolerance.money,
+        );
+        compare(
+            "open_trades",
+            self.open_trades.map_or(f64::NAN, |x| x as f64),
+            expected.open_trades.map_or(f64::NAN, |x| x as f64),
+            0.5,
+        );
+        compare(
+            "closed_trades",
+            self.closed_trades.map_or(f64::NAN, |x| x as f64),
+            expected.closed_trades.map_or(f64::NAN, |x| x as f64),
+            0.5,
+        );
+        compare(
+            "winning_trades",
+            self.winning_trades.map_or(f64::NAN, |x| x as f64),
+            expected.winning_trades.map_or(f64::NAN, |x| x as f64),
+            0.5,
+        );
+        compare(
+            "losing_trades",
+            self.losing_trades.map_or(f64::NAN, |x| x as f64),
+            expected.losing_trades.map_or(f64::NAN, |x| x as f64),
+            0.5,
+        );
+        return mismatches;
+    }
+
     // pub fn assert_length(actual: &[BacktestBarDump], expected: &Backtest, len: usize) {
     //     assert_eq!(self.equity.len(), len, "equity");
     //     assert_eq!(self.net_equity.len(), len, "net_equity");
@@ -1223,7 +1731,7 @@ impl BacktestBarDump {
             expected.trades.len()
         );
         assert_eq!(
-            self.open_trades + self.closed_trades,
+            self.open_trades.unwrap_or(0) + self.closed_trades.unwrap_or(0),
             expected.trades.len(),
             "{}open_trades + closed_trades: {:?} + {:?} != {:?}",
             debug_msg_prefix,
@@ -0,0 +1,532 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use chrono::{Duration, TimeZone, Utc};
+use polars::frame::DataFrame;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    backtest::{Backtest, BacktestBarDump, BacktestConfig, BacktestMismatch, BacktestTolerance},
+    ctx::Ctx,
+    legacy::Float64Utils,
+    ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvWriter},
+    signal::Signal,
+    sym::Sym,
+    timeframe::Timeframe,
+    trade::{Trade, TradeDirection},
+    utils::{read_df, SeriesCastUtils},
+};
+
+#[derive(Debug, Error)]
+pub enum BacktestFixtureError {
+    #[error("Error reading fixture {0:?}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("Invalid fixture {0:?}: {1}")]
+    Json(PathBuf, serde_json::Error),
+    #[error("{0}")]
+    DataFrame(String),
+    #[error("Fixture has neither bars file nor inline prices")]
+    MissingBars,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BacktestFixtureSymInfo {
+    pub min_tick: f64,
+    pub min_qty: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestFixtureConfig {
+    pub initial_capital: f64,
+    #[serde(default)]
+    pub process_orders_on_close: bool,
+    /// CSV or parquet file with bars and per-bar strategy tester values, relative to the fixture file.
+    #[serde(default)]
+    pub fixture: Option<String>,
+    /// Symbol used for the replay. `BITSTAMP:BTCUSD` if missing.
+    #[serde(default)]
+    pub sym_info: Option<BacktestFixtureSymInfo>,
+    /// Inline close prices for small fixtures without a bars file. Every bar is flat at its price.
+    #[serde(default)]
+    pub price: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BacktestFixtureSignalKind {
+    /// `strategy.order`, sized in contracts by `qty`.
+    Order,
+    /// `strategy.entry` with the default 100% of equity size.
+    Entry,
+    /// `strategy.close`.
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BacktestFixtureDirection {
+    Long,
+    Short,
+}
+
+impl Into<TradeDirection> for BacktestFixtureDirection {
+    #[inline]
+    fn into(self) -> TradeDirection {
+        match self {
+            BacktestFixtureDirection::Long => TradeDirection::Long,
+            BacktestFixtureDirection::Short => TradeDirection::Short,
+        }
+    }
+}
+
+impl Into<f64> for BacktestFixtureDirection {
+    #[inline]
+    fn into(self) -> f64 {
+        match self {
+            BacktestFixtureDirection::Long => 1.0,
+            BacktestFixtureDirection::Short => -1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestFixtureSignal {
+    pub bar_index: usize,
+    pub kind: BacktestFixtureSignalKind,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub direction: Option<BacktestFixtureDirection>,
+    #[serde(default)]
+    pub qty: Option<f64>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl BacktestFixtureSignal {
+    pub fn to_signal(&self) -> Signal {
+        let direction: f64 = self
+            .direction
+            .unwrap_or(BacktestFixtureDirection::Long)
+            .into();
+        let mut signal = match self.kind {
+            BacktestFixtureSignalKind::Order => Signal::size(direction * self.qty.unwrap_or(0.0)),
+            BacktestFixtureSignalKind::Entry => Signal::equity_pct(direction),
+            BacktestFixtureSignalKind::Close => Signal::close_all(),
+        };
+        signal.set_id(self.id.clone());
+        signal.set_comment(self.comment.clone());
+        return signal;
+    }
+}
+
+/// Trade from the strategy tester "List of Trades". `size` is unsigned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestFixtureTrade {
+    #[serde(default)]
+    pub entry_id: Option<String>,
+    pub entry_bar_index: usize,
+    pub entry_price: f64,
+    #[serde(default)]
+    pub exit_id: Option<String>,
+    #[serde(default)]
+    pub exit_bar_index: Option<usize>,
+    #[serde(default)]
+    pub exit_price: Option<f64>,
+    pub size: f64,
+    pub direction: BacktestFixtureDirection,
+    pub closed: bool,
+    /// Missing for open trades in some exports.
+    #[serde(default)]
+    pub profit: Option<f64>,
+}
+
+/// Golden-file fixture exported from the TradingView strategy tester.
+///
+/// The JSON file holds the config, the signals emitted by the Pine strategy and the expected trades.
+/// Bars and per-bar strategy values (`position_size`, `equity`, `net_profit`, ...) are read from the bars file, missing columns are not compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestFixture {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub config: BacktestFixtureConfig,
+    pub signals: Vec<BacktestFixtureSignal>,
+    #[serde(default)]
+    pub trades: Vec<BacktestFixtureTrade>,
+    #[serde(skip)]
+    bars: Vec<OhlcvBar>,
+    #[serde(skip)]
+    timeframe: Timeframe,
+    #[serde(skip)]
+    expected_bars: Vec<BacktestBarDump>,
+}
+
+impl BacktestFixture {
+    pub fn load(path: &Path) -> Result<Self, BacktestFixtureError> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| BacktestFixtureError::Io(path.to_path_buf(), e))?;
+        let mut fixture: BacktestFixture = serde_json::from_str(&json)
+            .map_err(|e| BacktestFixtureError::Json(path.to_path_buf(), e))?;
+        if fixture.name.is_none() {
+            fixture.name = path.file_stem().map(|x| x.to_string_lossy().to_string());
+        }
+        if let Some(bars_path) = &fixture.config.fixture {
+            let bars_path = path.parent().unwrap_or(Path::new("")).join(bars_path);
+            let df = read_df(&bars_path).map_err(BacktestFixtureError::DataFrame)?;
+            let mut ohlcv = Ohlcv::new();
+            ohlcv
+                .read_polars(&df)
+                .map_err(|e| BacktestFixtureError::DataFrame(e.to_string()))?;
+            fixture.bars = ohlcv.bars();
+            fixture.expected_bars = expected_bars_from_df(&df);
+        } else if let Some(prices) = &fixture.config.price {
+            let start = Utc.timestamp_opt(0, 0).unwrap();
+            fixture.bars = prices
+                .iter()
+                .enumerate()
+                .map(|(i, price)| {
+                    let open_time = start + Duration::days(i as i64);
+                    OhlcvBar::new(
+                        Some(open_time),
+                        Some(open_time + Duration::days(1)),
+                        *price,
+                        *price,
+                        *price,
+                        *price,
+                        0.0,
+                    )
+                })
+                .collect();
+        } else {
+            return Err(BacktestFixtureError::MissingBars);
+        }
+        fixture.timeframe = infer_timeframe(&fixture.bars);
+        return Ok(fixture);
+    }
+
+    #[inline]
+    pub fn bars(&self) -> &[OhlcvBar] {
+        &self.bars
+    }
+
+    /// Per-bar values from the strategy tester. Empty if the fixture has no bars file.
+    #[inline]
+    pub fn expected_bars(&self) -> &[BacktestBarDump] {
+        &self.expected_bars
+    }
+
+    pub fn sym(&self) -> Sym {
+        let mut sym = Sym::btc_usd();
+        if let Some(sym_info) = self.config.sym_info {
+            sym.set_min_tick(sym_info.min_tick);
+            sym.set_min_qty(sym_info.min_qty);
+        }
+        return sym;
+    }
+
+    pub fn ctx(&self) -> Rc<RefCell<Ctx>> {
+        let mut ctx = Ctx::new();
+        let mut ohlcv = Ohlcv::from_bars(self.bars.clone());
+        ohlcv.set_timeframe(self.timeframe);
+        ctx.set_ohlcv(ohlcv.into_box());
+        ctx.set_sym(self.sym());
+        return Rc::new(RefCell::new(ctx));
+    }
+
+    /// Later signals on the same bar replace earlier ones.
+    pub fn signals(&self) -> HashMap<usize, Signal> {
+        return self
+            .signals
+            .iter()
+            .map(|s| (s.bar_index, s.to_signal()))
+            .collect();
+    }
+
+    /// Replays the signals through a new `Backtest`. Returns it along with a dump of every bar.
+    pub fn run(&self) -> (Backtest, Vec<BacktestBarDump>) {
+        let ctx = self.ctx();
+        let config = BacktestConfig::new(
+            self.config.initial_capital,
+            self.config.process_orders_on_close,
+        );
+        let mut bt = Backtest::new(ctx.clone(), config);
+        let signals = self.signals();
+        let mut dumps = Vec::with_capacity(self.bars.len());
+        loop {
+            let bar_index = match ctx.borrow_mut().next() {
+                Some(bar_index) => bar_index,
+                None => break,
+            };
+            bt.on_bar_open();
+            if let Some(signal) = signals.get(&bar_index) {
+                bt.signal(signal.clone());
+            }
+            bt.on_bar_close();
+            dumps.push(bt.dump_bar());
+        }
+        return (bt, dumps);
+    }
+
+    /// Replays the fixture and diffs every bar and trade against the expected values.
+    pub fn compare(&self, tolerance: &BacktestTolerance) -> BacktestFixtureReport {
+        let (bt, dumps) = self.run();
+        let mut mismatches = vec![];
+        for expected in &self.expected_bars {
+            match dumps.get(expected.bar_index) {
+                Some(actual) => mismatches.extend(actual.diff(expected, tolerance)),
+                None => mismatches.push(BacktestMismatch::value(
+                    Some(expected.bar_index),
+                    "bar",
+                    "present",
+                    "missing",
+                )),
+            }
+        }
+        let mut actual_trades: Vec<&Trade> = bt.trades();
+        actual_trades.sort_by_key(|t| !t.is_closed());
+        let mut expected_trades: Vec<&BacktestFixtureTrade> = self.trades.iter().collect();
+        expected_trades.sort_by_key(|t| !t.closed);
+        if actual_trades.len() != expected_trades.len() {
+            mismatches.push(BacktestMismatch::value(
+                None,
+                "trades",
+                expected_trades.len(),
+                actual_trades.len(),
+            ));
+        }
+        let min_tick = self.sym().min_tick();
+        for (i, (actual, expected)) in actual_trades.iter().zip(expected_trades).enumerate() {
+            mismatches.extend(diff_trade(i, actual, expected, tolerance, min_tick));
+        }
+        return BacktestFixtureReport {
+            name: self.name.clone().unwrap_or_default(),
+            bars: self.expected_bars.len(),
+            trades: self.trades.len(),
+            mismatches,
+        };
+    }
+}
+
+/// Spacing of the first two bars. Fixtures don't store the chart timeframe.
+fn infer_timeframe(bars: &[OhlcvBar]) -> Timeframe {
+    if bars.len() < 2 {
+        return Timeframe::default();
+    }
+    return match (bars[0].open_time(), bars[1].open_time()) {
+        (Some(a), Some(b)) => (*b - *a).try_into().unwrap_or_default(),
+        _ => Timeframe::default(),
+    };
+}
+
+fn expected_bars_from_df(df: &DataFrame) -> Vec<BacktestBarDump> {
+    let cols = df.get_column_names();
+    let column = |name: &str| -> Vec<f64> {
+        if cols.iter().any(|c| *c == name) {
+            return df.column(name).unwrap().to_f64();
+        }
+        return vec![f64::NAN; df.height()];
+    };
+    let count = |values: &[f64], i: usize| -> Option<usize> {
+        values.get(i).filter(|x| !x.is_nan()).map(|x| *x as usize)
+    };
+    if !cols.iter().any(|c| *c == "bar_index") {
+        return vec![];
+    }
+    let bar_index = column("bar_index");
+    let equity = column("equity");
+    let net_equity = column("net_equity");
+    let position_size = column("position_size");
+    let open_profit = column("open_profit");
+    let net_profit = column("net_profit");
+    let gross_profit = column("gross_profit");
+    let gross_loss = column("gross_loss");
+    let open_trades = column("open_trades");
+    let closed_trades = column("closed_trades");
+    let winning_trades = column("winning_trades");
+    let losing_trades = column("losing_trades");
+    return (0..df.height())
+        .map(|i| BacktestBarDump {
+            bar_index: bar_index[i] as usize,
+            equity: equity[i],
+            net_equity: net_equity[i],
+            position_size: position_size[i],
+            open_profit: open_profit[i],
+            net_profit: net_profit[i],
+            gross_profit: gross_profit[i],
+            gross_loss: gross_loss[i],
+            open_trades: count(&open_trades, i),
+            closed_trades: count(&closed_trades, i),
+            winning_trades: count(&winning_trades, i),
+            losing_trades: count(&losing_trades, i),
+            trades: vec![],
+        })
+        .collect();
+}
+
+fn diff_trade(
+    index: usize,
+    actual: &Trade,
+    expected: &BacktestFixtureTrade,
+    tolerance: &BacktestTolerance,
+    min_tick: f64,
+) -> Vec<BacktestMismatch> {
+    let price_eps = tolerance.price * min_tick;
+    let bar_index = Some(expected.entry_bar_index);
+    let field = |name: &str| format!("trades[{}].{}", index, name);
+    let mut mismatches = vec![];
+    let mut compare = |name: &str, actual: f64, expected: f64, eps: f64| {
+        if !actual.compare_with_precision(expected, eps) {
+            mismatches.push(BacktestMismatch::new(
+                bar_index,
+                &field(name),
+                expected,
+                actual,
+            ));
+        }
+    };
+    let entry = actual.entry();
+    let exit = actual.exit();
+    compare(
+        "entry_bar_index",
+        entry.map_or(f64::NAN, |x| x.fill_bar_index() as f64),
+        expected.entry_bar_index as f64,
+        0.5,
+    );
+    compare(
+        "entry_price",
+        entry.map_or(f64::NAN, |x| x.price()),
+        expected.entry_price,
+        price_eps,
+    );
+    compare(
+        "exit_bar_index",
+        exit.map_or(f64::NAN, |x| x.fill_bar_index() as f64),
+        expected.exit_bar_index.map_or(f64::NAN, |x| x as f64),
+        0.5,
+    );
+    compare(
+        "exit_price",
+        exit.map_or(f64::NAN, |x| x.price()),
+        expected.exit_price.unwrap_or(f64::NAN),
+        price_eps,
+    );
+    compare("size", actual.size().abs(), expected.size, tolerance.size);
+    if let Some(profit) = expected.profit {
+        compare("profit", actual.pnl(), profit, tolerance.money);
+    }
+
+    let direction: TradeDirection = expected.direction.into();
+    if actual.direction() != direction {
+        mismatches.push(BacktestMismatch::value(
+            bar_index,
+            &field("direction"),
+            direction,
+            actual.direction(),
+        ));
+    }
+    if actual.is_closed() != expected.closed {
+        mismatches.push(BacktestMismatch::value(
+            bar_index,
+            &field("closed"),
+            expected.closed,
+            actual.is_closed(),
+        ));
+    }
+    let entry_id = entry.and_then(|x| x.id()).cloned();
+    if entry_id != expected.entry_id {
+        mismatches.push(BacktestMismatch::value(
+            bar_index,
+            &field("entry_id"),
+            expected.entry_id.clone(),
+            entry_id,
+        ));
+    }
+    let exit_id = exit.and_then(|x| x.id()).cloned();
+    if exit_id != expected.exit_id {
+        mismatches.push(BacktestMismatch::value(
+            bar_index,
+            &field("exit_id"),
+            expected.exit_id.clone(),
+            exit_id,
+        ));
+    }
+    return mismatches;
+}
+
+/// Result of `BacktestFixture::compare`. Printing it gives a summary per field followed by the first mismatches.
+#[derive(Debug, Clone)]
+pub struct BacktestFixtureReport {
+    pub name: String,
+    /// Number of compared bars.
+    pub bars: usize,
+    /// Number of expected trades.
+    pub trades: usize,
+    pub mismatches: Vec<BacktestMismatch>,
+}
+
+impl BacktestFixtureReport {
+    /// Maximum number of mismatches listed by `Display`.
+    pub const MAX_LISTED: usize = 25;
+
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Panics with the report if there are any mismatches.
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "{}", self);
+    }
+}
+
+impl std::fmt::Display for BacktestFixtureReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(
+                f,
+                "{}: ok ({} bars, {} trades)",
+                self.name, self.bars, self.trades
+            );
+        }
+        writeln!(
+            f,
+            "{}: {} mismatches ({} bars, {} trades)",
+            self.name,
+            self.mismatches.len(),
+            self.bars,
+            self.trades
+        )?;
+        // per field summary, in order of first occurrence
+        let mut fields: Vec<(&str, usize, Option<usize>)> = vec![];
+        for mismatch in &self.mismatches {
+            let field = mismatch.field.as_str();
+            match fields.iter_mut().find(|(f, _, _)| *f == field) {
+                Some((_, count, _)) => *count += 1,
+                None => fields.push((field, 1, mismatch.bar_index)),
+            }
+        }
+        for (field, count, first_bar_index) in fields {
+            match first_bar_index {
+                Some(bar_index) => writeln!(
+                    f,
+                    "  {}: {} mismatches, first at bar {}",
+                    field, count, bar_index
+                )?,
+                None => writeln!(f, "  {}: {} mismatches", field, count)?,
+            }
+        }
+        writeln!(f)?;
+        for mismatch in self.mismatches.iter().take(Self::MAX_LISTED) {
+            writeln!(f, "  {}", mismatch)?;
+        }
+        if self.mismatches.len() > Self::MAX_LISTED {
+            writeln!(f, "  ... {} more", self.mismatches.len() - Self::MAX_LISTED)?;
+        }
+        return Ok(());
+    }
+}
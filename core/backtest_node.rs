@@ -1,5 +1,6 @@
 use crate::{
-    backtest::{Backtest, BacktestConfig},
+    backtest::{Backtest, BacktestConfig, BacktestState},
+    pine::PineExportConfig,
     ctx_node::{NodeCtx, NodeCtxSkip},
     signal_node::NodeSignal,
     trade_node::NodeTrade,
@@ -21,6 +22,16 @@ impl NodeBacktest {
     pub fn inner(&self) -> &Rc<RefCell<Backtest>> {
         &self.inner
     }
+
+    #[inline]
+    fn restore(ctx: &NodeCtx, state: BacktestState) -> Result<Self> {
+        let bt = Backtest::restore(ctx.inner().clone(), state)
+            .map_err(|e| Error::from_reason(e.to_string()))?;
+        return Ok(Self {
+            inner: Rc::new(RefCell::new(bt)),
+            ctx: ctx.clone(),
+        });
+    }
 }
 
 #[napi]
@@ -33,7 +44,7 @@ impl NodeBacktest {
         process_orders_on_close: Option<bool>,
         risk_free_rate: Option<f64>,
         annualization_factor: Option<f64>,
-    ) -> Self {
+    ) -> Result<Self> {
         let initial_capital = initial_capital.unwrap_or(1000.0);
         let process_orders_on_close = process_orders_on_close.unwrap_or(false);
         let mut config = BacktestConfig::default();
@@ -41,10 +52,12 @@ impl NodeBacktest {
         config.set_process_orders_on_close(process_orders_on_close);
         config.set_risk_free_rate(risk_free_rate.unwrap_or(f64::NAN));
         config.set_annualization_factor(annualization_factor.unwrap_or(f64::NAN));
-        Self {
-            inner: Rc::new(RefCell::new(Backtest::new(ctx.inner().clone(), config))),
+        let backtest = Backtest::try_new(ctx.inner().clone(), config)
+            .map_err(|e| Error::from_reason(e.to_string()))?;
+        Ok(Self {
+            inner: Rc::new(RefCell::new(backtest)),
             ctx: ctx.clone(),
-        }
+        })
     }
 
     #[napi(getter = initialCapital)]
@@ -77,6 +90,12 @@ impl NodeBacktest {
         self.inner.borrow().equity_list().to_vec()
     }
 
+    #[napi(getter = equityArray)]
+    #[inline]
+    pub fn node_equity_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.borrow().equity_list().to_vec())
+    }
+
     #[napi(getter = netEquity)]
     #[inline]
     pub fn node_net_equity(&self) -> f64 {
@@ -89,6 +108,12 @@ impl NodeBacktest {
         self.inner.borrow().net_equity_list().to_vec()
     }
 
+    #[napi(getter = netEquityArray)]
+    #[inline]
+    pub fn node_net_equity_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.borrow().net_equity_list().to_vec())
+    }
+
     #[napi(getter = pnlList)]
     #[inline]
     pub fn node_pnl_list(&self) -> Vec<f64> {
@@ -179,6 +204,12 @@ impl NodeBacktest {
         self.inner.borrow().returns_list().to_vec()
     }
 
+    #[napi(getter = returnsArray)]
+    #[inline]
+    pub fn node_returns_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.borrow().returns_list())
+    }
+
     #[napi(js_name = "sharpeRatio")]
     #[inline]
     pub fn node_sharpe_ratio(&self) -> f64 {
@@ -296,6 +327,47 @@ impl NodeBacktest {
         self.inner.borrow_mut().on_bar_close();
     }
 
+    #[napi(js_name = "onBarUpdate")]
+    #[inline]
+    pub fn node_on_bar_update(&mut self) {
+        self.inner.borrow_mut().on_bar_update();
+    }
+
+    #[napi(js_name = "snapshotJson")]
+    #[inline]
+    pub fn node_snapshot_json(&self) -> Result<String> {
+        let state = self.inner.borrow().snapshot();
+        return state
+            .to_json()
+            .map_err(|e| Error::from_reason(e.to_string()));
+    }
+
+    #[napi(js_name = "snapshotBytes")]
+    #[inline]
+    pub fn node_snapshot_bytes(&self) -> Result<Buffer> {
+        let state = self.inner.borrow().snapshot();
+        let bytes = state
+            .to_bytes()
+            .map_err(|e| Error::from_reason(e.to_string()))?;
+        return Ok(bytes.into());
+    }
+
+    #[napi(js_name = "restoreJson")]
+    #[inline]
+    pub fn node_restore_json(ctx: &NodeCtx, json: String) -> Result<NodeBacktest> {
+        let state =
+            BacktestState::from_json(&json).map_err(|e| Error::from_reason(e.to_string()))?;
+        return NodeBacktest::restore(ctx, state);
+    }
+
+    #[napi(js_name = "restoreBytes")]
+    #[inline]
+    pub fn node_restore_bytes(ctx: &NodeCtx, data: Buffer) -> Result<NodeBacktest> {
+        let state =
+            BacktestState::from_bytes(&data).map_err(|e| Error::from_reason(e.to_string()))?;
+        return NodeBacktest::restore(ctx, state);
+    }
+
     #[napi(js_name = "signal")]
     #[inline]
     pub fn node_signal(&mut self, signal: &NodeSignal) {
@@ -326,10 +398,19 @@ impl NodeBacktest {
         return Some(next.unwrap() as i32);
     }
 
+    /// `config` is a `PineExportConfig` object, missing keys use the defaults.
     #[napi(js_name = "toPine")]
     #[inline]
-    pub fn node_to_pine(&self) -> String {
-        self.inner.borrow().to_pine()
+    pub fn node_to_pine(&self, config: Option<serde_json::Value>) -> Result<String> {
+        let config: PineExportConfig = match config {
+            Some(config) => serde_json::from_value(config)
+                .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?,
+            None => PineExportConfig::default(),
+        };
+        self.inner
+            .borrow()
+            .to_pine_with(&config)
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
     }
 
     #[napi(js_name = "display")]
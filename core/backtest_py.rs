@@ -1,12 +1,14 @@
 use crate::ctx_py::{PyCtx, PyCtxSkip};
 use crate::signal_py::PySignal;
 use crate::{
-    backtest::{Backtest, BacktestConfig},
+    backtest::{Backtest, BacktestConfig, BacktestState},
+    pine::PineExportConfig,
     trade::Trade,
 };
-use pyo3::exceptions::PyStopIteration;
+use numpy::{IntoPyArray, PyArray1};
+use pyo3::exceptions::{PyStopIteration, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyBytes, PyDict};
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 use std::collections::HashMap;
 use std::{cell::RefCell, rc::Rc};
@@ -24,6 +26,16 @@ impl PyBacktest {
     pub fn inner(&self) -> &Rc<RefCell<Backtest>> {
         &self.inner
     }
+
+    #[inline]
+    fn restore(ctx: PyCtx, state: BacktestState) -> PyResult<Self> {
+        let bt = Backtest::restore(ctx.inner().clone(), state)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        return Ok(Self {
+            inner: Rc::new(RefCell::new(bt)),
+            ctx,
+        });
+    }
 }
 
 #[gen_stub_pymethods]
@@ -39,17 +51,19 @@ impl PyBacktest {
         debug: bool,
         risk_free_rate: Option<f64>,
         annualization_factor: Option<f64>,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut config = BacktestConfig::default();
         config.set_initial_capital(initial_capital);
         config.set_process_orders_on_close(process_orders_on_close);
         config.set_debug(debug);
         config.set_risk_free_rate(risk_free_rate.unwrap_or(f64::NAN));
         config.set_annualization_factor(annualization_factor.unwrap_or(f64::NAN));
-        Self {
-            inner: Rc::new(RefCell::new(Backtest::new(ctx.inner().clone(), config))),
+        let backtest = Backtest::try_new(ctx.inner().clone(), config)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self {
+            inner: Rc::new(RefCell::new(backtest)),
             ctx,
-        }
+        })
     }
 
     #[getter(initial_capital)]
@@ -82,6 +96,13 @@ impl PyBacktest {
         self.inner.borrow().equity_list().to_vec()
     }
 
+    #[getter(equity_array)]
+    #[inline]
+    #[doc = "Numpy copy of the list, bars processed later are not reflected."]
+    pub fn py_equity_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.borrow().equity_list())
+    }
+
     #[getter(net_equity)]
     #[inline]
     #[doc = "`initial_capital + net_profit`"]
@@ -95,6 +116,13 @@ impl PyBacktest {
         self.inner.borrow().net_equity_list().to_vec()
     }
 
+    #[getter(net_equity_array)]
+    #[inline]
+    #[doc = "Numpy copy of the list, bars processed later are not reflected."]
+    pub fn py_net_equity_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.borrow().net_equity_list())
+    }
+
     #[getter(pnl_list)]
     #[inline]
     pub fn py_pnl_list(&self) -> Vec<f64> {
@@ -185,6 +213,13 @@ impl PyBacktest {
         self.inner.borrow().returns_list().to_vec()
     }
 
+    #[getter(returns_array)]
+    #[inline]
+    #[doc = "Computed on each call, the numpy array owns the returns."]
+    pub fn py_returns_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.inner.borrow().returns_list().into_pyarray(py)
+    }
+
     #[pyo3(name = "sharpe_ratio")]
     #[inline]
     pub fn py_sharpe_ratio(&self) -> f64 {
@@ -255,6 +290,49 @@ impl PyBacktest {
         self.inner.borrow_mut().on_bar_close();
     }
 
+    #[pyo3(name = "on_bar_update")]
+    #[inline]
+    pub fn py_on_bar_update(&mut self) {
+        self.inner.borrow_mut().on_bar_update();
+    }
+
+    #[pyo3(name = "snapshot_json")]
+    #[inline]
+    pub fn py_snapshot_json(&self) -> PyResult<String> {
+        let state = self.inner.borrow().snapshot();
+        return state
+            .to_json()
+            .map_err(|e| PyValueError::new_err(e.to_string()));
+    }
+
+    #[pyo3(name = "snapshot_bytes")]
+    #[inline]
+    pub fn py_snapshot_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let state = self.inner.borrow().snapshot();
+        let bytes = state
+            .to_bytes()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        return Ok(PyBytes::new(py, &bytes));
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "restore_json")]
+    #[inline]
+    pub fn py_restore_json(ctx: PyCtx, json: &str) -> PyResult<Self> {
+        let state =
+            BacktestState::from_json(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        return PyBacktest::restore(ctx, state);
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "restore_bytes")]
+    #[inline]
+    pub fn py_restore_bytes(ctx: PyCtx, data: &Bound<'_, PyBytes>) -> PyResult<Self> {
+        let state = BacktestState::from_bytes(data.as_bytes())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        return PyBacktest::restore(ctx, state);
+    }
+
     #[pyo3(name = "signal")]
     #[inline]
     pub fn py_signal(&mut self, signal: PySignal) {
@@ -307,10 +385,19 @@ impl PyBacktest {
         s
     }
 
-    #[pyo3(name = "to_pine")]
+    /// `config` is a `PineExportConfig` dict, missing keys use the defaults.
+    #[pyo3(name = "to_pine", signature = (config=None))]
     #[inline]
-    pub fn py_to_pine(&self) -> String {
-        self.inner.borrow().to_pine()
+    pub fn py_to_pine(&self, config: Option<&Bound<'_, PyAny>>) -> PyResult<String> {
+        let config: PineExportConfig = match config {
+            Some(config) => pythonize::depythonize(config)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+            None => PineExportConfig::default(),
+        };
+        self.inner
+            .borrow()
+            .to_pine_with(&config)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     #[pyo3(name = "display")]
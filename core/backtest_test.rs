@@ -3,11 +3,455 @@
 #[allow(dead_code)]
 #[cfg(test)]
 #[cfg(feature = "polars")]
+#[cfg(feature = "json")]
 mod test {
-    // @TODO
+    use crate::{
+        backtest::{BacktestBarDump, BacktestTolerance},
+        backtest_fixture::BacktestFixture,
+        legacy::format_pace_fixture_path,
+    };
+
+    fn assert_fixture(name: &str) {
+        let path = format_pace_fixture_path(&format!("backtest/{}.json", name));
+        let fixture = BacktestFixture::load(&path).unwrap();
+        fixture.compare(&BacktestTolerance::default()).assert_ok();
+    }
+
+    #[test]
+    pub fn simple() {
+        assert_fixture("simple");
+    }
 
     #[test]
     pub fn tv_order_contracts() {
-        assert_eq!(true, true, "test");
+        assert_fixture("tv_order_contracts");
+    }
+
+    #[test]
+    pub fn tv_equity_pct() {
+        assert_fixture("tv_equity_pct");
+    }
+
+    #[test]
+    pub fn tv_entry_only_duplicates() {
+        assert_fixture("tv_entry_only_duplicates");
+    }
+
+    #[test]
+    pub fn tv_entry_close_duplicates() {
+        assert_fixture("tv_entry_close_duplicates");
+    }
+
+    #[test]
+    pub fn missing_values_are_not_compared() {
+        let path = format_pace_fixture_path("backtest/simple.json");
+        let (_, dumps) = BacktestFixture::load(&path).unwrap().run();
+        let tolerance = BacktestTolerance::default();
+        let mut expected = BacktestBarDump {
+            bar_index: dumps[0].bar_index,
+            ..BacktestBarDump::default()
+        };
+        assert!(dumps[0].diff(&expected, &tolerance).is_empty());
+        expected.open_trades = Some(5);
+        let mismatches = dumps[0].diff(&expected, &tolerance);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].field, "open_trades");
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod state_test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig, BacktestState, BacktestStateError},
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        signal::Signal,
+        sym::Sym,
+        timeframe::Timeframe,
+    };
+
+    fn create_ctx() -> Rc<RefCell<Ctx>> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bars: Vec<OhlcvBar> = (0..20)
+            .map(|i| {
+                let open_time = start + Duration::hours(i);
+                let close = 100.0 + ((i * 7) % 11) as f64;
+                OhlcvBar::new(
+                    Some(open_time),
+                    Some(open_time + Duration::hours(1)),
+                    close - 1.0,
+                    close + 1.0,
+                    close - 2.0,
+                    close,
+                    1.0,
+                )
+            })
+            .collect();
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Hours(1));
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        ctx.set_sym(Sym::btc_usd());
+        return Rc::new(RefCell::new(ctx));
+    }
+
+    fn signals() -> Vec<Option<Signal>> {
+        return (0..20)
+            .map(|i| match i {
+                2 => Some(Signal::equity_pct(0.5)),
+                6 => Some(Signal::equity_pct(-1.0)),
+                // pending order at the snapshot
+                9 => Some(Signal::size(0.5)),
+                14 => Some(Signal::close_all()),
+                _ => None,
+            })
+            .collect();
+    }
+
+    fn resume(encode: impl Fn(&BacktestState) -> BacktestState) {
+        let mut expected = Backtest::new(create_ctx(), BacktestConfig::default());
+        expected.signal_list(signals());
+
+        let mut bt = Backtest::new(create_ctx(), BacktestConfig::default());
+        bt.signal_list(signals()[0..10].to_vec());
+        let state = encode(&bt.snapshot());
+
+        let ctx = create_ctx();
+        let mut bt = Backtest::restore(ctx.clone(), state).unwrap();
+        assert_eq!(ctx.borrow().bar_index(), 9);
+        bt.signal_list(signals()[10..].to_vec());
+
+        assert_eq!(bt.closed_trades(), expected.closed_trades());
+        assert_eq!(bt.open_trades(), expected.open_trades());
+        assert_eq!(bt.equity_list(), expected.equity_list());
+        assert_eq!(bt.net_profit(), expected.net_profit());
+        assert_eq!(bt.position_size(), expected.position_size());
+    }
+
+    #[test]
+    pub fn restore_json() {
+        resume(|state| BacktestState::from_json(&state.to_json().unwrap()).unwrap());
+    }
+
+    #[test]
+    pub fn restore_bytes() {
+        resume(|state| BacktestState::from_bytes(&state.to_bytes().unwrap()).unwrap());
+    }
+
+    #[test]
+    pub fn restore_version_1() {
+        fn strip_comments(value: &mut serde_json::Value) {
+            match value {
+                serde_json::Value::Object(map) => {
+                    map.remove("comment");
+                    map.values_mut().for_each(strip_comments);
+                }
+                serde_json::Value::Array(items) => items.iter_mut().for_each(strip_comments),
+                _ => {}
+            }
+        }
+        resume(|state| {
+            let mut value: serde_json::Value =
+                serde_json::from_str(&state.to_json().unwrap()).unwrap();
+            strip_comments(&mut value);
+            value["version"] = 1.into();
+            return BacktestState::from_json(&value.to_string()).unwrap();
+        });
+    }
+
+    #[test]
+    pub fn restore_unsupported_version() {
+        let mut bt = Backtest::new(create_ctx(), BacktestConfig::default());
+        bt.signal_list(signals()[0..10].to_vec());
+        let mut bytes = bt.snapshot().to_bytes().unwrap();
+        bytes[0..4].copy_from_slice(&1u32.to_le_bytes());
+        assert!(matches!(
+            BacktestState::from_bytes(&bytes),
+            Err(BacktestStateError::UnsupportedVersion(1))
+        ));
+    }
+
+    #[test]
+    pub fn restore_invalid() {
+        let mut bt = Backtest::new(create_ctx(), BacktestConfig::default());
+        bt.signal_list(signals()[0..10].to_vec());
+        let state = bt.snapshot();
+
+        let ctx = create_ctx();
+        let mut sym = Sym::btc_usd();
+        sym.set_min_tick(0.0);
+        ctx.borrow_mut().set_sym(sym);
+        assert!(matches!(
+            Backtest::restore(ctx.clone(), state),
+            Err(BacktestStateError::Sym(_))
+        ));
+        assert!(!ctx.borrow().is_initialized());
+    }
+}
+
+#[cfg(test)]
+mod expiry_test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        signal::Signal,
+        sym::{SettlementType, Sym, SymDerivative, SymKind},
+        timeframe::Timeframe,
+    };
+
+    fn run(settlement: SettlementType) -> Backtest {
+        let start = Utc.with_ymd_and_hms(2024, 3, 15, 0, 0, 0).unwrap();
+        let bars: Vec<OhlcvBar> = (0..10)
+            .map(|i| {
+                let open_time = start + Duration::hours(i);
+                let close = 100.0 + i as f64;
+                OhlcvBar::new(
+                    Some(open_time),
+                    Some(open_time + Duration::hours(1)),
+                    close - 1.0,
+                    close,
+                    close - 1.0,
+                    close,
+                    1.0,
+                )
+            })
+            .collect();
+        let mut sym = Sym::default();
+        sym.set_id(Some("CME:ESH2024".to_string()));
+        sym.set_kind(SymKind::Future);
+        sym.set_min_tick(0.25);
+        sym.set_min_qty(1.0);
+        let mut derivative =
+            SymDerivative::future(start + Duration::hours(5), Some("ES".to_string()), 50.0);
+        derivative.settlement = settlement;
+        derivative.settlement_price = Some(200.0);
+        sym.set_derivative(Some(derivative));
+
+        let mut ctx = Ctx::new();
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Hours(1));
+        ctx.set_ohlcv(ohlcv.into_box());
+        ctx.set_sym(sym);
+        let mut bt = Backtest::new(Rc::new(RefCell::new(ctx)), BacktestConfig::default());
+        bt.signal_list(
+            (0..10)
+                .map(|i| match i {
+                    1 => Some(Signal::size(2.0)),
+                    // after expiry, dropped
+                    6 => Some(Signal::size(1.0)),
+                    _ => None,
+                })
+                .collect(),
+        );
+        return bt;
+    }
+
+    #[test]
+    pub fn closes_at_expiry() {
+        for (settlement, pnl) in [
+            // 50 per point
+            (SettlementType::Cash, 2.0 * (200.0 - 101.0) * 50.0),
+            (SettlementType::Physical, 2.0 * (104.0 - 101.0) * 50.0),
+        ] {
+            let bt = run(settlement);
+            assert_eq!(bt.position_size(), 0.0);
+            assert!(bt.open_trades().is_empty());
+            assert_eq!(bt.closed_trades().len(), 1);
+            let trade = &bt.closed_trades()[0];
+            let exit = trade.exit().unwrap();
+            assert_eq!(exit.id().map(|x| x.as_str()), Some("expiry"));
+            assert_eq!(exit.fill_bar_index(), 4);
+            assert_eq!(trade.pnl(), pnl);
+            assert_eq!(bt.net_profit(), pnl);
+            assert_eq!(bt.equity(), bt.initial_capital() + pnl);
+        }
+    }
+}
+
+#[cfg(test)]
+mod streaming_test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        ctx::Ctx,
+        ohlcv::{OhlcvBar, OhlcvReader, OhlcvWriter, RcOhlcv},
+        security::Security,
+        signal::Signal,
+        sym::Sym,
+        timeframe::Timeframe,
+    };
+
+    fn hour(i: i64) -> DateTime<Utc> {
+        return Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(i);
+    }
+
+    fn bar(i: i64, open: f64, high: f64, low: f64, close: f64) -> OhlcvBar {
+        return OhlcvBar::new(
+            Some(hour(i)),
+            Some(hour(i + 1)),
+            open,
+            high,
+            low,
+            close,
+            1.0,
+        );
+    }
+
+    #[test]
+    pub fn realtime_bar() {
+        let ohlcv = RcOhlcv::from_bars(
+            (0..3)
+                .map(|i| {
+                    let close = 100.0 + i as f64;
+                    bar(i, close - 1.0, close + 1.0, close - 2.0, close)
+                })
+                .collect(),
+        );
+        ohlcv.set_timeframe(Timeframe::Hours(1));
+        let mut sym = Sym::default();
+        sym.set_min_tick(0.01);
+        sym.set_min_qty(1.0);
+        let mut ctx = Ctx::new();
+        ctx.set_sym(sym);
+        ctx.set_ohlcv(ohlcv.clone().into_box());
+        let ctx = Rc::new(RefCell::new(ctx));
+        let mut bt = Backtest::new(ctx.clone(), BacktestConfig::default());
+        let mut security = Security::new(ctx.clone(), Timeframe::Hours(2)).unwrap();
+
+        // filled at the open of bar 2
+        bt.signal_list(vec![None, Some(Signal::size(1.0)), None]);
+        assert!(ctx.borrow().is_last_bar());
+        assert_eq!(bt.open_trades().len(), 1);
+        assert_eq!(security.completed_len(), 1);
+
+        let mut writer = ohlcv.clone();
+        writer.push(bar(3, 102.0, 103.0, 101.0, 102.0));
+        assert!(!ctx.borrow().is_last_bar());
+        ctx.borrow_mut().next();
+        bt.on_bar_open();
+        assert!(ctx.borrow().is_last_bar());
+        assert_eq!(bt.equity(), 1001.0);
+
+        writer.set(3, bar(3, 102.0, 125.0, 101.0, 120.0));
+        bt.on_bar_update();
+        assert_eq!(bt.equity(), 1019.0);
+        assert_eq!(security.completed(0).unwrap().high(), 125.0);
+
+        // pending until the next bar opens, updates do not fill it
+        bt.signal(Signal::close_all());
+        writer.set(3, bar(3, 102.0, 121.0, 99.0, 110.0));
+        bt.on_bar_update();
+        assert_eq!(bt.equity_list().len(), 4);
+        assert_eq!(bt.equity(), 1009.0);
+        assert_eq!(bt.open_trades().len(), 1);
+        assert!(bt.closed_trades().is_empty());
+        // the updated bar replaced its previous version instead of being merged twice
+        let two_hours = security.completed(0).unwrap();
+        assert_eq!(security.completed_len(), 2);
+        assert_eq!(two_hours.high(), 121.0);
+        assert_eq!(two_hours.close(), 110.0);
+        assert_eq!(two_hours.volume(), 2.0);
+
+        bt.on_bar_close();
+        assert_eq!(bt.open_trades().len(), 1);
+        writer.push(bar(4, 111.0, 112.0, 110.0, 111.0));
+        ctx.borrow_mut().next();
+        bt.on_bar_open();
+        bt.on_bar_close();
+        assert!(bt.open_trades().is_empty());
+        assert_eq!(bt.closed_trades()[0].pnl(), 10.0);
+        assert_eq!(bt.equity_list().len(), 5);
+        assert_eq!(bt.equity(), 1010.0);
+    }
+}
+
+#[cfg(test)]
+mod fees_test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        signal::Signal,
+        sym::{Sym, SymFees},
+        timeframe::Timeframe,
+    };
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    pub fn entry_and_exit_pay_fees() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bars: Vec<OhlcvBar> = (0..6)
+            .map(|i| {
+                let open_time = start + Duration::hours(i);
+                let price = 100.0 + 10.0 * i as f64;
+                OhlcvBar::new(
+                    Some(open_time),
+                    Some(open_time + Duration::hours(1)),
+                    price,
+                    price,
+                    price,
+                    price,
+                    1.0,
+                )
+            })
+            .collect();
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Hours(1));
+        let mut sym = Sym::default();
+        sym.set_min_tick(0.01);
+        sym.set_min_qty(1.0);
+        sym.set_fees(SymFees {
+            maker: 0.0,
+            taker: 0.001,
+            per_qty: 0.5,
+        });
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        ctx.set_sym(sym);
+        let mut bt = Backtest::new(Rc::new(RefCell::new(ctx)), BacktestConfig::default());
+        bt.signal_list(vec![
+            Some(Signal::size(2.0)),
+            None,
+            Some(Signal::size(-1.0)),
+            None,
+            Some(Signal::close_all()),
+            None,
+        ]);
+
+        // 2 at 110, 1 at 130, 1 at 150
+        let trades = bt.closed_trades();
+        assert_eq!(trades.len(), 2);
+        assert_close(trades[0].commission(), 0.61 + 0.63);
+        assert_close(trades[0].pnl(), 20.0 - 0.61 - 0.63);
+        assert_close(trades[1].commission(), 0.61 + 0.65);
+        assert_close(trades[1].pnl(), 40.0 - 0.61 - 0.65);
+        assert_close(bt.net_profit(), 60.0 - 2.5);
+        // the entry fee is paid as soon as the trade opens
+        assert_close(bt.equity_list()[1], 1000.0 - 1.22);
     }
 }
@@ -2,7 +2,8 @@ use crate::ctx_wasm::{WasmCtx, WasmCtxSkip};
 use crate::signal::Signal;
 use crate::signal_wasm::WasmSignal;
 use crate::{
-    backtest::{Backtest, BacktestConfig},
+    backtest::{Backtest, BacktestConfig, BacktestState},
+    pine::PineExportConfig,
     trade::Trade,
 };
 use js_sys::{Object, Reflect};
@@ -32,6 +33,16 @@ impl WasmBacktest {
     pub fn inner(&self) -> &Rc<RefCell<Backtest>> {
         &self.inner
     }
+
+    #[inline]
+    fn restore(ctx: WasmCtx, state: BacktestState) -> Result<WasmBacktest, JsError> {
+        let bt = Backtest::restore(ctx.inner().clone(), state)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        return Ok(Self {
+            inner: Rc::new(RefCell::new(bt)),
+            ctx,
+        });
+    }
 }
 
 #[wasm_bindgen(js_class=Backtest)]
@@ -44,7 +55,7 @@ impl WasmBacktest {
         process_orders_on_close: Option<bool>,
         risk_free_rate: Option<f64>,
         annualization_factor: Option<f64>,
-    ) -> Self {
+    ) -> Result<WasmBacktest, JsError> {
         let initial_capital = initial_capital.unwrap_or(1000.0);
         let process_orders_on_close = process_orders_on_close.unwrap_or(false);
         let mut config = BacktestConfig::default();
@@ -52,10 +63,12 @@ impl WasmBacktest {
         config.set_process_orders_on_close(process_orders_on_close);
         config.set_risk_free_rate(risk_free_rate.unwrap_or(f64::NAN));
         config.set_annualization_factor(annualization_factor.unwrap_or(f64::NAN));
-        Self {
-            inner: Rc::new(RefCell::new(Backtest::new(ctx.inner().clone(), config))),
+        let backtest = Backtest::try_new(ctx.inner().clone(), config)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self {
+            inner: Rc::new(RefCell::new(backtest)),
             ctx,
-        }
+        })
     }
 
     #[wasm_bindgen(getter = initialCapital)]
@@ -302,6 +315,40 @@ impl WasmBacktest {
         self.inner.borrow_mut().on_bar_close();
     }
 
+    #[wasm_bindgen(js_name = "onBarUpdate")]
+    #[inline]
+    pub fn wasm_on_bar_update(&mut self) {
+        self.inner.borrow_mut().on_bar_update();
+    }
+
+    #[wasm_bindgen(js_name = "snapshotJson")]
+    #[inline]
+    pub fn wasm_snapshot_json(&self) -> Result<String, JsError> {
+        let state = self.inner.borrow().snapshot();
+        return state.to_json().map_err(|e| JsError::new(&e.to_string()));
+    }
+
+    #[wasm_bindgen(js_name = "snapshotBytes")]
+    #[inline]
+    pub fn wasm_snapshot_bytes(&self) -> Result<Vec<u8>, JsError> {
+        let state = self.inner.borrow().snapshot();
+        return state.to_bytes().map_err(|e| JsError::new(&e.to_string()));
+    }
+
+    #[wasm_bindgen(js_name = "restoreJson")]
+    #[inline]
+    pub fn wasm_restore_json(ctx: WasmCtx, json: &str) -> Result<WasmBacktest, JsError> {
+        let state = BacktestState::from_json(json).map_err(|e| JsError::new(&e.to_string()))?;
+        return WasmBacktest::restore(ctx, state);
+    }
+
+    #[wasm_bindgen(js_name = "restoreBytes")]
+    #[inline]
+    pub fn wasm_restore_bytes(ctx: WasmCtx, data: &[u8]) -> Result<WasmBacktest, JsError> {
+        let state = BacktestState::from_bytes(data).map_err(|e| JsError::new(&e.to_string()))?;
+        return WasmBacktest::restore(ctx, state);
+    }
+
     #[wasm_bindgen(js_name = "signal")]
     #[inline]
     pub fn wasm_signal(&mut self, signal: WasmSignal) {
@@ -371,10 +418,22 @@ impl WasmBacktest {
         return Some(next.unwrap());
     }
 
+    /// `config` is a `PineExportConfig` object, missing keys use the defaults.
     #[wasm_bindgen(js_name = "toPine")]
     #[inline]
-    pub fn wasm_to_pine(&self) -> String {
-        self.inner.borrow().to_pine()
+    pub fn wasm_to_pine(&self, config: JsValue) -> Result<String, JsError> {
+        let config: PineExportConfig = if config.is_undefined() || config.is_null() {
+            PineExportConfig::default()
+        } else {
+            let json: String = js_sys::JSON::stringify(&config)
+                .map_err(|_| JsError::new("Invalid Pine export config"))?
+                .into();
+            serde_json::from_str(&json).map_err(|e| JsError::new(&e.to_string()))?
+        };
+        self.inner
+            .borrow()
+            .to_pine_with(&config)
+            .map_err(|e| JsError::new(&e.to_string()))
     }
 
     #[wasm_bindgen(js_name = "display")]
@@ -0,0 +1,383 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use thiserror::Error;
+
+use crate::{
+    ohlcv::{floor_to_timeframe, hlc3, next_timeframe_boundary, Ohlcv, OhlcvBar},
+    timeframe::Timeframe,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BarBuilderError {
+    #[error("Unknown timeframe")]
+    UnknownTimeframe,
+    #[error("{0} bars require a positive min_tick")]
+    MinTick(Timeframe),
+}
+
+/// Trade count and VWAP of a built bar. The count is the number of trades, or of finer bars when built with `push_bar`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarStats {
+    pub trades: usize,
+    pub vwap: f64,
+}
+
+impl Default for BarStats {
+    fn default() -> Self {
+        Self {
+            trades: 0,
+            vwap: f64::NAN,
+        }
+    }
+}
+
+impl BarStats {
+    #[inline]
+    fn new(trades: usize, notional: f64, volume: f64) -> Self {
+        Self {
+            trades,
+            vwap: if volume > 0.0 {
+                notional / volume
+            } else {
+                f64::NAN
+            },
+        }
+    }
+}
+
+/// Builds bars of any `Timeframe` incrementally, either from raw trades or from bars of a finer timeframe.
+///
+/// - time based timeframes bucket trades and bars by their open time.
+/// - `Ticks(n)` completes a bar every `n` trades or finer bars.
+/// - `Volume(n)` and `Dollars(n)` complete a bar once the volume or `price * volume` reaches `n`. Trades and bars are never split, so the last one can overshoot. The notional of a finer bar is `volume * hlc3`.
+/// - `Ranges(n)` completes a bar when its high-low range reaches `n * min_tick`. A price outside of the range starts a new bar. Finer bars are replayed as open, high/low, low/high, close.
+/// - `Renko(n)` emits bricks of `n * min_tick`, reversals need to move two bricks. Finer bars use their close price.
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    timeframe: Timeframe,
+    min_tick: f64,
+    timezone: Tz,
+    bar: Option<OhlcvBar>,
+    count: usize,
+    volume: f64,
+    notional: f64,
+    bucket_end: Option<DateTime<Utc>>,
+    /// Bottom and top of the last brick, in ticks.
+    renko_levels: Option<(i64, i64)>,
+    completed: Vec<OhlcvBar>,
+    completed_stats: Vec<BarStats>,
+}
+
+/// `Ranges` and `Renko` bars are sized in ticks, so they need a positive `min_tick`.
+#[inline]
+pub fn validate_min_tick(timeframe: Timeframe, min_tick: f64) -> Result<(), BarBuilderError> {
+    if matches!(timeframe, Timeframe::Ranges(_) | Timeframe::Renko(_))
+        && !(min_tick > 0.0 && min_tick.is_finite())
+    {
+        return Err(BarBuilderError::MinTick(timeframe));
+    }
+    return Ok(());
+}
+
+impl BarBuilder {
+    /// `min_tick` is only used by `Ranges` and `Renko`, see `validate_min_tick`.
+    #[inline]
+    pub fn try_new(timeframe: Timeframe, min_tick: f64) -> Result<Self, BarBuilderError> {
+        if timeframe.unknown() {
+            return Err(BarBuilderError::UnknownTimeframe);
+        }
+        validate_min_tick(timeframe, min_tick)?;
+        Ok(Self {
+            timeframe,
+            min_tick,
+            timezone: Tz::UTC,
+            bar: None,
+            count: 0,
+            volume: 0.0,
+            notional: 0.0,
+            bucket_end: None,
+            renko_levels: None,
+            completed: vec![],
+            completed_stats: vec![],
+        })
+    }
+
+    #[inline]
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    #[inline]
+    pub fn min_tick(&self) -> f64 {
+        self.min_tick
+    }
+
+    /// Time zone of day, week and month boundaries of time based bars. UTC by default.
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    #[inline]
+    pub fn set_timezone(&mut self, timezone: Tz) {
+        self.timezone = timezone;
+    }
+
+    /// Bar that is still being built. Always `None` for `Renko`.
+    #[inline]
+    pub fn partial(&self) -> Option<&OhlcvBar> {
+        if matches!(self.timeframe, Timeframe::Renko(_)) {
+            return None;
+        }
+        self.bar.as_ref()
+    }
+
+    #[inline]
+    pub fn partial_stats(&self) -> Option<BarStats> {
+        self.partial()
+            .map(|_| BarStats::new(self.count, self.notional, self.volume))
+    }
+
+    #[inline]
+    pub fn completed(&self) -> &[OhlcvBar] {
+        &self.completed
+    }
+
+    /// Stats of `completed`, index by index.
+    #[inline]
+    pub fn completed_stats(&self) -> &[BarStats] {
+        &self.completed_stats
+    }
+
+    /// Removes and returns the completed bars along with their stats.
+    #[inline]
+    pub fn drain(&mut self) -> (Vec<OhlcvBar>, Vec<BarStats>) {
+        (
+            std::mem::take(&mut self.completed),
+            std::mem::take(&mut self.completed_stats),
+        )
+    }
+
+    /// Completed bars followed by the partial one.
+    #[inline]
+    pub fn finish(self) -> Ohlcv {
+        return self.finish_with_stats().0;
+    }
+
+    pub fn finish_with_stats(mut self) -> (Ohlcv, Vec<BarStats>) {
+        if let Some(bar) = self.partial().copied() {
+            let stats = self.partial_stats().unwrap();
+            self.completed.push(bar);
+            self.completed_stats.push(stats);
+        }
+        let mut ohlcv = Ohlcv::from_bars(self.completed);
+        ohlcv.set_timeframe(self.timeframe);
+        ohlcv.set_timezone(self.timezone);
+        return (ohlcv, self.completed_stats);
+    }
+
+    /// Adds a single trade. Returns the number of completed bars.
+    pub fn push_trade(&mut self, time: DateTime<Utc>, price: f64, volume: f64) -> usize {
+        let len = self.completed.len();
+        let trade = OhlcvBar::new(Some(time), Some(time), price, price, price, price, volume);
+        match self.timeframe {
+            Timeframe::Ranges(n) => self.push_range(trade, n, price * volume),
+            Timeframe::Renko(n) => self.push_renko(trade, n, price * volume),
+            _ => self.push_point(trade, price * volume),
+        }
+        return self.completed.len() - len;
+    }
+
+    /// Adds a bar of a finer timeframe. Returns the number of completed bars.
+    pub fn push_bar(&mut self, bar: &OhlcvBar) -> usize {
+        let len = self.completed.len();
+        match self.timeframe {
+            Timeframe::Ranges(n) => {
+                let open_time = bar.open_time().copied();
+                let close_time = bar.close_time().copied().or(open_time);
+                let (first, second) = if bar.close() >= bar.open() {
+                    (bar.low(), bar.high())
+                } else {
+                    (bar.high(), bar.low())
+                };
+                let volume = bar.volume() / 4.0;
+                for (time, price) in [
+                    (open_time, bar.open()),
+                    (open_time, first),
+                    (close_time, second),
+                    (close_time, bar.close()),
+                ] {
+                    let point = OhlcvBar::new(time, time, price, price, price, price, volume);
+                    self.push_range(point, n, price * volume);
+                }
+            }
+            Timeframe::Renko(n) => {
+                let mut point = *bar;
+                point.set_open(bar.close());
+                point.set_high(bar.close());
+                point.set_low(bar.close());
+                let notional = bar.volume() * hlc3(bar.high(), bar.low(), bar.close());
+                self.push_renko(point, n, notional);
+            }
+            _ => {
+                let notional = bar.volume() * hlc3(bar.high(), bar.low(), bar.close());
+                self.push_point(*bar, notional);
+            }
+        }
+        return self.completed.len() - len;
+    }
+
+    fn extend(&mut self, point: OhlcvBar, notional: f64) {
+        self.count += 1;
+        self.volume += point.volume();
+        self.notional += notional;
+        match &mut self.bar {
+            Some(bar) => {
+                bar.set_high(bar.high().max(point.high()));
+                bar.set_low(bar.low().min(point.low()));
+                bar.set_close(point.close());
+                bar.set_close_time(point.close_time().copied());
+                bar.set_volume(bar.volume() + point.volume());
+            }
+            None => self.bar = Some(point),
+        }
+    }
+
+    fn complete(&mut self) {
+        if let Some(mut bar) = self.bar.take() {
+            if self.bucket_end.is_some() {
+                bar.set_close_time(self.bucket_end);
+            }
+            self.completed.push(bar);
+            self.completed_stats
+                .push(BarStats::new(self.count, self.notional, self.volume));
+        }
+        self.count = 0;
+        self.volume = 0.0;
+        self.notional = 0.0;
+        self.bucket_end = None;
+    }
+
+    fn push_point(&mut self, mut point: OhlcvBar, notional: f64) {
+        if self.timeframe.is_time_based() {
+            let time = *point
+                .open_time()
+                .expect("Time based bars require an open time");
+            if self.bucket_end.is_some_and(|end| time >= end) {
+                self.complete();
+            }
+            if self.bar.is_none() {
+                point.set_open_time(Some(floor_to_timeframe(
+                    time,
+                    &self.timeframe,
+                    &self.timezone,
+                )));
+                self.bucket_end = Some(next_timeframe_boundary(
+                    time,
+                    &self.timeframe,
+                    &self.timezone,
+                ));
+            }
+            self.extend(point, notional);
+            return;
+        }
+        self.extend(point, notional);
+        let done = match self.timeframe {
+            Timeframe::Ticks(n) => self.count >= n,
+            Timeframe::Volume(n) => self.volume >= n as f64,
+            Timeframe::Dollars(n) => self.notional >= n as f64,
+            _ => false,
+        };
+        if done {
+            self.complete();
+        }
+    }
+
+    fn push_range(&mut self, point: OhlcvBar, n: usize, notional: f64) {
+        let range = n as f64 * self.min_tick;
+        let eps = self.min_tick * 1e-6;
+        if let Some(bar) = &self.bar {
+            let high = bar.high().max(point.high());
+            let low = bar.low().min(point.low());
+            if high - low > range + eps {
+                self.complete();
+            }
+        }
+        self.extend(point, notional);
+        let bar = self.bar.as_ref().unwrap();
+        if bar.high() - bar.low() >= range - eps {
+            self.complete();
+        }
+    }
+
+    fn push_renko(&mut self, point: OhlcvBar, n: usize, notional: f64) {
+        let n = n.max(1) as i64;
+        let price = point.close();
+        let ticks = price / self.min_tick;
+        let time = point.close_time().copied();
+        self.extend(point, notional);
+        let (mut bottom, mut top) = *self.renko_levels.get_or_insert_with(|| {
+            let anchor = (ticks / n as f64).floor() as i64 * n;
+            (anchor, anchor)
+        });
+        loop {
+            let (open, close) = if ticks >= (top + n) as f64 {
+                let brick = (top, top + n);
+                bottom = top;
+                top += n;
+                brick
+            } else if ticks <= (bottom - n) as f64 {
+                let brick = (bottom, bottom - n);
+                top = bottom;
+                bottom -= n;
+                brick
+            } else {
+                break;
+            };
+            // the first brick takes the time, volume and trades since the previous one
+            let (open_time, volume, stats) = match self.bar.take() {
+                Some(bar) => (
+                    bar.open_time().copied(),
+                    bar.volume(),
+                    BarStats::new(self.count, self.notional, self.volume),
+                ),
+                None => (time, 0.0, BarStats::default()),
+            };
+            self.count = 0;
+            self.volume = 0.0;
+            self.notional = 0.0;
+            let open = open as f64 * self.min_tick;
+            let close = close as f64 * self.min_tick;
+            self.completed.push(OhlcvBar::new(
+                open_time,
+                time,
+                open,
+                open.max(close),
+                open.min(close),
+                close,
+                volume,
+            ));
+            self.completed_stats.push(stats);
+        }
+        self.renko_levels = Some((bottom, top));
+    }
+}
+
+/// Heikin-Ashi transform. Times and volume are kept, so the timeframe doesn't change.
+pub fn heikin_ashi(bars: &[OhlcvBar]) -> Vec<OhlcvBar> {
+    let mut result: Vec<OhlcvBar> = Vec::with_capacity(bars.len());
+    for bar in bars {
+        let close = (bar.open() + bar.high() + bar.low() + bar.close()) / 4.0;
+        let open = match result.last() {
+            Some(prev) => (prev.open() + prev.close()) / 2.0,
+            None => (bar.open() + bar.close()) / 2.0,
+        };
+        let mut ha = *bar;
+        ha.set_open(open);
+        ha.set_high(bar.high().max(open).max(close));
+        ha.set_low(bar.low().min(open).min(close));
+        ha.set_close(close);
+        result.push(ha);
+    }
+    return result;
+}
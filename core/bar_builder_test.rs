@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+
+    use crate::{
+        bar_builder::{heikin_ashi, BarBuilder, BarBuilderError},
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvReaderOps},
+        test_utils::time_seconds,
+        timeframe::Timeframe,
+    };
+
+    fn build(timeframe: Timeframe, trades: &[(f64, f64)]) -> Vec<OhlcvBar> {
+        let mut builder = BarBuilder::try_new(timeframe, 0.5).unwrap();
+        for (i, (price, volume)) in trades.iter().enumerate() {
+            builder.push_trade(time_seconds(i as i64 * 20), *price, *volume);
+        }
+        return builder.finish().bars();
+    }
+
+    fn ohlc(bar: &OhlcvBar) -> (f64, f64, f64, f64, f64) {
+        return (bar.open(), bar.high(), bar.low(), bar.close(), bar.volume());
+    }
+
+    #[test]
+    pub fn tick_volume_dollar_bars() {
+        let trades = [
+            (10.0, 1.0),
+            (12.0, 2.0),
+            (11.0, 1.0),
+            (9.0, 3.0),
+            (10.0, 1.0),
+        ];
+
+        let bars = build(Timeframe::Ticks(2), &trades);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(ohlc(&bars[0]), (10.0, 12.0, 10.0, 12.0, 3.0));
+        assert_eq!(ohlc(&bars[2]), (10.0, 10.0, 10.0, 10.0, 1.0));
+
+        let bars = build(Timeframe::Volume(3), &trades);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(ohlc(&bars[0]), (10.0, 12.0, 10.0, 12.0, 3.0));
+        // the last trade overshoots instead of being split
+        assert_eq!(ohlc(&bars[1]), (11.0, 11.0, 9.0, 9.0, 4.0));
+
+        let bars = build(Timeframe::Dollars(30), &trades);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(ohlc(&bars[0]), (10.0, 12.0, 10.0, 12.0, 3.0));
+        assert_eq!(ohlc(&bars[1]), (11.0, 11.0, 9.0, 9.0, 4.0));
+        // partial
+        assert_eq!(ohlc(&bars[2]), (10.0, 10.0, 10.0, 10.0, 1.0));
+    }
+
+    #[test]
+    pub fn time_bars_from_trades() {
+        let trades = [
+            (10.0, 1.0),
+            (12.0, 1.0),
+            (11.0, 1.0),
+            (9.0, 1.0),
+            (10.0, 1.0),
+        ];
+        let bars = build(Timeframe::Minutes(1), &trades);
+        assert_eq!(bars.len(), 2);
+        assert_eq!(ohlc(&bars[0]), (10.0, 12.0, 10.0, 11.0, 3.0));
+        assert_eq!(bars[0].open_time(), Some(&time_seconds(0)));
+        assert_eq!(bars[0].close_time(), Some(&time_seconds(60)));
+        assert_eq!(ohlc(&bars[1]), (9.0, 10.0, 9.0, 10.0, 2.0));
+    }
+
+    #[test]
+    pub fn range_bars() {
+        // range of 4 ticks of 0.5
+        let trades = [
+            (10.0, 1.0),
+            (11.0, 1.0),
+            (12.0, 1.0),
+            (12.5, 1.0),
+            (10.0, 1.0),
+        ];
+        let bars = build(Timeframe::Ranges(4), &trades);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(ohlc(&bars[0]), (10.0, 12.0, 10.0, 12.0, 3.0));
+        assert_eq!(ohlc(&bars[1]), (12.5, 12.5, 12.5, 12.5, 1.0));
+        assert_eq!(ohlc(&bars[2]), (10.0, 10.0, 10.0, 10.0, 1.0));
+    }
+
+    #[test]
+    pub fn renko_bricks() {
+        // bricks of 2 ticks of 0.5
+        let trades = [
+            (10.2, 1.0),
+            (12.1, 1.0),
+            (11.0, 1.0),
+            (9.9, 1.0),
+            (8.0, 1.0),
+        ];
+        let bars = build(Timeframe::Renko(2), &trades);
+        let bricks: Vec<(f64, f64)> = bars.iter().map(|b| (b.open(), b.close())).collect();
+        assert_eq!(
+            bricks,
+            vec![
+                (10.0, 11.0),
+                (11.0, 12.0),
+                (11.0, 10.0),
+                (10.0, 9.0),
+                (9.0, 8.0)
+            ]
+        );
+        assert_eq!(bars[0].volume(), 2.0);
+        assert_eq!(bars[1].volume(), 0.0);
+        assert_eq!(bars[2].volume(), 2.0);
+    }
+
+    #[test]
+    pub fn resample_and_heikin_ashi() {
+        let bars: Vec<OhlcvBar> = [(10.0, 14.0, 9.0, 12.0), (12.0, 13.0, 11.0, 11.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, (o, h, l, c))| {
+                let open_time = time_seconds(i as i64 * 60);
+                OhlcvBar::new(
+                    Some(open_time),
+                    Some(open_time + Duration::minutes(1)),
+                    *o,
+                    *h,
+                    *l,
+                    *c,
+                    2.0,
+                )
+            })
+            .collect();
+        let mut ohlcv = Ohlcv::from_bars(bars.clone());
+        ohlcv.set_timeframe(Timeframe::Minutes(1));
+
+        let ranges = ohlcv
+            .resample_with_tick(Timeframe::Ranges(6), false, 0.5)
+            .unwrap();
+        let mut builder = BarBuilder::try_new(Timeframe::Ranges(6), 0.5).unwrap();
+        for bar in &bars {
+            builder.push_bar(bar);
+        }
+        assert_eq!(ranges.bars(), builder.finish().bars());
+        assert_eq!(ranges.timeframe(), Timeframe::Ranges(6));
+        assert_eq!(
+            BarBuilder::try_new(Timeframe::Renko(2), f64::NAN).unwrap_err(),
+            BarBuilderError::MinTick(Timeframe::Renko(2))
+        );
+        assert_eq!(
+            ohlcv.resample(Timeframe::Ranges(6), false).unwrap_err(),
+            BarBuilderError::MinTick(Timeframe::Ranges(6))
+        );
+        assert_eq!(
+            ohlcv.resample(Timeframe::Unknown(), false).unwrap_err(),
+            BarBuilderError::UnknownTimeframe
+        );
+
+        let volume = ohlcv.resample(Timeframe::Volume(4), false).unwrap();
+        assert_eq!(volume.timeframe(), Timeframe::Volume(4));
+        assert_eq!(volume.len(), 1);
+        assert_eq!(ohlc(&volume.bars()[0]), (10.0, 14.0, 9.0, 11.0, 4.0));
+
+        let ha = heikin_ashi(&bars);
+        assert_eq!(ohlc(&ha[0]), (11.0, 14.0, 9.0, 11.25, 2.0));
+        assert_eq!(ohlc(&ha[1]), (11.125, 13.0, 11.0, 11.75, 2.0));
+        assert_eq!(ohlcv.heikin_ashi().bars(), ha);
+    }
+}
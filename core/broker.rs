@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::{
+    backtest::Backtest,
+    orderbook::{
+        round_to_min_tick, validate_contracts, Order, OrderBook, OrderBookConfig, OrderBookError,
+        OrderConfig,
+    },
+    signal::Signal,
+    sym::Sym,
+    trade::TradeError,
+};
+
+#[derive(Debug, Error)]
+pub enum BrokerError {
+    #[error("Unknown order: {0}")]
+    UnknownOrder(usize),
+    #[error("Order {0} is not pending")]
+    NotPending(usize),
+    #[error("Broker reports {fills} fills, {cursor} were already applied")]
+    FillsReset { cursor: usize, fills: usize },
+    #[error(transparent)]
+    OrderBook(#[from] OrderBookError),
+    #[error(transparent)]
+    Trade(#[from] TradeError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokerFill {
+    id: usize,
+    order_id: usize,
+    size: f64,
+    price: f64,
+    tag: Option<String>,
+}
+
+impl BrokerFill {
+    #[inline]
+    pub fn new(id: usize, order_id: usize, size: f64, price: f64, tag: Option<String>) -> Self {
+        Self {
+            id,
+            order_id,
+            size,
+            price,
+            tag,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    #[inline]
+    pub fn order_id(&self) -> usize {
+        self.order_id
+    }
+
+    /// Signed filled size. Positive for buys, negative for sells.
+    #[inline]
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+
+    #[inline]
+    pub fn price(&self) -> f64 {
+        self.price
+    }
+
+    #[inline]
+    pub fn tag(&self) -> Option<&String> {
+        self.tag.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokerPosition {
+    sym_id: Option<String>,
+    size: f64,
+    avg_price: f64,
+}
+
+impl BrokerPosition {
+    #[inline]
+    pub fn new(sym_id: Option<String>, size: f64, avg_price: f64) -> Self {
+        Self {
+            sym_id,
+            size,
+            avg_price,
+        }
+    }
+
+    #[inline]
+    pub fn sym_id(&self) -> Option<&str> {
+        self.sym_id.as_deref()
+    }
+
+    #[inline]
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+
+    #[inline]
+    pub fn avg_price(&self) -> f64 {
+        self.avg_price
+    }
+
+    fn apply_fill(&mut self, size: f64, price: f64) {
+        let new_size = self.size + size;
+        if new_size == 0.0 {
+            self.avg_price = f64::NAN;
+        } else if self.size == 0.0 || new_size.signum() != self.size.signum() {
+            // opened or flipped
+            self.avg_price = price;
+        } else if size.signum() == self.size.signum() {
+            // increased
+            self.avg_price =
+                (self.avg_price * self.size.abs() + price * size.abs()) / new_size.abs();
+        }
+        self.size = new_size;
+    }
+}
+
+/// Order routing interface of a broker or exchange.
+/// Orders are market orders sized in contracts, same as `OrderConfig`, fills are reported asynchronously through `fills`.
+pub trait Broker {
+    fn submit(&mut self, order: OrderConfig) -> Result<usize, BrokerError>;
+    fn cancel(&mut self, order_id: usize) -> Result<(), BrokerError>;
+    fn pending_orders(&self) -> Vec<Order>;
+    fn positions(&self) -> Vec<BrokerPosition>;
+    /// All fills since the broker was created, oldest first.
+    fn fills(&self) -> &[BrokerFill];
+}
+
+/// In-process exchange for paper trading and tests.
+/// Orders are queued in an `OrderBook` and filled at the current price on `process`.
+pub struct MockBroker {
+    sym: Sym,
+    orderbook: OrderBook,
+    position: BrokerPosition,
+    fills: Vec<BrokerFill>,
+}
+
+impl MockBroker {
+    #[inline]
+    pub fn new(sym: Sym) -> Self {
+        let orderbook = OrderBook::new(OrderBookConfig {
+            min_qty: sym.min_qty(),
+            qty_scale: sym.qty_scale(),
+            ..Default::default()
+        });
+        let position = BrokerPosition::new(sym.id().map(|id| id.to_string()), 0.0, f64::NAN);
+        Self {
+            sym,
+            orderbook,
+            position,
+            fills: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn sym(&self) -> &Sym {
+        &self.sym
+    }
+
+    #[inline]
+    pub fn price(&self) -> f64 {
+        self.orderbook.price()
+    }
+
+    #[inline]
+    pub fn set_price(&mut self, price: f64) {
+        self.orderbook
+            .set_price(round_to_min_tick(price, self.sym.min_tick()));
+    }
+
+    /// Fills all pending orders at the current price. Returns the number of fills.
+    pub fn process(&mut self) -> usize {
+        let price = self.orderbook.price();
+        let mut count = 0;
+        while let Some(order_id) = self.orderbook.pop_front() {
+            let order = self.orderbook.get_order(order_id).unwrap().clone();
+            if !validate_contracts(order.size(), self.sym.min_qty()) {
+                continue;
+            }
+            self.position.apply_fill(order.size(), price);
+            self.fills.push(BrokerFill::new(
+                self.fills.len(),
+                order.id(),
+                order.size(),
+                price,
+                order.tag().clone(),
+            ));
+            count += 1;
+        }
+        return count;
+    }
+}
+
+impl Broker for MockBroker {
+    #[inline]
+    fn submit(&mut self, order: OrderConfig) -> Result<usize, BrokerError> {
+        return Ok(self.orderbook.enqueue(order)?);
+    }
+
+    #[inline]
+    fn cancel(&mut self, order_id: usize) -> Result<(), BrokerError> {
+        if self.orderbook.cancel(order_id).is_some() {
+            return Ok(());
+        }
+        if self.orderbook.get_order(order_id).is_some() {
+            return Err(BrokerError::NotPending(order_id));
+        }
+        return Err(BrokerError::UnknownOrder(order_id));
+    }
+
+    #[inline]
+    fn pending_orders(&self) -> Vec<Order> {
+        self.orderbook
+            .queued_orders()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    #[inline]
+    fn positions(&self) -> Vec<BrokerPosition> {
+        if self.position.size() == 0.0 {
+            return vec![];
+        }
+        return vec![self.position.clone()];
+    }
+
+    #[inline]
+    fn fills(&self) -> &[BrokerFill] {
+        &self.fills
+    }
+}
+
+/// Routes orders produced by `Backtest::signal` to a `Broker` and reconciles its fills back into the `Backtest` trades.
+///
+/// The `Backtest` orderbook is bypassed, so trades, position and equity only change when the broker reports fills.
+pub struct BrokerBridge<B: Broker> {
+    backtest: Backtest,
+    broker: B,
+    /// Bar index and unfilled size of the submitted orders, by order id.
+    orders: HashMap<usize, (usize, f64)>,
+    fills_cursor: usize,
+}
+
+impl<B: Broker> BrokerBridge<B> {
+    #[inline]
+    pub fn new(backtest: Backtest, broker: B) -> Self {
+        Self {
+            backtest,
+            broker,
+            orders: HashMap::new(),
+            fills_cursor: 0,
+        }
+    }
+
+    #[inline]
+    pub fn backtest(&self) -> &Backtest {
+        &self.backtest
+    }
+
+    #[inline]
+    pub fn backtest_mut(&mut self) -> &mut Backtest {
+        &mut self.backtest
+    }
+
+    #[inline]
+    pub fn broker(&self) -> &B {
+        &self.broker
+    }
+
+    #[inline]
+    pub fn broker_mut(&mut self) -> &mut B {
+        &mut self.broker
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (Backtest, B) {
+        (self.backtest, self.broker)
+    }
+
+    /// Submits the order for `signal` to the broker. Returns `None` if the signal does not produce an order.
+    pub fn signal(&mut self, signal: Signal) -> Result<Option<usize>, BrokerError> {
+        let order = match self.backtest.order_for_signal(signal) {
+            Some(order) => order,
+            None => return Ok(None),
+        };
+        let size = order.size();
+        let order_id = self.broker.submit(order)?;
+        let bar_index = self.backtest.ctx().borrow().bar_index();
+        self.orders.insert(order_id, (bar_index, size));
+        return Ok(Some(order_id));
+    }
+
+    #[inline]
+    pub fn cancel(&mut self, order_id: usize) -> Result<(), BrokerError> {
+        self.broker.cancel(order_id)?;
+        self.orders.remove(&order_id);
+        return Ok(());
+    }
+
+    /// Applies fills reported by the broker since the last call. Returns the number of applied fills.
+    /// If a fill fails, the fills before it stay applied and the next call resumes from the failed one.
+    pub fn sync(&mut self) -> Result<usize, BrokerError> {
+        let fills = self
+            .broker
+            .fills()
+            .get(self.fills_cursor..)
+            .ok_or(BrokerError::FillsReset {
+                cursor: self.fills_cursor,
+                fills: self.broker.fills().len(),
+            })?
+            .to_vec();
+        let (bar_index, min_qty) = {
+            let ctx = self.backtest.ctx();
+            let ctx = ctx.borrow();
+            (ctx.bar_index(), ctx.sym().min_qty())
+        };
+        for fill in &fills {
+            let order_bar_index = match self.orders.get(&fill.order_id()) {
+                Some((order_bar_index, _)) => *order_bar_index,
+                None => bar_index,
+            };
+            self.backtest.on_fill(
+                order_bar_index,
+                fill.size(),
+                fill.price(),
+                fill.tag().cloned(),
+            )?;
+            self.fills_cursor += 1;
+            if let Some((_, remaining)) = self.orders.get_mut(&fill.order_id()) {
+                *remaining -= fill.size();
+                // filled, or only rounding noise left
+                if *remaining * fill.size() <= 0.0 || !validate_contracts(*remaining, min_qty) {
+                    self.orders.remove(&fill.order_id());
+                }
+            }
+        }
+        return Ok(fills.len());
+    }
+
+    /// Number of submitted orders that are not fully filled or cancelled yet.
+    #[inline]
+    pub fn open_orders(&self) -> usize {
+        self.orders.len()
+    }
+
+    #[inline]
+    pub fn on_bar_open(&mut self) -> Result<usize, BrokerError> {
+        self.backtest.on_bar_open();
+        return self.sync();
+    }
+
+    #[inline]
+    pub fn on_bar_close(&mut self) -> Result<usize, BrokerError> {
+        self.backtest.on_bar_close();
+        return self.sync();
+    }
+}
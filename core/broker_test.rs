@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        broker::{Broker, BrokerBridge, BrokerError, BrokerFill, BrokerPosition, MockBroker},
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        orderbook::{Order, OrderConfig},
+        signal::Signal,
+        sym::Sym,
+        timeframe::Timeframe,
+    };
+
+    fn create_ctx() -> Rc<RefCell<Ctx>> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let closes = [100.0, 102.0, 101.0, 105.0, 104.0, 99.0, 98.0, 103.0];
+        let bars: Vec<OhlcvBar> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let open_time = start + Duration::days(i as i64);
+                let open = close - 1.0;
+                OhlcvBar::new(
+                    Some(open_time),
+                    Some(open_time + Duration::days(1)),
+                    open,
+                    close + 2.0,
+                    open - 2.0,
+                    *close,
+                    10.0,
+                )
+            })
+            .collect();
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Days(1));
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        ctx.set_sym(Sym::btc_usd());
+        return Rc::new(RefCell::new(ctx));
+    }
+
+    fn signal_at(bar_index: usize) -> Option<Signal> {
+        match bar_index {
+            1 => Some(Signal::size(1.0)),
+            3 => Some(Signal::size(-2.0)),
+            5 => Some(Signal::close_all()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    pub fn bridge_matches_backtest() {
+        let ctx = create_ctx();
+        let mut expected = Backtest::new(ctx.clone(), BacktestConfig::default());
+        let signals = (0..ctx.borrow().len()).map(signal_at).collect();
+        expected.signal_list(signals);
+
+        let ctx = create_ctx();
+        let backtest = Backtest::new(ctx.clone(), BacktestConfig::default());
+        let mut bridge = BrokerBridge::new(backtest, MockBroker::new(Sym::btc_usd()));
+        loop {
+            let bar_index = match ctx.borrow_mut().next() {
+                Some(bar_index) => bar_index,
+                None => break,
+            };
+            let bar = ctx.borrow().bar();
+            bridge.broker_mut().set_price(bar.open());
+            bridge.broker_mut().process();
+            bridge.on_bar_open().unwrap();
+            if let Some(signal) = signal_at(bar_index) {
+                bridge.signal(signal).unwrap();
+            }
+            bridge.on_bar_close().unwrap();
+        }
+
+        assert_eq!(bridge.open_orders(), 0);
+        let (actual, broker) = bridge.into_inner();
+        assert_eq!(broker.fills().len(), 3);
+        assert!(broker.positions().is_empty());
+        assert_eq!(actual.closed_trades(), expected.closed_trades());
+        assert_eq!(actual.open_trades(), expected.open_trades());
+        assert_eq!(actual.equity_list(), expected.equity_list());
+        assert_eq!(actual.net_profit(), expected.net_profit());
+    }
+
+    #[test]
+    pub fn mock_broker_cancel() {
+        let mut broker = MockBroker::new(Sym::btc_usd());
+        broker.set_price(100.0);
+        let a = broker.submit(OrderConfig::new(1.0, None)).unwrap();
+        let b = broker.submit(OrderConfig::new(2.0, None)).unwrap();
+        broker.cancel(b).unwrap();
+        assert_eq!(broker.pending_orders().len(), 1);
+        assert_eq!(broker.process(), 1);
+        assert!(matches!(broker.cancel(a), Err(BrokerError::NotPending(_))));
+        assert!(matches!(
+            broker.cancel(42),
+            Err(BrokerError::UnknownOrder(42))
+        ));
+
+        broker.set_price(110.0);
+        broker.submit(OrderConfig::new(1.0, None)).unwrap();
+        broker.process();
+        let position = &broker.positions()[0];
+        assert_eq!(position.size(), 2.0);
+        assert_eq!(position.avg_price(), 105.0);
+    }
+
+    /// Replays a fixed list of fills, like a broker that can lose its history on reconnect.
+    struct ReplayBroker {
+        fills: Vec<BrokerFill>,
+    }
+
+    impl Broker for ReplayBroker {
+        fn submit(&mut self, _order: OrderConfig) -> Result<usize, BrokerError> {
+            return Ok(0);
+        }
+
+        fn cancel(&mut self, order_id: usize) -> Result<(), BrokerError> {
+            return Err(BrokerError::UnknownOrder(order_id));
+        }
+
+        fn pending_orders(&self) -> Vec<Order> {
+            return vec![];
+        }
+
+        fn positions(&self) -> Vec<BrokerPosition> {
+            return vec![];
+        }
+
+        fn fills(&self) -> &[BrokerFill] {
+            return &self.fills;
+        }
+    }
+
+    #[test]
+    pub fn partial_fills_and_reset() {
+        let ctx = create_ctx();
+        let backtest = Backtest::new(ctx.clone(), BacktestConfig::default());
+        let broker = ReplayBroker { fills: vec![] };
+        let mut bridge = BrokerBridge::new(backtest, broker);
+        ctx.borrow_mut().next();
+        bridge.on_bar_open().unwrap();
+        let order_id = bridge.signal(Signal::size(2.0)).unwrap().unwrap();
+        bridge.on_bar_close().unwrap();
+
+        ctx.borrow_mut().next();
+        bridge.broker_mut().fills = vec![BrokerFill::new(0, order_id, 1.5, 100.0, None)];
+        assert_eq!(bridge.on_bar_open().unwrap(), 1);
+        assert_eq!(bridge.open_orders(), 1);
+        bridge
+            .broker_mut()
+            .fills
+            .push(BrokerFill::new(1, order_id, 0.5, 101.0, None));
+        assert_eq!(bridge.sync().unwrap(), 1);
+        assert_eq!(bridge.open_orders(), 0);
+        assert_eq!(bridge.backtest().position_size(), 2.0);
+
+        bridge.broker_mut().fills.clear();
+        assert!(matches!(
+            bridge.sync(),
+            Err(BrokerError::FillsReset {
+                cursor: 2,
+                fills: 0
+            })
+        ));
+    }
+}
@@ -3,4 +3,21 @@ fn main() {
     {
         pyo3_build_config::add_extension_module_link_args();
     }
+    // messages of lib/proto/ohlcv.proto are written by hand in ohlcv_grpc.rs, so no protoc is needed
+    #[cfg(feature = "grpc")]
+    {
+        let get = tonic_build::manual::Method::builder()
+            .name("get")
+            .route_name("Get")
+            .input_type("crate::ohlcv_grpc::proto::GetRequest")
+            .output_type("crate::ohlcv_grpc::proto::GetResponse")
+            .codec_path("tonic::codec::ProstCodec")
+            .build();
+        let service = tonic_build::manual::Service::builder()
+            .name("OhlcvApi")
+            .package("ohlcv")
+            .method(get)
+            .build();
+        tonic_build::manual::Builder::new().compile(&[service]);
+    }
 }
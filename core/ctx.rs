@@ -1,7 +1,7 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 
 use crate::{
-    metrics::annualization_factor,
     ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
     sym::Sym,
 };
@@ -51,6 +51,14 @@ impl Ctx {
         self.is_initialized = false;
     }
 
+    /// Moves to `bar_index` as if `next` was called up to it. `None` leaves the ctx uninitialized.
+    #[cfg(feature = "json")]
+    #[inline]
+    pub(crate) fn restore_bar_index(&mut self, bar_index: Option<usize>) {
+        self.bar_index = bar_index.unwrap_or(0);
+        self.is_initialized = bar_index.is_some();
+    }
+
     #[inline]
     pub fn bar_index(&self) -> usize {
         self.bar_index
@@ -66,6 +74,13 @@ impl Ctx {
         self.ohlcv.get(self.bar_index()).unwrap()
     }
 
+    /// `true` when the current bar is the last one available. In streaming mode this is the realtime bar,
+    /// which can still be updated in place until a new bar is appended.
+    #[inline]
+    pub fn is_last_bar(&self) -> bool {
+        self.is_initialized && self.bar_index == self.last_bar_index()
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.ohlcv.len()
@@ -81,11 +96,20 @@ impl Ctx {
         &self.sym
     }
 
+    /// Time zone of the symbol exchange, or of the `Ohlcv` when the symbol has none.
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.sym.timezone().unwrap_or_else(|| self.ohlcv.timezone())
+    }
+
     #[inline]
     pub fn ohlcv(&self) -> &dyn OhlcvReader {
         self.ohlcv.as_ref()
     }
 
+    /// Advances to the next bar. Returns `None` once all bars were visited.
+    /// If the `Ctx` was created from a shared `RcOhlcv` or `ArcOhlcv`, bars appended later are picked up
+    /// by subsequent calls, so the same loop can keep running in streaming mode.
     #[inline]
     pub fn next(&mut self) -> Option<usize> {
         let bar_index = if self.is_initialized {
@@ -108,7 +132,52 @@ impl Ctx {
 
     #[inline]
     pub fn annualization_factor(&self) -> f64 {
-        return annualization_factor(self.ohlcv.timeframe(), self.sym.kind().periods());
+        return self.sym.annualization_factor(self.ohlcv.timeframe());
+    }
+
+    /// `true` when the current bar opens within the regular hours of the symbol session.
+    /// Always `true` without a session.
+    #[inline]
+    pub fn is_session_open(&self) -> bool {
+        match (self.sym.session(), self.bar().open_time()) {
+            (Some(session), Some(time)) => session.is_open(*time, false),
+            _ => true,
+        }
+    }
+
+    /// `true` when the current bar is the first one of its trading day.
+    #[inline]
+    pub fn is_first_bar_of_session(&self) -> bool {
+        if self.bar_index == 0 {
+            return true;
+        }
+        return self.trading_date(self.bar_index - 1) != self.trading_date(self.bar_index);
+    }
+
+    /// `true` when the next bar belongs to another trading day. For the last available bar this is only
+    /// known once it reaches the session close.
+    #[inline]
+    pub fn is_last_bar_of_session(&self) -> bool {
+        if self.bar_index < self.last_bar_index() {
+            return self.trading_date(self.bar_index + 1) != self.trading_date(self.bar_index);
+        }
+        let bar = self.bar();
+        let (Some(session), Some(time)) = (self.sym.session(), bar.close_time()) else {
+            return false;
+        };
+        let date = session.trading_date(*bar.open_time().unwrap_or(time));
+        return session
+            .bounds(date, true)
+            .is_some_and(|(_, close)| *time >= close);
+    }
+
+    #[inline]
+    fn trading_date(&self, bar_index: usize) -> Option<NaiveDate> {
+        let time = *self.ohlcv.get(bar_index)?.open_time()?;
+        return match self.sym.session() {
+            Some(session) => Some(session.trading_date(time)),
+            None => Some(time.with_timezone(&self.timezone()).date_naive()),
+        };
     }
 }
 
@@ -74,6 +74,30 @@ impl NodeCtx {
         self.inner.borrow().is_initialized()
     }
 
+    #[napi(getter = isLastBar)]
+    #[inline]
+    pub fn node_is_last_bar(&self) -> bool {
+        self.inner.borrow().is_last_bar()
+    }
+
+    #[napi(getter = isSessionOpen)]
+    #[inline]
+    pub fn node_is_session_open(&self) -> bool {
+        self.inner.borrow().is_session_open()
+    }
+
+    #[napi(getter = isFirstBarOfSession)]
+    #[inline]
+    pub fn node_is_first_bar_of_session(&self) -> bool {
+        self.inner.borrow().is_first_bar_of_session()
+    }
+
+    #[napi(getter = isLastBarOfSession)]
+    #[inline]
+    pub fn node_is_last_bar_of_session(&self) -> bool {
+        self.inner.borrow().is_last_bar_of_session()
+    }
+
     #[napi(getter = sym)]
     #[inline]
     pub fn node_sym(&self) -> NodeSym {
@@ -88,6 +88,30 @@ impl PyCtx {
         self.inner.borrow().is_initialized()
     }
 
+    #[getter(is_last_bar)]
+    #[inline]
+    pub fn py_is_last_bar(&self) -> bool {
+        self.inner.borrow().is_last_bar()
+    }
+
+    #[getter(is_session_open)]
+    #[inline]
+    pub fn py_is_session_open(&self) -> bool {
+        self.inner.borrow().is_session_open()
+    }
+
+    #[getter(is_first_bar_of_session)]
+    #[inline]
+    pub fn py_is_first_bar_of_session(&self) -> bool {
+        self.inner.borrow().is_first_bar_of_session()
+    }
+
+    #[getter(is_last_bar_of_session)]
+    #[inline]
+    pub fn py_is_last_bar_of_session(&self) -> bool {
+        self.inner.borrow().is_last_bar_of_session()
+    }
+
     #[getter(sym)]
     #[inline]
     pub fn py_sym(&self) -> PySym {
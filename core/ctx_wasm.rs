@@ -69,6 +69,30 @@ impl WasmCtx {
         self.inner.borrow().is_initialized()
     }
 
+    #[wasm_bindgen(getter = isLastBar)]
+    #[inline]
+    pub fn wasm_is_last_bar(&self) -> bool {
+        self.inner.borrow().is_last_bar()
+    }
+
+    #[wasm_bindgen(getter = isSessionOpen)]
+    #[inline]
+    pub fn wasm_is_session_open(&self) -> bool {
+        self.inner.borrow().is_session_open()
+    }
+
+    #[wasm_bindgen(getter = isFirstBarOfSession)]
+    #[inline]
+    pub fn wasm_is_first_bar_of_session(&self) -> bool {
+        self.inner.borrow().is_first_bar_of_session()
+    }
+
+    #[wasm_bindgen(getter = isLastBarOfSession)]
+    #[inline]
+    pub fn wasm_is_last_bar_of_session(&self) -> bool {
+        self.inner.borrow().is_last_bar_of_session()
+    }
+
     #[wasm_bindgen(getter = sym)]
     #[inline]
     pub fn wasm_sym(&self) -> WasmSym {
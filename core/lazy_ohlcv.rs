@@ -0,0 +1,282 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use polars::{
+    error::PolarsError,
+    frame::DataFrame,
+    lazy::{
+        dsl::{col, count, lit},
+        frame::{LazyFrame, ScanArgsParquet},
+    },
+    prelude::IdxSize,
+};
+
+use crate::{
+    ohlcv::{ohlcv_bars_from_polars, Ohlcv, OhlcvBar, OhlcvReader},
+    ohlcv_io::{OhlcvIoError, OhlcvTimeFormat},
+    timeframe::Timeframe,
+    utils::SeriesCastUtils,
+};
+
+pub const DEFAULT_CHUNK_SIZE: usize = 65_536;
+
+/// `OhlcvReader` over a lazy polars scan, e.g. a multi-year parquet history.
+/// Bars are collected `chunk_size` at a time on access, only the last chunk is kept in memory.
+/// Times are expected in seconds, like `OhlcvWriter::read_parquet`.
+/// Columns are checked up front, a chunk that fails to load later makes `get` return `None` and `slice` panic,
+/// `read_range` returns the error instead.
+#[derive(Clone)]
+pub struct LazyOhlcv {
+    frame: LazyFrame,
+    path: Option<PathBuf>,
+    time_column: Option<String>,
+    len: usize,
+    chunk_size: usize,
+    chunk: RefCell<(usize, Vec<OhlcvBar>)>,
+    timeframe: Timeframe,
+    timezone: Tz,
+}
+
+impl fmt::Debug for LazyOhlcv {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LazyOhlcv")
+            .field("path", &self.path)
+            .field("len", &self.len)
+            .field("chunk_size", &self.chunk_size)
+            .field("timeframe", &self.timeframe)
+            .field("timezone", &self.timezone)
+            .finish()
+    }
+}
+
+impl LazyOhlcv {
+    /// Fails if the columns cannot be read as bars.
+    pub fn from_lazy(frame: LazyFrame) -> Result<Self, OhlcvIoError> {
+        let schema = frame.schema()?;
+        let time_column = ["open_time", "time"]
+            .into_iter()
+            .find(|name| schema.contains(name))
+            .map(|name| name.to_string());
+        let mut ohlcv = Self {
+            frame,
+            path: None,
+            time_column,
+            len: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            chunk: RefCell::new((0, vec![])),
+            timeframe: Timeframe::Unknown(),
+            timezone: Tz::UTC,
+        };
+        ohlcv.len = ohlcv.count()?;
+        ohlcv.read_range(0..ohlcv.len.min(1))?;
+        return Ok(ohlcv);
+    }
+
+    /// Nothing is read besides the parquet metadata, a row count and the first row.
+    pub fn scan_parquet(path: &Path) -> Result<Self, OhlcvIoError> {
+        let frame = LazyFrame::scan_parquet(path, ScanArgsParquet::default())?;
+        let mut ohlcv = Self::from_lazy(frame)?;
+        ohlcv.path = Some(path.to_path_buf());
+        return Ok(ohlcv);
+    }
+
+    /// Bars with `start <= open_time < end`. The filter is pushed down to the scan,
+    /// so row groups outside of the range are skipped using parquet statistics.
+    pub fn between(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Self, PolarsError> {
+        let time_column = self.time_column.as_deref().ok_or_else(|| {
+            PolarsError::ColumnNotFound("Expected open_time or time column".into())
+        })?;
+        let mut frame = self.frame.clone();
+        if let Some(start) = start {
+            frame = frame.filter(col(time_column).gt_eq(lit(start.timestamp())));
+        }
+        if let Some(end) = end {
+            frame = frame.filter(col(time_column).lt(lit(end.timestamp())));
+        }
+        let mut ohlcv = self.clone();
+        ohlcv.frame = frame;
+        ohlcv.chunk = RefCell::new((0, vec![]));
+        ohlcv.len = ohlcv.count()?;
+        return Ok(ohlcv);
+    }
+
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    #[inline]
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        assert!(chunk_size > 0, "Chunk size must be positive");
+        self.chunk_size = chunk_size;
+        self.chunk = RefCell::new((0, vec![]));
+    }
+
+    #[inline]
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    #[inline]
+    pub fn set_timeframe(&mut self, timeframe: Timeframe) {
+        self.timeframe = timeframe;
+    }
+
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    #[inline]
+    pub fn set_timezone(&mut self, timezone: Tz) {
+        self.timezone = timezone;
+    }
+
+    #[inline]
+    pub fn frame(&self) -> &LazyFrame {
+        &self.frame
+    }
+
+    /// Loads every bar.
+    pub fn collect(&self) -> Result<Ohlcv, OhlcvIoError> {
+        let df = self.frame.clone().collect()?;
+        let mut ohlcv = Ohlcv::from_bars(ohlcv_bars_from_polars(&df, OhlcvTimeFormat::Seconds)?);
+        ohlcv.set_timeframe(self.timeframe);
+        ohlcv.set_timezone(self.timezone);
+        return Ok(ohlcv);
+    }
+
+    fn count(&self) -> Result<usize, PolarsError> {
+        let df = self.frame.clone().select([count()]).collect()?;
+        let len = df.get_columns()[0].to_usize()[0].unwrap_or(0);
+        return Ok(len);
+    }
+
+    /// Loads the bars of `range` without touching the cached chunk.
+    pub fn read_range(&self, range: Range<usize>) -> Result<Vec<OhlcvBar>, OhlcvIoError> {
+        let df: DataFrame = self
+            .frame
+            .clone()
+            .slice(range.start as i64, (range.end - range.start) as IdxSize)
+            .collect()?;
+        return ohlcv_bars_from_polars(&df, OhlcvTimeFormat::Seconds);
+    }
+
+    fn column_f64(&self, name: &str) -> Vec<f64> {
+        let df = self.frame.clone().select([col(name)]).collect().unwrap();
+        return df.get_columns()[0].to_f64();
+    }
+}
+
+impl OhlcvReader for LazyOhlcv {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<OhlcvBar> {
+        if index >= self.len {
+            return None;
+        }
+        let mut chunk = self.chunk.borrow_mut();
+        let (start, bars) = &mut *chunk;
+        if index < *start || index >= *start + bars.len() {
+            let chunk_start = index - index % self.chunk_size;
+            let end = (chunk_start + self.chunk_size).min(self.len);
+            *bars = self.read_range(chunk_start..end).ok()?;
+            *start = chunk_start;
+        }
+        return bars.get(index - *start).copied();
+    }
+
+    fn slice(&self, range: Range<usize>) -> Vec<OhlcvBar> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range {:?} out of bounds for {} bars",
+            range,
+            self.len
+        );
+        {
+            let chunk = self.chunk.borrow();
+            let (start, bars) = &*chunk;
+            if range.start >= *start && range.end <= *start + bars.len() {
+                return bars[range.start - start..range.end - start].to_vec();
+            }
+        }
+        if range.is_empty() {
+            return vec![];
+        }
+        return self
+            .read_range(range)
+            .unwrap_or_else(|e| panic!("Failed to read bars: {}", e));
+    }
+
+    #[inline]
+    fn into_box(self) -> Box<dyn OhlcvReader> {
+        Box::new(self)
+    }
+
+    #[inline]
+    fn clone_box(&self) -> Box<dyn OhlcvReader> {
+        Box::new(self.clone())
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    #[inline]
+    fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    #[inline]
+    fn open(&self) -> Vec<f64> {
+        self.column_f64("open")
+    }
+
+    #[inline]
+    fn high(&self) -> Vec<f64> {
+        self.column_f64("high")
+    }
+
+    #[inline]
+    fn low(&self) -> Vec<f64> {
+        self.column_f64("low")
+    }
+
+    #[inline]
+    fn close(&self) -> Vec<f64> {
+        self.column_f64("close")
+    }
+
+    #[inline]
+    fn volume(&self) -> Vec<f64> {
+        if !self.frame.schema().unwrap().contains("volume") {
+            return vec![f64::NAN; self.len];
+        }
+        self.column_f64("volume")
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+#[cfg(feature = "polars")]
+mod test {
+    use crate::{
+        ctx::Ctx,
+        lazy_ohlcv::LazyOhlcv,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        test_utils::time,
+        timeframe::Timeframe,
+    };
+
+    fn write(name: &str, len: i64) -> std::path::PathBuf {
+        let bars: Vec<OhlcvBar> = (0..len)
+            .map(|i| {
+                let price = 100.0 + i as f64;
+                OhlcvBar::new(
+                    Some(time(i)),
+                    Some(time(i + 1)),
+                    price,
+                    price + 1.0,
+                    price - 1.0,
+                    price + 0.5,
+                    i as f64,
+                )
+            })
+            .collect();
+        let path = std::env::temp_dir().join(name);
+        Ohlcv::from_bars(bars).write_parquet(&path);
+        return path;
+    }
+
+    #[test]
+    pub fn reads_in_chunks() {
+        let path = write("qpace_lazy_ohlcv_test_chunks.parquet", 1000);
+        let mut ohlcv = LazyOhlcv::scan_parquet(&path).unwrap();
+        ohlcv.set_chunk_size(64);
+        ohlcv.set_timeframe(Timeframe::Minutes(1));
+        assert_eq!(ohlcv.len(), 1000);
+        assert_eq!(ohlcv.get(999).unwrap().open_time(), Some(&time(999)));
+        assert_eq!(ohlcv.get(1000), None);
+        assert_eq!(ohlcv.at(-1).unwrap().close(), 1099.5);
+        let bars = ohlcv.slice(60..70);
+        assert_eq!(bars.len(), 10);
+        assert_eq!(bars[0].open(), 160.0);
+        assert_eq!(ohlcv.close()[500], 600.5);
+
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        let mut volume = 0.0;
+        while ctx.next().is_some() {
+            volume += ctx.bar().volume();
+        }
+        assert_eq!(volume, (0..1000).sum::<i64>() as f64);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    pub fn between_filters_open_time() {
+        let path = write("qpace_lazy_ohlcv_test_between.parquet", 500);
+        let ohlcv = LazyOhlcv::scan_parquet(&path).unwrap();
+        let range = ohlcv.between(Some(time(100)), Some(time(200))).unwrap();
+        assert_eq!(range.len(), 100);
+        assert_eq!(range.get(0).unwrap().open_time(), Some(&time(100)));
+        assert_eq!(range.at(-1).unwrap().open_time(), Some(&time(199)));
+        let tail = ohlcv.between(Some(time(450)), None).unwrap();
+        assert_eq!(tail.collect().unwrap().len(), 50);
+        let _ = std::fs::remove_file(path);
+    }
+}
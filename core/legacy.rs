@@ -309,7 +309,7 @@ impl Fixture {
     pub fn load(path: &Path) -> (DataFrame, Rc<RefCell<Ctx>>) {
         let df = read_df(&path).unwrap();
         let mut ohlcv = Ohlcv::new();
-        ohlcv.read_polars(&df);
+        ohlcv.read_polars(&df).unwrap();
         let mut ctx = Ctx::new();
         ctx.set_ohlcv(ohlcv.into_box());
         return (df, Rc::new(RefCell::new(ctx)));
@@ -15,18 +15,59 @@ extern crate num_traits;
 #[macro_use]
 extern crate num_derive;
 
+mod align_test;
 mod backtest_test;
+mod bar_builder_test;
+mod broker_test;
+mod lazy_ohlcv_test;
+mod ohlcv_arrow_test;
+mod ohlcv_grpc_test;
+mod ohlcv_io_test;
+mod ohlcv_store_test;
+mod ohlcv_test;
+mod options_test;
+mod pine_test;
+mod quality_test;
+mod roll_test;
+mod security_test;
+mod session_test;
+mod sym_registry_test;
+mod tick_test;
+#[cfg(test)]
+mod test_utils;
 
+pub mod align;
 pub mod backtest;
+#[cfg(all(feature = "json", feature = "polars"))]
+pub mod backtest_fixture;
+pub mod bar_builder;
+pub mod broker;
 pub mod ctx;
+#[cfg(feature = "polars")]
+pub mod lazy_ohlcv;
 pub mod legacy;
 pub mod metrics;
 pub mod ohlcv;
+#[cfg(feature = "arrow")]
+pub mod ohlcv_arrow;
+#[cfg(feature = "grpc")]
+pub mod ohlcv_grpc;
+pub mod ohlcv_io;
+pub mod ohlcv_store;
+pub mod options;
 pub mod orderbook;
+pub mod pine;
 pub mod plot;
+pub mod quality;
+pub mod roll;
+pub mod security;
+pub mod session;
 pub mod signal;
 pub mod stats;
 pub mod sym;
+#[cfg(feature = "json")]
+pub mod sym_registry;
+pub mod tick;
 pub mod timeframe;
 pub mod trade;
 pub mod utils;
@@ -46,6 +87,7 @@ cfg_if::cfg_if! { if #[cfg(feature = "bindings_py")] {
   pub mod sym_py;
   pub mod stats_py;
   pub mod metrics_py;
+  pub mod options_py;
   pub mod ohlcv_py;
   pub mod ctx_py;
   pub mod trade_py;
@@ -68,6 +110,7 @@ cfg_if::cfg_if! { if #[cfg(all(feature = "bindings_wasm"))] {
   pub mod sym_wasm;
   pub mod stats_wasm;
   pub mod metrics_wasm;
+  pub mod options_wasm;
   pub mod ohlcv_wasm;
   pub mod ctx_wasm;
   pub mod trade_wasm;
@@ -84,6 +127,8 @@ pub mod ctx_node;
 #[cfg(feature = "bindings_node")]
 pub mod metrics_node;
 #[cfg(feature = "bindings_node")]
+pub mod options_node;
+#[cfg(feature = "bindings_node")]
 pub mod ohlcv_node;
 #[cfg(feature = "bindings_node")]
 pub mod orderbook_node;
@@ -127,6 +172,8 @@ fn py_lib_mod(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<ohlcv::OhlcvBar>()?;
     m.add_class::<plot_py::PyLineStyle>()?;
     m.add_class::<plot_py::PyPosition>()?;
+    m.add_class::<plot_py::PyBox>()?;
+    m.add_class::<plot_py::PyLabel>()?;
     m.add_function(wrap_pyfunction!(get_version, m)?)?;
     m.add_function(wrap_pyfunction!(metrics_py::py_expectancy, m)?)?;
     m.add_function(wrap_pyfunction!(metrics_py::py_expectancy_score, m)?)?;
@@ -155,6 +202,13 @@ fn py_lib_mod(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(metrics_py::py_f1, m)?)?;
     m.add_function(wrap_pyfunction!(metrics_py::py_annualization_factor, m)?)?;
     m.add_function(wrap_pyfunction!(metrics_py::py_max_drawdown_from_equity, m)?)?;
+    m.add_function(wrap_pyfunction!(options_py::py_option_price, m)?)?;
+    m.add_function(wrap_pyfunction!(options_py::py_option_greeks, m)?)?;
+    m.add_function(wrap_pyfunction!(options_py::py_implied_volatility, m)?)?;
+    m.add_function(wrap_pyfunction!(options_py::py_option_price_series, m)?)?;
+    m.add_function(wrap_pyfunction!(options_py::py_option_greeks_series, m)?)?;
+    m.add_function(wrap_pyfunction!(options_py::py_implied_volatility_series, m)?)?;
+    m.add_function(wrap_pyfunction!(plot_py::py_pane_to_pine, m)?)?;
     Ok(())
 }
 define_stub_info_gatherer!(stub_info);
@@ -1,15 +1,15 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
 cfg_if::cfg_if! { if #[cfg(feature = "polars")] {
     use polars::frame::DataFrame;
     use polars::series::Series;
     use polars::error::PolarsError;
     use polars::prelude::NamedFrom;
-    use crate::utils::SeriesCastUtils;
     use crate::utils::read_df_csv;
     use crate::utils::read_df_parquet;
     use crate::utils::write_df_csv;
     use crate::utils::write_df_parquet;
-
+    use crate::ohlcv_io::{ohlcv_from_polars, OhlcvIoConfig, OhlcvIoError, OhlcvTimeFormat};
 }}
 cfg_if::cfg_if! { if #[cfg(feature = "bindings_py")] {
   use pyo3::prelude::*;
@@ -18,17 +18,20 @@ cfg_if::cfg_if! { if #[cfg(feature = "bindings_py")] {
 }}
 use std::{
     any::Any,
-    cell::RefCell,
+    cell::{Ref, RefCell},
     fmt,
     ops::Range,
     rc::Rc,
-    sync::{Arc, RwLock},
+    sync::{Arc, RwLock, RwLockReadGuard},
 };
 cfg_if::cfg_if! {
     if #[cfg(feature = "bindings_wasm")] {
         use wasm_bindgen::prelude::*;
     }
 }
+use crate::bar_builder::{heikin_ashi, BarBuilder, BarBuilderError};
+use crate::quality::{check, repair, OhlcvQualityConfig, OhlcvQualityReport, OhlcvRepairConfig};
+use crate::session::{local_to_utc, Session};
 use crate::timeframe::Timeframe;
 use std::path::Path;
 
@@ -287,6 +290,11 @@ pub trait OhlcvReader: fmt::Debug {
         Timeframe::Unknown()
     }
 
+    /// Time zone used to align day, week and month boundaries. Bar times are always UTC.
+    fn timezone(&self) -> Tz {
+        Tz::UTC
+    }
+
     fn slice(&self, range: Range<usize>) -> Vec<OhlcvBar>;
     fn bars(&self) -> Vec<OhlcvBar> {
         return self.slice(0..self.len());
@@ -398,6 +406,12 @@ pub trait OhlcvReader: fmt::Debug {
         write_df_parquet(Path::new(&path), &mut df.clone()).unwrap();
     }
 
+    /// Every data-quality issue, see `quality::check`.
+    #[inline]
+    fn quality_report(&self, config: &OhlcvQualityConfig) -> OhlcvQualityReport {
+        check(&self.bars(), config)
+    }
+
     #[inline]
     fn sanity_check(&self) -> Result<(), String> {
         if self.len() == 0 {
@@ -514,25 +528,27 @@ pub trait OhlcvWriter: fmt::Debug {
 
     fn set(&mut self, index: usize, bar: OhlcvBar);
 
+    /// Times are read as seconds unless the column is a datetime. Nothing is pushed on error.
     #[cfg(feature = "polars")]
     #[inline]
-    fn read_polars(&mut self, df: &DataFrame) {
-        let bars = ohlcv_bars_from_polars(df, "s");
+    fn read_polars(&mut self, df: &DataFrame) -> Result<(), OhlcvIoError> {
+        let bars = ohlcv_bars_from_polars(df, OhlcvTimeFormat::Seconds)?;
         self.push_many(bars);
+        return Ok(());
     }
 
     #[cfg(feature = "polars")]
     #[inline]
-    fn read_csv(&mut self, path: &Path) {
-        let df = read_df_csv(path).unwrap();
-        self.read_polars(&df);
+    fn read_csv(&mut self, path: &Path) -> Result<(), OhlcvIoError> {
+        let df = read_df_csv(path)?;
+        return self.read_polars(&df);
     }
 
     #[cfg(feature = "polars")]
     #[inline]
-    fn read_parquet(&mut self, path: &Path) {
-        let df = read_df_parquet(path).unwrap();
-        self.read_polars(&df);
+    fn read_parquet(&mut self, path: &Path) -> Result<(), OhlcvIoError> {
+        let df = read_df_parquet(path)?;
+        return self.read_polars(&df);
     }
 }
 
@@ -550,7 +566,37 @@ pub trait OhlcvReaderOps: OhlcvReader {
     fn copy(&self) -> Self;
     fn head(&self, n: usize) -> Vec<OhlcvBar>;
     fn tail(&self, n: usize) -> Vec<OhlcvBar>;
-    fn resample(&self, timeframe: Timeframe, align_utc: bool) -> Self;
+    /// `align` pins bars to calendar boundaries in `timezone`, otherwise windows start at the first bar.
+    /// `Ranges` and `Renko` are sized in ticks of the symbol and fail here, use `resample_with_tick` for them.
+    #[inline]
+    fn resample(&self, timeframe: Timeframe, align: bool) -> Result<Self, BarBuilderError>
+    where
+        Self: Sized,
+    {
+        self.resample_with_tick(timeframe, align, f64::NAN)
+    }
+    /// Same as `resample`, `min_tick` of the symbol sizes `Ranges` and `Renko` bars.
+    fn resample_with_tick(
+        &self,
+        timeframe: Timeframe,
+        align: bool,
+        min_tick: f64,
+    ) -> Result<Self, BarBuilderError>
+    where
+        Self: Sized;
+    /// Resamples along `session` hours in its time zone, e.g. daily bars closing at 16:00 New York.
+    /// Bars outside of the session, or of the extended one when `extended` is set, are dropped.
+    fn resample_session(
+        &self,
+        timeframe: Timeframe,
+        session: &Session,
+        extended: bool,
+    ) -> Result<Self, BarBuilderError>
+    where
+        Self: Sized;
+    fn heikin_ashi(&self) -> Self;
+    /// Fixed copy, see `quality::repair`.
+    fn repair(&self, config: &OhlcvRepairConfig) -> Self;
 }
 
 pub trait OhlcvWriterOps: OhlcvWriter {
@@ -562,17 +608,32 @@ pub trait OhlcvWriterOps: OhlcvWriter {
     fn shift(&mut self) -> Option<OhlcvBar>;
 }
 
+/// Bars stored column by column, so price columns can be borrowed as `&[f64]` without copying.
 #[derive(Debug, Clone)]
 pub struct Ohlcv {
-    bars: Vec<OhlcvBar>,
+    open_time: Vec<Option<DateTime<Utc>>>,
+    close_time: Vec<Option<DateTime<Utc>>>,
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+    volume: Vec<f64>,
     timeframe: Timeframe,
+    timezone: Tz,
 }
 
 impl Default for Ohlcv {
     fn default() -> Self {
         Self {
-            bars: vec![],
+            open_time: vec![],
+            close_time: vec![],
+            open: vec![],
+            high: vec![],
+            low: vec![],
+            close: vec![],
+            volume: vec![],
             timeframe: Timeframe::Unknown(),
+            timezone: Tz::UTC,
         }
     }
 }
@@ -590,10 +651,60 @@ impl Ohlcv {
         Self::default()
     }
 
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            open_time: Vec::with_capacity(capacity),
+            close_time: Vec::with_capacity(capacity),
+            open: Vec::with_capacity(capacity),
+            high: Vec::with_capacity(capacity),
+            low: Vec::with_capacity(capacity),
+            close: Vec::with_capacity(capacity),
+            volume: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
     #[inline]
     pub fn from_bars(bars: Vec<OhlcvBar>) -> Self {
+        let mut ohlcv = Self::with_capacity(bars.len());
+        ohlcv.push_many(bars);
+        return ohlcv;
+    }
+
+    /// Takes ownership of the columns, all of which must have the same length.
+    pub fn from_columns(
+        open_time: Vec<Option<DateTime<Utc>>>,
+        close_time: Vec<Option<DateTime<Utc>>>,
+        open: Vec<f64>,
+        high: Vec<f64>,
+        low: Vec<f64>,
+        close: Vec<f64>,
+        volume: Vec<f64>,
+    ) -> Self {
+        let len = open.len();
+        for (name, column_len) in [
+            ("open_time", open_time.len()),
+            ("close_time", close_time.len()),
+            ("high", high.len()),
+            ("low", low.len()),
+            ("close", close.len()),
+            ("volume", volume.len()),
+        ] {
+            assert_eq!(
+                column_len, len,
+                "Expected {} {} values, got {}",
+                len, name, column_len
+            );
+        }
         Self {
-            bars,
+            open_time,
+            close_time,
+            open,
+            high,
+            low,
+            close,
+            volume,
             ..Self::default()
         }
     }
@@ -607,22 +718,105 @@ impl Ohlcv {
     pub fn set_timeframe(&mut self, timeframe: Timeframe) {
         self.timeframe = timeframe;
     }
+
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    #[inline]
+    pub fn set_timezone(&mut self, timezone: Tz) {
+        self.timezone = timezone;
+    }
+
+    #[inline]
+    pub fn open_time_slice(&self) -> &[Option<DateTime<Utc>>] {
+        &self.open_time
+    }
+
+    #[inline]
+    pub fn close_time_slice(&self) -> &[Option<DateTime<Utc>>] {
+        &self.close_time
+    }
+
+    #[inline]
+    pub fn open_slice(&self) -> &[f64] {
+        &self.open
+    }
+
+    #[inline]
+    pub fn high_slice(&self) -> &[f64] {
+        &self.high
+    }
+
+    #[inline]
+    pub fn low_slice(&self) -> &[f64] {
+        &self.low
+    }
+
+    #[inline]
+    pub fn close_slice(&self) -> &[f64] {
+        &self.close
+    }
+
+    #[inline]
+    pub fn volume_slice(&self) -> &[f64] {
+        &self.volume
+    }
+
+    #[inline]
+    fn with_bars(&self, bars: Vec<OhlcvBar>) -> Self {
+        let mut ohlcv = Self::from_bars(bars);
+        ohlcv.timeframe = self.timeframe;
+        ohlcv.timezone = self.timezone;
+        return ohlcv;
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> OhlcvBar {
+        return OhlcvBar::new(
+            self.open_time.remove(index),
+            self.close_time.remove(index),
+            self.open.remove(index),
+            self.high.remove(index),
+            self.low.remove(index),
+            self.close.remove(index),
+            self.volume.remove(index),
+        );
+    }
 }
 
 impl OhlcvReader for Ohlcv {
     #[inline]
     fn len(&self) -> usize {
-        self.bars.len()
+        self.open.len()
     }
 
     #[inline]
     fn get(&self, index: usize) -> Option<OhlcvBar> {
-        self.bars.get(index).copied()
+        if index >= self.len() {
+            return None;
+        }
+        return Some(OhlcvBar::new(
+            self.open_time[index],
+            self.close_time[index],
+            self.open[index],
+            self.high[index],
+            self.low[index],
+            self.close[index],
+            self.volume[index],
+        ));
     }
 
     #[inline]
     fn slice(&self, range: Range<usize>) -> Vec<OhlcvBar> {
-        self.bars[range].to_vec()
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "Range {:?} out of bounds {}",
+            range,
+            self.len()
+        );
+        range.map(|i| self.get(i).unwrap()).collect()
     }
 
     #[inline]
@@ -644,17 +838,74 @@ impl OhlcvReader for Ohlcv {
     fn timeframe(&self) -> Timeframe {
         self.timeframe
     }
-}
 
-impl OhlcvWriter for Ohlcv {
     #[inline]
-    fn push(&mut self, bar: OhlcvBar) {
-        self.bars.push(bar);
+    fn timezone(&self) -> Tz {
+        self.timezone
     }
 
     #[inline]
-    fn push_many(&mut self, bars: Vec<OhlcvBar>) {
-        self.bars.extend(bars);
+    fn open_time(&self) -> Vec<Option<DateTime<Utc>>> {
+        self.open_time.clone()
+    }
+
+    #[inline]
+    fn close_time(&self) -> Vec<Option<DateTime<Utc>>> {
+        self.close_time.clone()
+    }
+
+    #[inline]
+    fn open(&self) -> Vec<f64> {
+        self.open.clone()
+    }
+
+    #[inline]
+    fn high(&self) -> Vec<f64> {
+        self.high.clone()
+    }
+
+    #[inline]
+    fn low(&self) -> Vec<f64> {
+        self.low.clone()
+    }
+
+    #[inline]
+    fn close(&self) -> Vec<f64> {
+        self.close.clone()
+    }
+
+    #[inline]
+    fn volume(&self) -> Vec<f64> {
+        self.volume.clone()
+    }
+
+    #[cfg(feature = "polars")]
+    fn to_polars(&self) -> Result<DataFrame, PolarsError> {
+        let timestamp = |x: &Option<DateTime<Utc>>| x.map(|x| x.timestamp());
+        let open_time: Vec<Option<i64>> = self.open_time.iter().map(timestamp).collect();
+        let close_time: Vec<Option<i64>> = self.close_time.iter().map(timestamp).collect();
+        return DataFrame::new(vec![
+            Series::new("open_time", open_time),
+            Series::new("close_time", close_time),
+            Series::new("open", &self.open),
+            Series::new("high", &self.high),
+            Series::new("low", &self.low),
+            Series::new("close", &self.close),
+            Series::new("volume", &self.volume),
+        ]);
+    }
+}
+
+impl OhlcvWriter for Ohlcv {
+    #[inline]
+    fn push(&mut self, bar: OhlcvBar) {
+        self.open_time.push(bar.open_time);
+        self.close_time.push(bar.close_time);
+        self.open.push(bar.open);
+        self.high.push(bar.high);
+        self.low.push(bar.low);
+        self.close.push(bar.close);
+        self.volume.push(bar.volume);
     }
 
     #[inline]
@@ -665,17 +916,20 @@ impl OhlcvWriter for Ohlcv {
             index,
             self.len()
         );
-        self.bars[index] = bar;
+        self.open_time[index] = bar.open_time;
+        self.close_time[index] = bar.close_time;
+        self.open[index] = bar.open;
+        self.high[index] = bar.high;
+        self.low[index] = bar.low;
+        self.close[index] = bar.close;
+        self.volume[index] = bar.volume;
     }
 }
 
 impl OhlcvReaderOps for Ohlcv {
     #[inline]
     fn copy(&self) -> Self {
-        return Self {
-            bars: self.bars(),
-            timeframe: self.timeframe,
-        };
+        return self.clone();
     }
 
     #[inline]
@@ -692,40 +946,103 @@ impl OhlcvReaderOps for Ohlcv {
     }
 
     #[inline]
-    fn resample(&self, timeframe: Timeframe, align_utc: bool) -> Self {
-        let bars = resample(&self.bars, timeframe, align_utc);
-        Self { bars, timeframe }
+    fn resample_with_tick(
+        &self,
+        timeframe: Timeframe,
+        align: bool,
+        min_tick: f64,
+    ) -> Result<Self, BarBuilderError> {
+        let mut ohlcv = self.with_bars(resample(
+            &self.bars(),
+            timeframe,
+            align,
+            &self.timezone,
+            min_tick,
+        )?);
+        ohlcv.timeframe = timeframe;
+        return Ok(ohlcv);
+    }
+
+    #[inline]
+    fn resample_session(
+        &self,
+        timeframe: Timeframe,
+        session: &Session,
+        extended: bool,
+    ) -> Result<Self, BarBuilderError> {
+        let mut ohlcv = Self::from_bars(resample_session(
+            &self.bars(),
+            timeframe,
+            session,
+            extended,
+        )?);
+        ohlcv.timeframe = timeframe;
+        ohlcv.timezone = session.timezone();
+        return Ok(ohlcv);
+    }
+
+    #[inline]
+    fn heikin_ashi(&self) -> Self {
+        self.with_bars(heikin_ashi(&self.bars()))
+    }
+
+    #[inline]
+    fn repair(&self, config: &OhlcvRepairConfig) -> Self {
+        self.with_bars(repair(&self.bars(), config))
     }
 }
 
 impl OhlcvWriterOps for Ohlcv {
     #[inline]
     fn extend(&mut self, other: &Self) {
-        self.bars.extend(other.bars());
+        self.open_time.extend_from_slice(&other.open_time);
+        self.close_time.extend_from_slice(&other.close_time);
+        self.open.extend_from_slice(&other.open);
+        self.high.extend_from_slice(&other.high);
+        self.low.extend_from_slice(&other.low);
+        self.close.extend_from_slice(&other.close);
+        self.volume.extend_from_slice(&other.volume);
     }
 
     #[inline]
     fn sort(&mut self, ascending: bool) {
+        let mut bars = self.bars();
         if ascending {
-            self.bars.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+            bars.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
         } else {
-            self.bars.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
+            bars.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
         }
+        *self = self.with_bars(bars);
     }
 
     #[inline]
     fn reverse(&mut self) {
-        self.bars.reverse();
+        self.open_time.reverse();
+        self.close_time.reverse();
+        self.open.reverse();
+        self.high.reverse();
+        self.low.reverse();
+        self.close.reverse();
+        self.volume.reverse();
     }
 
     #[inline]
     fn clear(&mut self) {
-        self.bars.clear();
+        self.open_time.clear();
+        self.close_time.clear();
+        self.open.clear();
+        self.high.clear();
+        self.low.clear();
+        self.close.clear();
+        self.volume.clear();
     }
 
     #[inline]
     fn pop(&mut self) -> Option<OhlcvBar> {
-        self.bars.pop()
+        if self.len() == 0 {
+            return None;
+        }
+        return Some(self.remove(self.len() - 1));
     }
 
     #[inline]
@@ -733,7 +1050,7 @@ impl OhlcvWriterOps for Ohlcv {
         if self.len() == 0 {
             return None;
         }
-        return Some(self.bars.remove(0));
+        return Some(self.remove(0));
     }
 }
 
@@ -786,6 +1103,22 @@ impl RcOhlcv {
     pub fn set_timeframe(&self, timeframe: Timeframe) {
         self.inner.borrow_mut().set_timeframe(timeframe);
     }
+
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.inner.borrow().timezone()
+    }
+
+    #[inline]
+    pub fn set_timezone(&self, timezone: Tz) {
+        self.inner.borrow_mut().set_timezone(timezone);
+    }
+
+    /// Borrowed columns, e.g. `ohlcv.borrow().close_slice()`.
+    #[inline]
+    pub fn borrow(&self) -> Ref<'_, Ohlcv> {
+        self.inner.borrow()
+    }
 }
 
 impl OhlcvReader for RcOhlcv {
@@ -823,6 +1156,46 @@ impl OhlcvReader for RcOhlcv {
     fn timeframe(&self) -> Timeframe {
         self.inner.borrow().timeframe()
     }
+
+    #[inline]
+    fn timezone(&self) -> Tz {
+        self.inner.borrow().timezone()
+    }
+
+    #[inline]
+    fn open_time(&self) -> Vec<Option<DateTime<Utc>>> {
+        self.inner.borrow().open_time()
+    }
+
+    #[inline]
+    fn close_time(&self) -> Vec<Option<DateTime<Utc>>> {
+        self.inner.borrow().close_time()
+    }
+
+    #[inline]
+    fn open(&self) -> Vec<f64> {
+        self.inner.borrow().open()
+    }
+
+    #[inline]
+    fn high(&self) -> Vec<f64> {
+        self.inner.borrow().high()
+    }
+
+    #[inline]
+    fn low(&self) -> Vec<f64> {
+        self.inner.borrow().low()
+    }
+
+    #[inline]
+    fn close(&self) -> Vec<f64> {
+        self.inner.borrow().close()
+    }
+
+    #[inline]
+    fn volume(&self) -> Vec<f64> {
+        self.inner.borrow().volume()
+    }
 }
 
 impl OhlcvWriter for RcOhlcv {
@@ -859,8 +1232,39 @@ impl OhlcvReaderOps for RcOhlcv {
     }
 
     #[inline]
-    fn resample(&self, timeframe: Timeframe, align_utc: bool) -> Self {
-        self.inner.borrow().resample(timeframe, align_utc).into()
+    fn resample_with_tick(
+        &self,
+        timeframe: Timeframe,
+        align: bool,
+        min_tick: f64,
+    ) -> Result<Self, BarBuilderError> {
+        self.inner
+            .borrow()
+            .resample_with_tick(timeframe, align, min_tick)
+            .map(Into::into)
+    }
+
+    #[inline]
+    fn resample_session(
+        &self,
+        timeframe: Timeframe,
+        session: &Session,
+        extended: bool,
+    ) -> Result<Self, BarBuilderError> {
+        self.inner
+            .borrow()
+            .resample_session(timeframe, session, extended)
+            .map(Into::into)
+    }
+
+    #[inline]
+    fn heikin_ashi(&self) -> Self {
+        self.inner.borrow().heikin_ashi().into()
+    }
+
+    #[inline]
+    fn repair(&self, config: &OhlcvRepairConfig) -> Self {
+        self.inner.borrow().repair(config).into()
     }
 }
 
@@ -945,6 +1349,22 @@ impl ArcOhlcv {
     pub fn set_timeframe(&self, timeframe: Timeframe) {
         self.inner.write().unwrap().set_timeframe(timeframe);
     }
+
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.inner.read().unwrap().timezone()
+    }
+
+    #[inline]
+    pub fn set_timezone(&self, timezone: Tz) {
+        self.inner.write().unwrap().set_timezone(timezone);
+    }
+
+    /// Borrowed columns, e.g. `ohlcv.read().close_slice()`.
+    #[inline]
+    pub fn read(&self) -> RwLockReadGuard<'_, Ohlcv> {
+        self.inner.read().unwrap()
+    }
 }
 
 impl OhlcvReader for ArcOhlcv {
@@ -982,6 +1402,46 @@ impl OhlcvReader for ArcOhlcv {
     fn timeframe(&self) -> Timeframe {
         self.inner.read().unwrap().timeframe()
     }
+
+    #[inline]
+    fn timezone(&self) -> Tz {
+        self.inner.read().unwrap().timezone()
+    }
+
+    #[inline]
+    fn open_time(&self) -> Vec<Option<DateTime<Utc>>> {
+        self.inner.read().unwrap().open_time()
+    }
+
+    #[inline]
+    fn close_time(&self) -> Vec<Option<DateTime<Utc>>> {
+        self.inner.read().unwrap().close_time()
+    }
+
+    #[inline]
+    fn open(&self) -> Vec<f64> {
+        self.inner.read().unwrap().open()
+    }
+
+    #[inline]
+    fn high(&self) -> Vec<f64> {
+        self.inner.read().unwrap().high()
+    }
+
+    #[inline]
+    fn low(&self) -> Vec<f64> {
+        self.inner.read().unwrap().low()
+    }
+
+    #[inline]
+    fn close(&self) -> Vec<f64> {
+        self.inner.read().unwrap().close()
+    }
+
+    #[inline]
+    fn volume(&self) -> Vec<f64> {
+        self.inner.read().unwrap().volume()
+    }
 }
 
 impl OhlcvWriter for ArcOhlcv {
@@ -1018,12 +1478,41 @@ impl OhlcvReaderOps for ArcOhlcv {
     }
 
     #[inline]
-    fn resample(&self, timeframe: Timeframe, align_utc: bool) -> Self {
+    fn resample_with_tick(
+        &self,
+        timeframe: Timeframe,
+        align: bool,
+        min_tick: f64,
+    ) -> Result<Self, BarBuilderError> {
         self.inner
             .read()
             .unwrap()
-            .resample(timeframe, align_utc)
-            .into()
+            .resample_with_tick(timeframe, align, min_tick)
+            .map(Into::into)
+    }
+
+    #[inline]
+    fn resample_session(
+        &self,
+        timeframe: Timeframe,
+        session: &Session,
+        extended: bool,
+    ) -> Result<Self, BarBuilderError> {
+        self.inner
+            .read()
+            .unwrap()
+            .resample_session(timeframe, session, extended)
+            .map(Into::into)
+    }
+
+    #[inline]
+    fn heikin_ashi(&self) -> Self {
+        self.inner.read().unwrap().heikin_ashi().into()
+    }
+
+    #[inline]
+    fn repair(&self, config: &OhlcvRepairConfig) -> Self {
+        self.inner.read().unwrap().repair(config).into()
     }
 }
 
@@ -1063,19 +1552,73 @@ impl OhlcvWriterOps for ArcOhlcv {
 }
 
 #[inline]
-fn resample(bars: &[OhlcvBar], timeframe: Timeframe, align_utc: bool) -> Vec<OhlcvBar> {
-    match timeframe {
+fn resample(
+    bars: &[OhlcvBar],
+    timeframe: Timeframe,
+    align: bool,
+    tz: &Tz,
+    min_tick: f64,
+) -> Result<Vec<OhlcvBar>, BarBuilderError> {
+    let bars = match timeframe {
+        Timeframe::Unknown() => return Err(BarBuilderError::UnknownTimeframe),
         Timeframe::Ticks(n) => group_by_count(bars, n),
-        Timeframe::Ranges(n) => group_by_count(bars, n),
+        Timeframe::Ranges(_)
+        | Timeframe::Volume(_)
+        | Timeframe::Dollars(_)
+        | Timeframe::Renko(_) => {
+            let mut builder = BarBuilder::try_new(timeframe, min_tick)?;
+            builder.set_timezone(*tz);
+            for bar in bars {
+                builder.push_bar(bar);
+            }
+            builder.finish().bars()
+        }
         _ => {
-            if align_utc {
-                group_by_aligned_time(bars, timeframe)
+            if align {
+                group_by_aligned_time(bars, timeframe, tz)
             } else {
                 // rolling time-based grouping (no pinned boundary)
                 group_by_time(bars, timeframe.try_into().unwrap())
             }
         }
+    };
+    return Ok(bars);
+}
+
+fn resample_session(
+    bars: &[OhlcvBar],
+    timeframe: Timeframe,
+    session: &Session,
+    extended: bool,
+) -> Result<Vec<OhlcvBar>, BarBuilderError> {
+    let in_session = |bar: &&OhlcvBar| {
+        bar.open_time
+            .is_some_and(|time| session.is_open(time, extended))
+    };
+    if !timeframe.is_time_based() {
+        let bars: Vec<OhlcvBar> = bars.iter().filter(in_session).copied().collect();
+        return resample(&bars, timeframe, true, &session.timezone(), f64::NAN);
     }
+    let mut result: Vec<OhlcvBar> = Vec::new();
+    let mut buckets: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for bar in bars {
+        let time = bar.open_time.expect("Bar must have an open time");
+        let Some(bucket) = session.bucket(time, timeframe, extended) else {
+            continue;
+        };
+        if buckets.last() == Some(&bucket) {
+            let agg = result.last_mut().unwrap();
+            *agg = agg.merge(bar);
+        } else {
+            result.push(*bar);
+            buckets.push(bucket);
+        }
+    }
+    for (bar, (start, end)) in result.iter_mut().zip(buckets) {
+        bar.open_time = Some(start);
+        bar.close_time = Some(end);
+    }
+    return Ok(result);
 }
 
 fn group_by_count(bars: &[OhlcvBar], n: usize) -> Vec<OhlcvBar> {
@@ -1154,7 +1697,16 @@ fn group_by_time(bars: &[OhlcvBar], duration: Duration) -> Vec<OhlcvBar> {
 /// • Timeframe::Weeks(1) → Monday of that ISO week at 00:00:00
 /// For multiples (e.g. Months(3)), we use an anchor reference (1970-01-01, or Monday for weeks)
 /// and snap down to the largest multiple of n that does not exceed dt.
-fn floor_to_timeframe(
+/// Boundaries are wall clock times in `tz`, so `Days(1)` in New York starts at local midnight.
+pub(crate) fn floor_to_timeframe(dt: DateTime<Utc>, tf: &Timeframe, tz: &Tz) -> DateTime<Utc> {
+    if *tz == Tz::UTC {
+        return floor_to_timeframe_utc(dt, tf);
+    }
+    let local = Utc.from_utc_datetime(&dt.with_timezone(tz).naive_local());
+    return local_to_utc(tz, floor_to_timeframe_utc(local, tf).naive_utc());
+}
+
+fn floor_to_timeframe_utc(
     dt: chrono::DateTime<chrono::Utc>,
     tf: &Timeframe,
 ) -> chrono::DateTime<chrono::Utc> {
@@ -1327,20 +1879,42 @@ fn floor_to_timeframe(
     }
 }
 
-// Step 2: A small helper that forces aggregator.close_time to the *end* of that bucket.
-fn finalize_aggregator(agg: &mut OhlcvBar, tf: &Timeframe) {
+/// Start of the interval following the one that contains `dt`.
+/// Months and years are calendar aware, so `Months(1)` starting at 2024-01-01 ends at 2024-02-01.
+pub(crate) fn next_timeframe_boundary(dt: DateTime<Utc>, tf: &Timeframe, tz: &Tz) -> DateTime<Utc> {
+    if *tz == Tz::UTC {
+        return next_timeframe_boundary_utc(dt, tf);
+    }
+    let local = Utc.from_utc_datetime(&dt.with_timezone(tz).naive_local());
+    return local_to_utc(tz, next_timeframe_boundary_utc(local, tf).naive_utc());
+}
+
+fn next_timeframe_boundary_utc(
+    dt: chrono::DateTime<chrono::Utc>,
+    tf: &Timeframe,
+) -> chrono::DateTime<chrono::Utc> {
+    let start = floor_to_timeframe_utc(dt, tf);
     match tf {
-        // If you want daily bars to end exactly at next midnight:
-        Timeframe::Days(n) => {
-            // close_time = open_time + (n * 24 hours)
-            let offset = chrono::Duration::days(*n as i64);
-            agg.close_time = Some(agg.open_time.unwrap() + offset);
+        Timeframe::Years(n) => start
+            .checked_add_months(chrono::Months::new((*n as u32) * 12))
+            .unwrap(),
+        Timeframe::Months(n) => start
+            .checked_add_months(chrono::Months::new(*n as u32))
+            .unwrap(),
+        _ => {
+            let duration: Duration = (*tf).try_into().unwrap();
+            start + duration
         }
+    }
+}
 
-        // If you want hour bars pinned similarly, do the same for Hours(n).
-        Timeframe::Hours(n) => {
-            let offset = chrono::Duration::hours(*n as i64);
-            agg.close_time = Some(agg.open_time.unwrap() + offset);
+// Step 2: A small helper that forces aggregator.close_time to the *end* of that bucket.
+fn finalize_aggregator(agg: &mut OhlcvBar, tf: &Timeframe, tz: &Tz) {
+    match tf {
+        // Daily and hourly bars end exactly at the next boundary, which is not always
+        // n * 24 hours later in time zones with DST.
+        Timeframe::Days(_) | Timeframe::Hours(_) => {
+            agg.close_time = Some(next_timeframe_boundary(agg.open_time.unwrap(), tf, tz));
         }
 
         // Otherwise leave aggregator.close_time as the last intraday bar timestamp
@@ -1350,13 +1924,13 @@ fn finalize_aggregator(agg: &mut OhlcvBar, tf: &Timeframe) {
 
 // Step 3: group_by_aligned_time that sets open_time to the floored boundary
 // and calls finalize_aggregator whenever we finish a bucket.
-fn group_by_aligned_time(bars: &[OhlcvBar], timeframe: Timeframe) -> Vec<OhlcvBar> {
+fn group_by_aligned_time(bars: &[OhlcvBar], timeframe: Timeframe, tz: &Tz) -> Vec<OhlcvBar> {
     let mut result = Vec::new();
     let mut aggregator: Option<OhlcvBar> = None;
     let mut current_bucket: Option<chrono::DateTime<chrono::Utc>> = None;
 
     for bar in bars {
-        let bucket = floor_to_timeframe(bar.open_time.unwrap(), &timeframe);
+        let bucket = floor_to_timeframe(bar.open_time.unwrap(), &timeframe, tz);
 
         match aggregator {
             None => {
@@ -1373,7 +1947,7 @@ fn group_by_aligned_time(bars: &[OhlcvBar], timeframe: Timeframe) -> Vec<OhlcvBa
                     aggregator = Some(agg);
                 } else {
                     // finalize old aggregator
-                    finalize_aggregator(&mut agg, &timeframe);
+                    finalize_aggregator(&mut agg, &timeframe, tz);
                     result.push(agg);
 
                     // start next aggregator
@@ -1387,7 +1961,7 @@ fn group_by_aligned_time(bars: &[OhlcvBar], timeframe: Timeframe) -> Vec<OhlcvBa
     }
     // flush last aggregator
     if let Some(mut agg) = aggregator {
-        finalize_aggregator(&mut agg, &timeframe);
+        finalize_aggregator(&mut agg, &timeframe, tz);
         result.push(agg);
     }
 
@@ -1395,18 +1969,18 @@ fn group_by_aligned_time(bars: &[OhlcvBar], timeframe: Timeframe) -> Vec<OhlcvBa
 }
 
 /*
-Below is the overall idea behind “align_utc” in the resampling logic:
+Below is the overall idea behind “align” in the resampling logic:
 
-• When align_utc = false:
+• When align = false:
 The resampler performs a simple “rolling” grouping. It takes the earliest bar’s timestamp as a starting point and groups subsequent bars in fixed-length durations (e.g., every 24 hours for daily). This causes each group/window to shift relative to the actual calendar boundaries.
 
-• When align_utc = true:
-The resampler “pins” the bars to calendar-friendly boundaries in UTC. For example:
+• When align = true:
+The resampler “pins” the bars to calendar-friendly boundaries in the Ohlcv time zone (UTC by default). For example:
 – Daily bars get folded into the 00:00–23:59 UTC bucket for each calendar day, rather than just any 24-hour interval.
 – Weekly bars get anchored to Monday 00:00 UTC and end on Sunday 23:59 UTC (or the next Monday’s boundary).
 – Monthly bars align to the first day of the month at 00:00 UTC, and so on for other timeframes.
 
-This alignment makes the resampled bars match conventional calendar frames (like TradingView’s daily bars, which always cover midnight-to-midnight UTC). If you do not set align_utc (or set it to false), the resampling instead uses rolling windows that begin wherever your data starts, not necessarily on a neat boundary like midnight UTC.
+This alignment makes the resampled bars match conventional calendar frames (like TradingView’s daily bars, which always cover midnight-to-midnight UTC). Exchanges that define their days in local time (e.g. weekly bars of an Asian or US market) need their IANA time zone set with `set_timezone`. If you do not set align (or set it to false), the resampling instead uses rolling windows that begin wherever your data starts, not necessarily on a neat boundary like midnight UTC.
 */
 
 #[inline]
@@ -1420,60 +1994,12 @@ fn sort_bars_inplace(bars: &mut [OhlcvBar], desc: bool) {
 
 #[cfg(feature = "polars")]
 #[inline]
-fn ohlcv_bars_from_polars(df: &DataFrame, time_unit: &str) -> Vec<OhlcvBar> {
-    let cols = df.get_column_names();
-
-    let open = Some(df.column("open").unwrap().to_f64());
-    let high = Some(df.column("high").unwrap().to_f64());
-    let low = Some(df.column("low").unwrap().to_f64());
-    let close = Some(df.column("close").unwrap().to_f64());
-
-    let volume = if cols.contains(&"volume") {
-        Some(df.column("volume").unwrap().to_f64())
-    } else {
-        None
-    };
-
-    let open_time_col = if cols.contains(&"open_time") {
-        Some("open_time")
-    } else if cols.contains(&"time") {
-        Some("time")
-    } else {
-        None
-    };
-    let close_time_col = if cols.contains(&"close_time") {
-        Some("close_time")
-    } else if cols.contains(&"time") {
-        Some("time")
-    } else {
-        None
-    };
-
-    let open_time = if let Some(col) = open_time_col {
-        if time_unit == "s" {
-            Some(df.column(col).unwrap().to_datetime_from_s())
-        } else if time_unit == "ms" {
-            Some(df.column(col).unwrap().to_datetime_from_ms())
-        } else {
-            panic!("Invalid open time unit: {}", time_unit);
-        }
-    } else {
-        None
-    };
-
-    let close_time = if let Some(col) = close_time_col {
-        if time_unit == "s" {
-            Some(df.column(col).unwrap().to_datetime_from_s())
-        } else if time_unit == "ms" {
-            Some(df.column(col).unwrap().to_datetime_from_ms())
-        } else {
-            panic!("Invalid close time unit: {}", time_unit);
-        }
-    } else {
-        None
-    };
-
-    return zip_ohlcv_bars(open_time, close_time, open, high, low, close, volume);
+pub(crate) fn ohlcv_bars_from_polars(
+    df: &DataFrame,
+    time_format: OhlcvTimeFormat,
+) -> Result<Vec<OhlcvBar>, OhlcvIoError> {
+    let config = OhlcvIoConfig::new(time_format);
+    return Ok(ohlcv_from_polars(df, &config)?.bars());
 }
 
 #[inline]
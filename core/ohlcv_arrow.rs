@@ -0,0 +1,288 @@
+use std::io::Cursor;
+
+use arrow2::{
+    array::{Array, PrimitiveArray, StructArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    io::ipc::{
+        read::{read_file_metadata, read_stream_metadata, FileReader, StreamReader, StreamState},
+        write::{StreamWriter, WriteOptions},
+    },
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use thiserror::Error;
+
+use crate::{
+    ohlcv::{Ohlcv, OhlcvWriterOps},
+    ohlcv_io::{close_times_of, OhlcvIoConfig, OhlcvTimeFormat},
+};
+
+#[derive(Debug, Error)]
+pub enum OhlcvArrowError {
+    #[error(transparent)]
+    Arrow(#[from] arrow2::error::Error),
+    #[error("Expected a struct array or a table, got {0:?}")]
+    NotStruct(DataType),
+    #[error("Expected a numeric {0} column, got {1:?}")]
+    NotNumeric(String, DataType),
+    #[error("Expected a timestamp {0} column, got {1:?}")]
+    NotTimestamp(String, DataType),
+    #[error("Invalid time in {0} column: {1:?}")]
+    InvalidTime(String, String),
+}
+
+#[inline]
+fn time_type() -> DataType {
+    return DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".to_string()));
+}
+
+/// `open_time`, `close_time` as UTC millisecond timestamps, followed by `open`, `high`, `low`, `close` and `volume`.
+pub fn ohlcv_arrow_fields() -> Vec<Field> {
+    return vec![
+        Field::new("open_time", time_type(), true),
+        Field::new("close_time", time_type(), true),
+        Field::new("open", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("volume", DataType::Float64, false),
+    ];
+}
+
+fn to_columns(ohlcv: &Ohlcv) -> Vec<Box<dyn Array>> {
+    let time = |column: &[Option<DateTime<Utc>>]| {
+        let values: Vec<Option<i64>> = column
+            .iter()
+            .map(|x| x.map(|x| x.timestamp_millis()))
+            .collect();
+        PrimitiveArray::<i64>::from(values).to(time_type()).boxed()
+    };
+    return vec![
+        time(ohlcv.open_time_slice()),
+        time(ohlcv.close_time_slice()),
+        PrimitiveArray::<f64>::from_slice(ohlcv.open_slice()).boxed(),
+        PrimitiveArray::<f64>::from_slice(ohlcv.high_slice()).boxed(),
+        PrimitiveArray::<f64>::from_slice(ohlcv.low_slice()).boxed(),
+        PrimitiveArray::<f64>::from_slice(ohlcv.close_slice()).boxed(),
+        PrimitiveArray::<f64>::from_slice(ohlcv.volume_slice()).boxed(),
+    ];
+}
+
+/// Single struct array, the shape of a record batch in the C data interface.
+pub fn ohlcv_to_arrow(ohlcv: &Ohlcv) -> (Field, Box<dyn Array>) {
+    let data_type = DataType::Struct(ohlcv_arrow_fields());
+    let array = StructArray::new(data_type.clone(), to_columns(ohlcv), None);
+    return (Field::new("", data_type, false), array.boxed());
+}
+
+fn f64_column(name: &str, array: &dyn Array) -> Result<Vec<f64>, OhlcvArrowError> {
+    macro_rules! cast {
+        ($($t:ty),*) => {$(
+            if let Some(array) = array.as_any().downcast_ref::<PrimitiveArray<$t>>() {
+                return Ok(array
+                    .iter()
+                    .map(|x| x.map(|x| *x as f64).unwrap_or(f64::NAN))
+                    .collect());
+            }
+        )*};
+    }
+    cast!(f64, f32, i64, i32, u64, u32);
+    return Err(OhlcvArrowError::NotNumeric(
+        name.to_string(),
+        array.data_type().clone(),
+    ));
+}
+
+/// Timestamps of any unit and dates keep their own unit, numbers and text are read with `time_format`.
+fn time_column(
+    name: &str,
+    array: &dyn Array,
+    time_format: OhlcvTimeFormat,
+) -> Result<Vec<Option<DateTime<Utc>>>, OhlcvArrowError> {
+    let format = match array.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => OhlcvTimeFormat::Seconds,
+        DataType::Timestamp(TimeUnit::Millisecond, _) | DataType::Date64 => {
+            OhlcvTimeFormat::Milliseconds
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => OhlcvTimeFormat::Microseconds,
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => OhlcvTimeFormat::Nanoseconds,
+        _ => time_format,
+    };
+    let invalid = |value: String| OhlcvArrowError::InvalidTime(name.to_string(), value);
+    macro_rules! cast {
+        ($t:ty, $from:ident) => {
+            if let Some(array) = array.as_any().downcast_ref::<PrimitiveArray<$t>>() {
+                return array
+                    .iter()
+                    .map(|x| match x {
+                        Some(x) => format
+                            .$from(*x as _)
+                            .map(Some)
+                            .ok_or_else(|| invalid(x.to_string())),
+                        None => Ok(None),
+                    })
+                    .collect();
+            }
+        };
+    }
+    macro_rules! parse {
+        ($o:ty) => {
+            if let Some(array) = array.as_any().downcast_ref::<Utf8Array<$o>>() {
+                return array
+                    .iter()
+                    .map(|x| match x {
+                        Some(x) => format
+                            .parse(x)
+                            .map(Some)
+                            .ok_or_else(|| invalid(x.to_string())),
+                        None => Ok(None),
+                    })
+                    .collect();
+            }
+        };
+    }
+    if *array.data_type() == DataType::Date32 {
+        let array = array
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .unwrap();
+        return Ok(array
+            .iter()
+            .map(|x| x.and_then(|x| OhlcvTimeFormat::Seconds.from_i64(*x as i64 * 86_400)))
+            .collect());
+    }
+    cast!(i64, from_i64);
+    cast!(i32, from_i64);
+    cast!(u32, from_i64);
+    cast!(f64, from_f64);
+    parse!(i32);
+    parse!(i64);
+    return Err(OhlcvArrowError::NotTimestamp(
+        name.to_string(),
+        array.data_type().clone(),
+    ));
+}
+
+/// Columns are matched by `config.columns`, `time` stands in for a missing `open_time`.
+/// Missing prices and volume are NaN, missing times are `None`.
+fn from_columns(
+    fields: &[Field],
+    arrays: &[Box<dyn Array>],
+    len: usize,
+    config: &OhlcvIoConfig,
+) -> Result<Ohlcv, OhlcvArrowError> {
+    let columns = &config.columns;
+    let column = |names: &[Option<&str>]| {
+        names.iter().flatten().find_map(|name| {
+            fields
+                .iter()
+                .position(|field| field.name == *name)
+                .map(|i| (fields[i].name.as_str(), arrays[i].as_ref()))
+        })
+    };
+    let time = |names: &[Option<&str>]| match column(names) {
+        Some((name, array)) => time_column(name, array, config.time_format).map(Some),
+        None => Ok(None),
+    };
+    let float = |name: Option<&str>| match column(&[name]) {
+        Some((name, array)) => f64_column(name, array),
+        None => Ok(vec![f64::NAN; len]),
+    };
+    let open_time = time(&[columns.open_time.as_deref(), Some("time")])?.unwrap_or(vec![None; len]);
+    let close_time = match time(&[columns.close_time.as_deref()])? {
+        Some(close_time) => close_time,
+        None => close_times_of(&open_time, config.timeframe),
+    };
+    return Ok(Ohlcv::from_columns(
+        open_time,
+        close_time,
+        float(Some(&columns.open))?,
+        float(Some(&columns.high))?,
+        float(Some(&columns.low))?,
+        float(Some(&columns.close))?,
+        float(columns.volume.as_deref())?,
+    ));
+}
+
+/// Zone of the open time timestamps, e.g. `America/New_York`. Fixed offsets and unknown names are ignored.
+fn timezone_of(fields: &[Field], config: &OhlcvIoConfig) -> Option<Tz> {
+    let field = [config.columns.open_time.as_deref(), Some("time")]
+        .into_iter()
+        .flatten()
+        .find_map(|name| fields.iter().find(|field| field.name == name))?;
+    return match field.data_type() {
+        DataType::Timestamp(_, Some(tz)) => tz.parse().ok(),
+        _ => None,
+    };
+}
+
+/// Concatenates struct arrays, e.g. record batches imported through the C data interface.
+/// The zone of the open time column becomes the timezone of the result.
+pub fn ohlcv_from_arrow(
+    batches: impl IntoIterator<Item = Box<dyn Array>>,
+    config: &OhlcvIoConfig,
+) -> Result<Ohlcv, OhlcvArrowError> {
+    let mut ohlcv = Ohlcv::new();
+    ohlcv.set_timeframe(config.timeframe);
+    for batch in batches {
+        let batch = batch
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .ok_or_else(|| OhlcvArrowError::NotStruct(batch.data_type().clone()))?;
+        if let Some(timezone) = timezone_of(batch.fields(), config) {
+            ohlcv.set_timezone(timezone);
+        }
+        ohlcv.extend(&from_columns(
+            batch.fields(),
+            batch.values(),
+            batch.len(),
+            config,
+        )?);
+    }
+    return Ok(ohlcv);
+}
+
+/// Arrow IPC stream, readable by `tableFromIPC` of apache-arrow or `pyarrow.ipc.open_stream`.
+pub fn write_ohlcv_ipc(ohlcv: &Ohlcv) -> Result<Vec<u8>, OhlcvArrowError> {
+    let schema = Schema::from(ohlcv_arrow_fields());
+    let mut writer = StreamWriter::new(vec![], WriteOptions { compression: None });
+    writer.start(&schema, None)?;
+    writer.write(&Chunk::new(to_columns(ohlcv)), None)?;
+    writer.finish()?;
+    return Ok(writer.into_inner());
+}
+
+/// Reads an Arrow IPC stream or file, every record batch is appended.
+/// The zone of the open time column becomes the timezone of the result.
+pub fn read_ohlcv_ipc(bytes: &[u8], config: &OhlcvIoConfig) -> Result<Ohlcv, OhlcvArrowError> {
+    let mut reader = Cursor::new(bytes);
+    let mut ohlcv = Ohlcv::new();
+    ohlcv.set_timeframe(config.timeframe);
+    if bytes.starts_with(b"ARROW1") {
+        let metadata = read_file_metadata(&mut reader)?;
+        let fields = metadata.schema.fields.clone();
+        if let Some(timezone) = timezone_of(&fields, config) {
+            ohlcv.set_timezone(timezone);
+        }
+        for chunk in FileReader::new(reader, metadata, None, None) {
+            let chunk = chunk?;
+            ohlcv.extend(&from_columns(&fields, chunk.arrays(), chunk.len(), config)?);
+        }
+        return Ok(ohlcv);
+    }
+    let metadata = read_stream_metadata(&mut reader)?;
+    let fields = metadata.schema.fields.clone();
+    if let Some(timezone) = timezone_of(&fields, config) {
+        ohlcv.set_timezone(timezone);
+    }
+    for state in StreamReader::new(reader, metadata, None) {
+        match state? {
+            StreamState::Some(chunk) => {
+                ohlcv.extend(&from_columns(&fields, chunk.arrays(), chunk.len(), config)?);
+            }
+            StreamState::Waiting => break,
+        }
+    }
+    return Ok(ohlcv);
+}
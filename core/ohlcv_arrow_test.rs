@@ -0,0 +1,86 @@
+#[cfg(test)]
+#[cfg(feature = "arrow")]
+mod test {
+    use arrow2::{
+        array::{PrimitiveArray, StructArray},
+        datatypes::{DataType, Field, TimeUnit},
+    };
+    use chrono_tz::Tz;
+
+    use crate::{
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        ohlcv_arrow::{
+            ohlcv_from_arrow, ohlcv_to_arrow, read_ohlcv_ipc, write_ohlcv_ipc, OhlcvArrowError,
+        },
+        ohlcv_io::OhlcvIoConfig,
+        test_utils::time,
+    };
+
+    fn ohlcv() -> Ohlcv {
+        let mut bars: Vec<OhlcvBar> = (0..10)
+            .map(|i| {
+                let price = 100.0 + i as f64;
+                OhlcvBar::new(
+                    Some(time(i)),
+                    Some(time(i + 1)),
+                    price,
+                    price + 1.0,
+                    price - 1.0,
+                    price + 0.5,
+                    i as f64,
+                )
+            })
+            .collect();
+        bars[3].set_close_time(None);
+        return Ohlcv::from_bars(bars);
+    }
+
+    #[test]
+    pub fn ipc_roundtrip() {
+        let ohlcv = ohlcv();
+        let bytes = write_ohlcv_ipc(&ohlcv).unwrap();
+        let read = read_ohlcv_ipc(&bytes, &OhlcvIoConfig::default()).unwrap();
+        assert_eq!(read.bars(), ohlcv.bars());
+        assert_eq!(read.timezone(), Tz::UTC);
+        assert!(read_ohlcv_ipc(&bytes[..16], &OhlcvIoConfig::default()).is_err());
+    }
+
+    #[test]
+    pub fn struct_roundtrip() {
+        let ohlcv = ohlcv();
+        let (_, array) = ohlcv_to_arrow(&ohlcv);
+        let read = ohlcv_from_arrow(vec![array.clone(), array], &OhlcvIoConfig::default()).unwrap();
+        assert_eq!(read.len(), 20);
+        assert_eq!(read.slice(10..20), ohlcv.bars());
+
+        // seconds in a named zone, a shared time column and integer prices, no volume
+        let time_type = DataType::Timestamp(TimeUnit::Second, Some("America/New_York".to_string()));
+        let fields = vec![
+            Field::new("time", time_type.clone(), false),
+            Field::new("close", DataType::Int64, false),
+        ];
+        let columns = vec![
+            PrimitiveArray::<i64>::from_vec(vec![time(0).timestamp(), time(1).timestamp()])
+                .to(time_type)
+                .boxed(),
+            PrimitiveArray::<i64>::from_vec(vec![1, 2]).boxed(),
+        ];
+        let array = StructArray::new(DataType::Struct(fields), columns, None).boxed();
+        let read = ohlcv_from_arrow(vec![array], &OhlcvIoConfig::default()).unwrap();
+        let bar = read.get(1).unwrap();
+        assert_eq!(bar.open_time(), Some(&time(1)));
+        assert_eq!(bar.close_time(), Some(&time(1)));
+        assert_eq!(bar.close(), 2.0);
+        assert!(bar.volume().is_nan());
+        assert_eq!(read.timezone(), Tz::America__New_York);
+
+        let error = ohlcv_from_arrow(
+            vec![PrimitiveArray::<f64>::from_vec(vec![1.0]).boxed()],
+            &OhlcvIoConfig::default(),
+        );
+        assert!(matches!(
+            error,
+            Err(OhlcvArrowError::NotStruct(DataType::Float64))
+        ));
+    }
+}
@@ -0,0 +1,330 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tonic::{
+    metadata::MetadataValue,
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Request, Response, Status,
+};
+
+use crate::{
+    ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvWriter},
+    ohlcv_store::{OhlcvOrder, OhlcvPage, OhlcvQuery, OhlcvStore, OhlcvStoreError},
+    timeframe::Timeframe,
+};
+
+/// Messages and service of `lib/proto/ohlcv.proto`.
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetRequest {
+        #[prost(string, tag = "1")]
+        pub sym_id: String,
+        #[prost(string, tag = "2")]
+        pub timeframe: String,
+        #[prost(uint32, optional, tag = "3")]
+        pub limit: Option<u32>,
+        #[prost(uint32, optional, tag = "4")]
+        pub offset: Option<u32>,
+        #[prost(enumeration = "Order", optional, tag = "5")]
+        pub order: Option<i32>,
+        #[prost(message, optional, tag = "6")]
+        pub start_open_time: Option<prost_types::Timestamp>,
+        #[prost(message, optional, tag = "7")]
+        pub end_open_time: Option<prost_types::Timestamp>,
+        #[prost(message, optional, tag = "8")]
+        pub start_close_time: Option<prost_types::Timestamp>,
+        #[prost(message, optional, tag = "9")]
+        pub end_close_time: Option<prost_types::Timestamp>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub bars: Vec<OhlcvBar>,
+        #[prost(uint32, optional, tag = "2")]
+        pub total: Option<u32>,
+        #[prost(uint32, optional, tag = "3")]
+        pub remaining: Option<u32>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OhlcvBar {
+        #[prost(message, optional, tag = "1")]
+        pub open_time: Option<prost_types::Timestamp>,
+        #[prost(message, optional, tag = "2")]
+        pub close_time: Option<prost_types::Timestamp>,
+        #[prost(double, tag = "3")]
+        pub open: f64,
+        #[prost(double, tag = "4")]
+        pub high: f64,
+        #[prost(double, tag = "5")]
+        pub low: f64,
+        #[prost(double, tag = "6")]
+        pub close: f64,
+        #[prost(double, tag = "7")]
+        pub volume: f64,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Order {
+        Asc = 0,
+        Desc = 1,
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/ohlcv.OhlcvApi.rs"));
+}
+
+pub use proto::{ohlcv_api_client::OhlcvApiClient, ohlcv_api_server::OhlcvApiServer};
+
+#[derive(Debug, Error)]
+pub enum OhlcvGrpcError {
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+    #[error(transparent)]
+    Status(#[from] Status),
+    #[error("Invalid api key")]
+    InvalidApiKey,
+}
+
+#[inline]
+fn timestamp_to_proto(time: Option<&DateTime<Utc>>) -> Option<prost_types::Timestamp> {
+    return time.map(|x| prost_types::Timestamp {
+        seconds: x.timestamp(),
+        nanos: x.timestamp_subsec_nanos() as i32,
+    });
+}
+
+#[inline]
+fn timestamp_from_proto(time: Option<&prost_types::Timestamp>) -> Option<DateTime<Utc>> {
+    return time.and_then(|x| DateTime::from_timestamp(x.seconds, x.nanos.max(0) as u32));
+}
+
+impl From<&OhlcvBar> for proto::OhlcvBar {
+    #[inline]
+    fn from(bar: &OhlcvBar) -> Self {
+        return Self {
+            open_time: timestamp_to_proto(bar.open_time()),
+            close_time: timestamp_to_proto(bar.close_time()),
+            open: bar.open(),
+            high: bar.high(),
+            low: bar.low(),
+            close: bar.close(),
+            volume: bar.volume(),
+        };
+    }
+}
+
+impl From<&proto::OhlcvBar> for OhlcvBar {
+    #[inline]
+    fn from(bar: &proto::OhlcvBar) -> Self {
+        return OhlcvBar::new(
+            timestamp_from_proto(bar.open_time.as_ref()),
+            timestamp_from_proto(bar.close_time.as_ref()),
+            bar.open,
+            bar.high,
+            bar.low,
+            bar.close,
+            bar.volume,
+        );
+    }
+}
+
+impl proto::GetRequest {
+    pub fn new(sym_id: &str, timeframe: Timeframe, query: &OhlcvQuery) -> Self {
+        let order = match query.order {
+            OhlcvOrder::Asc => proto::Order::Asc,
+            OhlcvOrder::Desc => proto::Order::Desc,
+        };
+        return Self {
+            sym_id: sym_id.to_string(),
+            timeframe: timeframe.into(),
+            limit: query.limit.map(|x| x.min(u32::MAX as usize) as u32),
+            offset: query.offset.map(|x| x.min(u32::MAX as usize) as u32),
+            order: Some(order as i32),
+            start_open_time: timestamp_to_proto(query.start_open_time.as_ref()),
+            end_open_time: timestamp_to_proto(query.end_open_time.as_ref()),
+            start_close_time: timestamp_to_proto(query.start_close_time.as_ref()),
+            end_close_time: timestamp_to_proto(query.end_close_time.as_ref()),
+        };
+    }
+
+    pub fn query(&self) -> OhlcvQuery {
+        let order = match self.order.and_then(|x| proto::Order::try_from(x).ok()) {
+            Some(proto::Order::Desc) => OhlcvOrder::Desc,
+            _ => OhlcvOrder::Asc,
+        };
+        return OhlcvQuery {
+            limit: self.limit.map(|x| x as usize),
+            offset: self.offset.map(|x| x as usize),
+            order,
+            start_open_time: timestamp_from_proto(self.start_open_time.as_ref()),
+            end_open_time: timestamp_from_proto(self.end_open_time.as_ref()),
+            start_close_time: timestamp_from_proto(self.start_close_time.as_ref()),
+            end_close_time: timestamp_from_proto(self.end_close_time.as_ref()),
+        };
+    }
+}
+
+/// Rust counterpart of `OhlcvClient` in `lib/client.py`.
+#[derive(Debug, Clone)]
+pub struct OhlcvGrpcClient {
+    inner: OhlcvApiClient<Channel>,
+    api_key: Option<MetadataValue<tonic::metadata::Ascii>>,
+}
+
+impl OhlcvGrpcClient {
+    #[inline]
+    pub fn new(channel: Channel) -> Self {
+        return Self {
+            inner: OhlcvApiClient::new(channel),
+            api_key: None,
+        };
+    }
+
+    /// e.g. `http://127.0.0.1:3001`.
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self, OhlcvGrpcError> {
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        return Ok(Self::new(channel));
+    }
+
+    /// Sent as `x-api-key` with every request.
+    pub fn with_api_key(mut self, api_key: &str) -> Result<Self, OhlcvGrpcError> {
+        self.api_key = Some(api_key.parse().map_err(|_| OhlcvGrpcError::InvalidApiKey)?);
+        return Ok(self);
+    }
+
+    /// Single `Get` call, the server may return fewer bars than `query.limit`.
+    pub async fn get_page(
+        &mut self,
+        sym_id: &str,
+        timeframe: Timeframe,
+        query: &OhlcvQuery,
+    ) -> Result<OhlcvPage, OhlcvGrpcError> {
+        let mut request = Request::new(proto::GetRequest::new(sym_id, timeframe, query));
+        if let Some(api_key) = &self.api_key {
+            request.metadata_mut().insert("x-api-key", api_key.clone());
+        }
+        let response = self.inner.get(request).await?.into_inner();
+        let mut ohlcv = Ohlcv::with_capacity(response.bars.len());
+        ohlcv.set_timeframe(timeframe);
+        for bar in &response.bars {
+            ohlcv.push(bar.into());
+        }
+        return Ok(OhlcvPage {
+            ohlcv,
+            total: response.total.unwrap_or(0) as usize,
+            remaining: response.remaining.unwrap_or(0) as usize,
+        });
+    }
+
+    /// Requests pages until `query.limit` bars are fetched or the server has none remaining.
+    pub async fn get(
+        &mut self,
+        sym_id: &str,
+        timeframe: Timeframe,
+        query: &OhlcvQuery,
+    ) -> Result<Ohlcv, OhlcvGrpcError> {
+        let mut ohlcv = Ohlcv::new();
+        ohlcv.set_timeframe(timeframe);
+        let mut query = query.clone();
+        let mut offset = query.offset.unwrap_or(0);
+        loop {
+            query.offset = Some(offset);
+            let page = self.get_page(sym_id, timeframe, &query).await?;
+            let len = page.ohlcv.len();
+            ohlcv.push_many(page.ohlcv.bars());
+            offset += len;
+            if let Some(limit) = query.limit.as_mut() {
+                *limit -= len.min(*limit);
+                if *limit == 0 {
+                    break;
+                }
+            }
+            if len == 0 || page.remaining == 0 {
+                break;
+            }
+        }
+        return Ok(ohlcv);
+    }
+}
+
+/// Reference `OhlcvApi` server backed by an `OhlcvStore`. `total` is the number of stored bars and
+/// pages are capped to `page_size` bars.
+#[derive(Debug, Clone)]
+pub struct OhlcvStoreService {
+    store: OhlcvStore,
+    page_size: usize,
+}
+
+impl OhlcvStoreService {
+    pub const DEFAULT_PAGE_SIZE: usize = 10_000;
+
+    #[inline]
+    pub fn new(store: OhlcvStore) -> Self {
+        return Self {
+            store,
+            page_size: Self::DEFAULT_PAGE_SIZE,
+        };
+    }
+
+    #[inline]
+    pub fn page_size(&self) -> usize {
+        return self.page_size;
+    }
+
+    #[inline]
+    pub fn set_page_size(&mut self, page_size: usize) {
+        assert!(page_size > 0, "Page size must be positive");
+        self.page_size = page_size;
+    }
+
+    #[inline]
+    pub fn into_server(self) -> OhlcvApiServer<Self> {
+        return OhlcvApiServer::new(self);
+    }
+
+    /// Serves until the listener fails, bind to port 0 to get a free port.
+    pub async fn serve(self, listener: TcpListener) -> Result<(), OhlcvGrpcError> {
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Server::builder()
+            .add_service(self.into_server())
+            .serve_with_incoming(incoming)
+            .await?;
+        return Ok(());
+    }
+}
+
+#[tonic::async_trait]
+impl proto::ohlcv_api_server::OhlcvApi for OhlcvStoreService {
+    async fn get(
+        &self,
+        request: Request<proto::GetRequest>,
+    ) -> Result<Response<proto::GetResponse>, Status> {
+        let request = request.into_inner();
+        let timeframe = Timeframe::from(request.timeframe.clone());
+        if timeframe.unknown() {
+            return Err(Status::invalid_argument(format!(
+                "Invalid timeframe {:?}",
+                request.timeframe
+            )));
+        }
+        let mut query = request.query();
+        query.limit = Some(query.limit.unwrap_or(self.page_size).min(self.page_size));
+        let store = self.store.clone();
+        let sym_id = request.sym_id;
+        let page = tokio::task::spawn_blocking(move || store.get(&sym_id, timeframe, &query))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| match e {
+                OhlcvStoreError::Corrupted(..) => Status::data_loss(e.to_string()),
+                e => Status::internal(e.to_string()),
+            })?;
+        return Ok(Response::new(proto::GetResponse {
+            bars: page.ohlcv.bars().iter().map(Into::into).collect(),
+            total: Some(page.total as u32),
+            remaining: Some(page.remaining as u32),
+        }));
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+#[cfg(feature = "grpc")]
+mod test {
+    use crate::{
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        ohlcv_grpc::{OhlcvGrpcClient, OhlcvGrpcError, OhlcvStoreService},
+        ohlcv_store::{OhlcvOrder, OhlcvQuery, OhlcvStore},
+        test_utils::time,
+        timeframe::Timeframe,
+    };
+
+    #[tokio::test]
+    pub async fn client_pages_through_store() {
+        let root = std::env::temp_dir().join("qpace_ohlcv_grpc_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let store = OhlcvStore::open(&root).unwrap();
+        let tf = Timeframe::Minutes(1);
+        let bars = (0..25)
+            .map(|i| {
+                let mut bar = OhlcvBar::new(
+                    Some(time(i)),
+                    Some(time(i + 1)),
+                    1.0,
+                    2.0,
+                    0.5,
+                    i as f64,
+                    10.0,
+                );
+                if i == 3 {
+                    bar.set_close_time(None);
+                }
+                bar
+            })
+            .collect();
+        store
+            .append("BINANCE:BTCUSDT", tf, &Ohlcv::from_bars(bars))
+            .unwrap();
+
+        let mut service = OhlcvStoreService::new(store);
+        service.set_page_size(7);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(service.serve(listener));
+
+        let mut client = OhlcvGrpcClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap()
+            .with_api_key("secret")
+            .unwrap();
+
+        // capped to the page size
+        let page = client
+            .get_page("BINANCE:BTCUSDT", tf, &OhlcvQuery::default())
+            .await
+            .unwrap();
+        assert_eq!((page.ohlcv.len(), page.total, page.remaining), (7, 25, 18));
+        assert_eq!(page.ohlcv.timeframe(), tf);
+        assert_eq!(page.ohlcv.get(3).unwrap().close_time(), None);
+
+        let ohlcv = client
+            .get("BINANCE:BTCUSDT", tf, &OhlcvQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(ohlcv.len(), 25);
+        assert_eq!(ohlcv.at(-1).unwrap().open_time(), Some(&time(24)));
+        assert_eq!(ohlcv.get(10).unwrap().close(), 10.0);
+
+        let ohlcv = client
+            .get(
+                "BINANCE:BTCUSDT",
+                tf,
+                &OhlcvQuery {
+                    limit: Some(10),
+                    offset: Some(2),
+                    order: OhlcvOrder::Desc,
+                    end_open_time: Some(time(20)),
+                    ..OhlcvQuery::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(ohlcv.len(), 10);
+        assert_eq!(ohlcv.get(0).unwrap().open_time(), Some(&time(18)));
+        assert_eq!(ohlcv.at(-1).unwrap().open_time(), Some(&time(9)));
+
+        let ohlcv = client.get("ETH", tf, &OhlcvQuery::default()).await.unwrap();
+        assert_eq!(ohlcv.len(), 0);
+
+        let error = client
+            .get_page(
+                "BINANCE:BTCUSDT",
+                Timeframe::Unknown(),
+                &OhlcvQuery::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, OhlcvGrpcError::Status(status) if status.code() == tonic::Code::InvalidArgument)
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
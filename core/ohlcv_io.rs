@@ -0,0 +1,806 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use thiserror::Error;
+
+cfg_if::cfg_if! { if #[cfg(feature = "polars")] {
+    use polars::prelude::{DataFrame, DataType, PolarsError, Series, TimeUnit};
+}}
+
+use crate::{
+    ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvWriter},
+    timeframe::Timeframe,
+    utils::get_filename_extension,
+};
+
+#[derive(Debug, Error)]
+pub enum OhlcvIoError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Missing column: {0}")]
+    MissingColumn(String),
+    #[error("Line {line}: invalid {column} {value:?}")]
+    InvalidValue {
+        line: usize,
+        column: String,
+        value: String,
+    },
+    #[error("Line {line}: {message}")]
+    InvalidLine { line: usize, message: String },
+    #[error("Invalid column {column}: {message}")]
+    InvalidColumn { column: String, message: String },
+    #[error("Unsupported file extension {0:?}, expected csv, tsv, jsonl or ndjson")]
+    UnsupportedFormat(String),
+    #[cfg(feature = "polars")]
+    #[error(transparent)]
+    Polars(#[from] PolarsError),
+}
+
+/// How times are written and read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum OhlcvTimeFormat {
+    /// Picks the unit of each number by its magnitude and falls back to `Iso8601` for text. Written as milliseconds.
+    #[default]
+    Auto,
+    /// Unix seconds, fractions are allowed when reading.
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+    /// RFC 3339, e.g. `2024-01-01T00:00:00Z`. Times without an offset and plain dates are read as UTC.
+    Iso8601,
+}
+
+impl TryFrom<&str> for OhlcvTimeFormat {
+    type Error = String;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "auto" => Ok(OhlcvTimeFormat::Auto),
+            "s" => Ok(OhlcvTimeFormat::Seconds),
+            "ms" => Ok(OhlcvTimeFormat::Milliseconds),
+            "us" => Ok(OhlcvTimeFormat::Microseconds),
+            "ns" => Ok(OhlcvTimeFormat::Nanoseconds),
+            "iso8601" | "iso" => Ok(OhlcvTimeFormat::Iso8601),
+            _ => Err(format!(
+                "Unknown time format {:?}, expected auto, s, ms, us, ns or iso8601",
+                value
+            )),
+        }
+    }
+}
+
+fn parse_iso8601(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(time.and_utc());
+        }
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+}
+
+impl OhlcvTimeFormat {
+    /// Resolves `Auto` for a number, below `1e11` are seconds, `1e14` milliseconds, `1e17` microseconds and nanoseconds above.
+    #[inline]
+    pub fn unit_of(&self, value: f64) -> OhlcvTimeFormat {
+        if *self != OhlcvTimeFormat::Auto {
+            return *self;
+        }
+        return match value.abs() {
+            x if x < 1e11 => OhlcvTimeFormat::Seconds,
+            x if x < 1e14 => OhlcvTimeFormat::Milliseconds,
+            x if x < 1e17 => OhlcvTimeFormat::Microseconds,
+            _ => OhlcvTimeFormat::Nanoseconds,
+        };
+    }
+
+    pub fn format(&self, time: &DateTime<Utc>) -> String {
+        match self {
+            OhlcvTimeFormat::Seconds => time.timestamp().to_string(),
+            OhlcvTimeFormat::Auto | OhlcvTimeFormat::Milliseconds => {
+                time.timestamp_millis().to_string()
+            }
+            OhlcvTimeFormat::Microseconds => time.timestamp_micros().to_string(),
+            OhlcvTimeFormat::Nanoseconds => {
+                time.timestamp_nanos_opt().unwrap_or(i64::MIN).to_string()
+            }
+            OhlcvTimeFormat::Iso8601 => time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        }
+    }
+
+    /// `None` for `Iso8601` or out of range values.
+    pub fn from_i64(&self, value: i64) -> Option<DateTime<Utc>> {
+        match self.unit_of(value as f64) {
+            OhlcvTimeFormat::Seconds => DateTime::from_timestamp(value, 0),
+            OhlcvTimeFormat::Milliseconds => DateTime::from_timestamp_millis(value),
+            OhlcvTimeFormat::Microseconds => DateTime::from_timestamp_micros(value),
+            OhlcvTimeFormat::Nanoseconds => Some(DateTime::from_timestamp_nanos(value)),
+            _ => None,
+        }
+    }
+
+    /// Fractions are kept up to microseconds, `None` for NaN.
+    pub fn from_f64(&self, value: f64) -> Option<DateTime<Utc>> {
+        if !value.is_finite() {
+            return None;
+        }
+        let us = match self.unit_of(value) {
+            OhlcvTimeFormat::Seconds => value * 1e6,
+            OhlcvTimeFormat::Milliseconds => value * 1e3,
+            OhlcvTimeFormat::Microseconds => value,
+            OhlcvTimeFormat::Nanoseconds => value / 1e3,
+            _ => return None,
+        };
+        return DateTime::from_timestamp_micros(us.round() as i64);
+    }
+
+    pub fn parse(&self, value: &str) -> Option<DateTime<Utc>> {
+        if *self == OhlcvTimeFormat::Iso8601 {
+            return parse_iso8601(value);
+        }
+        if let Ok(value) = value.parse::<i64>() {
+            return self.from_i64(value);
+        }
+        if let Ok(value) = value.parse::<f64>() {
+            return self.from_f64(value);
+        }
+        if *self == OhlcvTimeFormat::Auto {
+            return parse_iso8601(value);
+        }
+        return None;
+    }
+}
+
+/// Column names of each bar field.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct OhlcvColumns {
+    pub open_time: Option<String>,
+    pub close_time: Option<String>,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: Option<String>,
+}
+
+impl Default for OhlcvColumns {
+    fn default() -> Self {
+        Self {
+            open_time: Some("open_time".to_string()),
+            close_time: Some("close_time".to_string()),
+            open: "open".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+            volume: Some("volume".to_string()),
+        }
+    }
+}
+
+impl OhlcvColumns {
+    /// Single `time` column used as both open and close time, like TradingView exports.
+    #[inline]
+    pub fn with_time(name: &str) -> Self {
+        Self {
+            open_time: Some(name.to_string()),
+            close_time: None,
+            ..Self::default()
+        }
+    }
+
+    /// Maps `field`, one of `open_time`, `close_time`, `open`, `high`, `low`, `close` or `volume`, to column `name`.
+    /// `None` unmaps the optional times and volume.
+    pub fn set(&mut self, field: &str, name: Option<String>) -> Result<(), String> {
+        let required = |name: Option<String>| name.ok_or(format!("Column {} is required", field));
+        match field {
+            "open_time" => self.open_time = name,
+            "close_time" => self.close_time = name,
+            "open" => self.open = required(name)?,
+            "high" => self.high = required(name)?,
+            "low" => self.low = required(name)?,
+            "close" => self.close = required(name)?,
+            "volume" => self.volume = name,
+            _ => {
+                return Err(format!(
+                    "Unknown field {:?}, expected open_time, close_time, open, high, low, close or volume",
+                    field
+                ))
+            }
+        }
+        return Ok(());
+    }
+
+    fn names(&self) -> Vec<&str> {
+        return [
+            self.open_time.as_deref(),
+            self.close_time.as_deref(),
+            Some(self.open.as_str()),
+            Some(self.high.as_str()),
+            Some(self.low.as_str()),
+            Some(self.close.as_str()),
+            self.volume.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct OhlcvIoConfig {
+    pub columns: OhlcvColumns,
+    pub time_format: OhlcvTimeFormat,
+    /// CSV only.
+    pub delimiter: u8,
+    /// Set on read bars. Without a close time column, close times are `open_time + timeframe`,
+    /// or the open time for timeframes without a duration.
+    pub timeframe: Timeframe,
+}
+
+impl Default for OhlcvIoConfig {
+    fn default() -> Self {
+        Self {
+            columns: OhlcvColumns::default(),
+            time_format: OhlcvTimeFormat::Auto,
+            delimiter: b',',
+            timeframe: Timeframe::default(),
+        }
+    }
+}
+
+impl OhlcvIoConfig {
+    #[inline]
+    pub fn new(time_format: OhlcvTimeFormat) -> Self {
+        Self {
+            time_format,
+            ..Self::default()
+        }
+    }
+}
+
+/// Close times of data without a close time column, see `OhlcvIoConfig::timeframe`.
+#[cfg(any(feature = "polars", feature = "arrow"))]
+pub(crate) fn close_times_of(
+    open_time: &[Option<DateTime<Utc>>],
+    timeframe: Timeframe,
+) -> Vec<Option<DateTime<Utc>>> {
+    let duration: Option<Duration> = timeframe.try_into().ok();
+    return open_time
+        .iter()
+        .map(|x| duration.map_or(*x, |duration| x.map(|x| x + duration)))
+        .collect();
+}
+
+/// Column positions of a header, `None` for unmapped or missing optional columns.
+pub(crate) struct Layout {
+    pub open_time: Option<usize>,
+    pub close_time: Option<usize>,
+    pub open: usize,
+    pub high: usize,
+    pub low: usize,
+    pub close: usize,
+    pub volume: Option<usize>,
+    bar_duration: Option<Duration>,
+}
+
+impl Layout {
+    /// Prices are required, missing times and volume are left empty and `time` stands in for `open_time`.
+    pub fn new(header: &[String], config: &OhlcvIoConfig) -> Result<Self, OhlcvIoError> {
+        let columns = &config.columns;
+        let find = |name: &str| header.iter().position(|x| x == name);
+        let required =
+            |name: &str| find(name).ok_or_else(|| OhlcvIoError::MissingColumn(name.to_string()));
+        let optional = |name: &Option<String>| name.as_deref().and_then(find);
+        return Ok(Self {
+            open_time: optional(&columns.open_time).or_else(|| find("time")),
+            close_time: optional(&columns.close_time),
+            open: required(&columns.open)?,
+            high: required(&columns.high)?,
+            low: required(&columns.low)?,
+            close: required(&columns.close)?,
+            volume: optional(&columns.volume),
+            bar_duration: config.timeframe.try_into().ok(),
+        });
+    }
+
+    /// Close time of a bar without a close time column.
+    #[inline]
+    pub fn close_time_of(&self, open_time: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        return match self.bar_duration {
+            Some(duration) => open_time.map(|x| x + duration),
+            None => open_time,
+        };
+    }
+
+    /// `values[i]` is `None` for empty or null fields.
+    fn bar(
+        &self,
+        values: &[Option<String>],
+        header: &[String],
+        line: usize,
+        time_format: OhlcvTimeFormat,
+    ) -> Result<OhlcvBar, OhlcvIoError> {
+        let invalid = |i: usize, value: &str| OhlcvIoError::InvalidValue {
+            line,
+            column: header[i].clone(),
+            value: value.to_string(),
+        };
+        let time = |i: Option<usize>| match i.and_then(|i| values[i].as_deref().map(|x| (i, x))) {
+            Some((i, value)) => time_format
+                .parse(value)
+                .map(Some)
+                .ok_or_else(|| invalid(i, value)),
+            None => Ok(None),
+        };
+        let float = |i: Option<usize>| match i.and_then(|i| values[i].as_deref().map(|x| (i, x))) {
+            Some((i, value)) => value.parse::<f64>().map_err(|_| invalid(i, value)),
+            None => Ok(f64::NAN),
+        };
+        let open_time = time(self.open_time)?;
+        let close_time = match self.close_time {
+            Some(_) => time(self.close_time)?,
+            None => self.close_time_of(open_time),
+        };
+        return Ok(OhlcvBar::new(
+            open_time,
+            close_time,
+            float(Some(self.open))?,
+            float(Some(self.high))?,
+            float(Some(self.low))?,
+            float(Some(self.close))?,
+            float(self.volume)?,
+        ));
+    }
+}
+
+/// Splits a CSV line, double quotes escape delimiters and `""` is a literal quote.
+fn split_csv(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    return fields;
+}
+
+fn format_f64(value: f64) -> String {
+    if value.is_nan() {
+        return String::new();
+    }
+    return value.to_string();
+}
+
+pub fn read_csv(reader: impl BufRead, config: &OhlcvIoConfig) -> Result<Ohlcv, OhlcvIoError> {
+    let delimiter = config.delimiter as char;
+    let mut lines = reader.lines();
+    let header: Vec<String> = match lines.next() {
+        Some(line) => split_csv(line?.trim_end_matches('\r'), delimiter)
+            .into_iter()
+            .map(|x| x.trim().to_string())
+            .collect(),
+        None => return Ok(Ohlcv::new()),
+    };
+    let layout = Layout::new(&header, config)?;
+    let mut ohlcv = Ohlcv::new();
+    ohlcv.set_timeframe(config.timeframe);
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut values: Vec<Option<String>> = split_csv(line, delimiter)
+            .into_iter()
+            .map(|x| Some(x.trim().to_string()).filter(|x| !x.is_empty()))
+            .collect();
+        if values.len() > header.len() {
+            return Err(OhlcvIoError::InvalidLine {
+                line: i + 2,
+                message: format!("expected {} fields, got {}", header.len(), values.len()),
+            });
+        }
+        values.resize(header.len(), None);
+        ohlcv.push(layout.bar(&values, &header, i + 2, config.time_format)?);
+    }
+    return Ok(ohlcv);
+}
+
+/// NaN prices and missing times are written as empty fields.
+pub fn write_csv(
+    mut writer: impl Write,
+    ohlcv: &dyn OhlcvReader,
+    config: &OhlcvIoConfig,
+) -> Result<(), OhlcvIoError> {
+    let delimiter = (config.delimiter as char).to_string();
+    let columns = &config.columns;
+    writeln!(writer, "{}", columns.names().join(&delimiter))?;
+    for bar in ohlcv.bars() {
+        let time =
+            |x: Option<&DateTime<Utc>>| x.map(|x| config.time_format.format(x)).unwrap_or_default();
+        let mut fields = vec![];
+        if columns.open_time.is_some() {
+            fields.push(time(bar.open_time()));
+        }
+        if columns.close_time.is_some() {
+            fields.push(time(bar.close_time()));
+        }
+        fields.push(format_f64(bar.open()));
+        fields.push(format_f64(bar.high()));
+        fields.push(format_f64(bar.low()));
+        fields.push(format_f64(bar.close()));
+        if columns.volume.is_some() {
+            fields.push(format_f64(bar.volume()));
+        }
+        writeln!(writer, "{}", fields.join(&delimiter))?;
+    }
+    writer.flush()?;
+    return Ok(());
+}
+
+/// Parses a flat JSON object, values are kept as text and `null` as `None`.
+fn parse_json_object(line: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut chars = line.trim().chars().peekable();
+    let mut entries = vec![];
+    let skip_whitespace = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    };
+    let parse_string =
+        |chars: &mut std::iter::Peekable<std::str::Chars>| -> Result<String, String> {
+            let mut value = String::new();
+            loop {
+                match chars.next().ok_or("unterminated string")? {
+                    '"' => return Ok(value),
+                    '\\' => match chars.next().ok_or("unterminated string")? {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        'r' => value.push('\r'),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'u' => {
+                            let code: String = chars.by_ref().take(4).collect();
+                            let code =
+                                u32::from_str_radix(&code, 16).map_err(|_| "invalid escape")?;
+                            value.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        }
+                        c => value.push(c),
+                    },
+                    c => value.push(c),
+                }
+            }
+        };
+    if chars.next() != Some('{') {
+        return Err("expected an object".to_string());
+    }
+    skip_whitespace(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+    }
+    while chars.peek().is_some() {
+        skip_whitespace(&mut chars);
+        if chars.next() != Some('"') {
+            return Err("expected a key".to_string());
+        }
+        let key = parse_string(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("expected ':' after {:?}", key));
+        }
+        skip_whitespace(&mut chars);
+        let value = match chars.peek() {
+            Some('"') => {
+                chars.next();
+                Some(parse_string(&mut chars)?)
+            }
+            Some('{') | Some('[') => return Err(format!("nested value of {:?}", key)),
+            _ => {
+                let mut raw = String::new();
+                while let Some(c) = chars.next_if(|c| *c != ',' && *c != '}') {
+                    raw.push(c);
+                }
+                let raw = raw.trim().to_string();
+                match raw.as_str() {
+                    "null" => None,
+                    "" => return Err(format!("missing value of {:?}", key)),
+                    _ => Some(raw),
+                }
+            }
+        };
+        entries.push((key, value));
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => break,
+            _ => return Err("expected ',' or '}'".to_string()),
+        }
+    }
+    return Ok(entries);
+}
+
+/// One JSON object per line, the columns are taken from the keys of the first object.
+pub fn read_jsonl(reader: impl BufRead, config: &OhlcvIoConfig) -> Result<Ohlcv, OhlcvIoError> {
+    let mut header: Vec<String> = vec![];
+    let mut layout: Option<Layout> = None;
+    let mut ohlcv = Ohlcv::new();
+    ohlcv.set_timeframe(config.timeframe);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entries = parse_json_object(&line).map_err(|message| OhlcvIoError::InvalidLine {
+            line: i + 1,
+            message,
+        })?;
+        if layout.is_none() {
+            header = entries.iter().map(|(key, _)| key.clone()).collect();
+            layout = Some(Layout::new(&header, config)?);
+        }
+        let mut values: Vec<Option<String>> = vec![None; header.len()];
+        for (key, value) in entries {
+            if let Some(j) = header.iter().position(|x| *x == key) {
+                values[j] = value;
+            }
+        }
+        let bar = layout
+            .as_ref()
+            .unwrap()
+            .bar(&values, &header, i + 1, config.time_format)?;
+        ohlcv.push(bar);
+    }
+    return Ok(ohlcv);
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+/// NaN prices and missing times are written as `null`, ISO 8601 times as strings.
+pub fn write_jsonl(
+    mut writer: impl Write,
+    ohlcv: &dyn OhlcvReader,
+    config: &OhlcvIoConfig,
+) -> Result<(), OhlcvIoError> {
+    let columns = &config.columns;
+    let time = |x: Option<&DateTime<Utc>>| match (x, config.time_format) {
+        (None, _) => "null".to_string(),
+        (Some(x), OhlcvTimeFormat::Iso8601) => json_string(&OhlcvTimeFormat::Iso8601.format(x)),
+        (Some(x), format) => format.format(x),
+    };
+    let float = |x: f64| {
+        if x.is_finite() {
+            x.to_string()
+        } else {
+            "null".to_string()
+        }
+    };
+    for bar in ohlcv.bars() {
+        let mut fields = vec![];
+        if let Some(name) = &columns.open_time {
+            fields.push((name, time(bar.open_time())));
+        }
+        if let Some(name) = &columns.close_time {
+            fields.push((name, time(bar.close_time())));
+        }
+        fields.push((&columns.open, float(bar.open())));
+        fields.push((&columns.high, float(bar.high())));
+        fields.push((&columns.low, float(bar.low())));
+        fields.push((&columns.close, float(bar.close())));
+        if let Some(name) = &columns.volume {
+            fields.push((name, float(bar.volume())));
+        }
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|(name, value)| format!("{}:{}", json_string(name), value))
+            .collect();
+        writeln!(writer, "{{{}}}", fields.join(","))?;
+    }
+    writer.flush()?;
+    return Ok(());
+}
+
+/// Picks the format by extension, `.tsv` reads tab separated values regardless of `config.delimiter`.
+pub fn read_ohlcv(path: &Path, config: &OhlcvIoConfig) -> Result<Ohlcv, OhlcvIoError> {
+    let reader = BufReader::new(File::open(path)?);
+    match get_filename_extension(path) {
+        Some("csv") => read_csv(reader, config),
+        Some("tsv") => read_csv(
+            reader,
+            &OhlcvIoConfig {
+                delimiter: b'\t',
+                ..config.clone()
+            },
+        ),
+        Some("jsonl") | Some("ndjson") => read_jsonl(reader, config),
+        ext => Err(OhlcvIoError::UnsupportedFormat(
+            ext.unwrap_or_default().to_string(),
+        )),
+    }
+}
+
+/// See `read_ohlcv`.
+pub fn write_ohlcv(
+    path: &Path,
+    ohlcv: &dyn OhlcvReader,
+    config: &OhlcvIoConfig,
+) -> Result<(), OhlcvIoError> {
+    let ext = get_filename_extension(path);
+    let config = match ext {
+        Some("csv") | Some("jsonl") | Some("ndjson") => config.clone(),
+        Some("tsv") => OhlcvIoConfig {
+            delimiter: b'\t',
+            ..config.clone()
+        },
+        ext => {
+            return Err(OhlcvIoError::UnsupportedFormat(
+                ext.unwrap_or_default().to_string(),
+            ))
+        }
+    };
+    let writer = BufWriter::new(File::create(path)?);
+    match ext {
+        Some("jsonl") | Some("ndjson") => write_jsonl(writer, ohlcv, &config),
+        _ => write_csv(writer, ohlcv, &config),
+    }
+}
+
+#[cfg(feature = "polars")]
+fn polars_column_error(series: &Series) -> impl Fn(PolarsError) -> OhlcvIoError + '_ {
+    return |e| OhlcvIoError::InvalidColumn {
+        column: series.name().to_string(),
+        message: e.to_string(),
+    };
+}
+
+#[cfg(feature = "polars")]
+fn polars_f64(series: &Series) -> Result<Vec<f64>, OhlcvIoError> {
+    let err = polars_column_error(series);
+    let values = series.strict_cast(&DataType::Float64).map_err(&err)?;
+    return Ok(values
+        .f64()
+        .map_err(&err)?
+        .into_iter()
+        .map(|x| x.unwrap_or(f64::NAN))
+        .collect());
+}
+
+/// Datetime and date columns keep their own unit, numbers and text are read with `time_format`.
+#[cfg(feature = "polars")]
+pub(crate) fn polars_time(
+    series: &Series,
+    time_format: OhlcvTimeFormat,
+) -> Result<Vec<Option<DateTime<Utc>>>, OhlcvIoError> {
+    let err = polars_column_error(series);
+    let invalid = |row: usize, value: String| OhlcvIoError::InvalidColumn {
+        column: series.name().to_string(),
+        message: format!("invalid time {:?} at row {}", value, row),
+    };
+    let from_i64 = |format: OhlcvTimeFormat, multiplier: i64| {
+        let values = series.cast(&DataType::Int64).map_err(&err)?;
+        let values = values.i64().map_err(&err)?;
+        return values
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| match x {
+                Some(x) => format
+                    .from_i64(x * multiplier)
+                    .map(Some)
+                    .ok_or_else(|| invalid(i, x.to_string())),
+                None => Ok(None),
+            })
+            .collect();
+    };
+    return match series.dtype() {
+        DataType::Datetime(TimeUnit::Milliseconds, _) => from_i64(OhlcvTimeFormat::Milliseconds, 1),
+        DataType::Datetime(TimeUnit::Microseconds, _) => from_i64(OhlcvTimeFormat::Microseconds, 1),
+        DataType::Datetime(TimeUnit::Nanoseconds, _) => from_i64(OhlcvTimeFormat::Nanoseconds, 1),
+        DataType::Date => from_i64(OhlcvTimeFormat::Seconds, 86_400),
+        DataType::Utf8 => series
+            .utf8()
+            .map_err(&err)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| match x {
+                Some(x) => time_format
+                    .parse(x)
+                    .map(Some)
+                    .ok_or_else(|| invalid(i, x.to_string())),
+                None => Ok(None),
+            })
+            .collect(),
+        dtype if dtype.is_float() => {
+            let values = series.cast(&DataType::Float64).map_err(&err)?;
+            let values = values.f64().map_err(&err)?;
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, x)| match x.filter(|x| !x.is_nan()) {
+                    Some(x) => time_format
+                        .from_f64(x)
+                        .map(Some)
+                        .ok_or_else(|| invalid(i, x.to_string())),
+                    None => Ok(None),
+                })
+                .collect()
+        }
+        dtype if dtype.is_numeric() => from_i64(time_format, 1),
+        dtype => Err(OhlcvIoError::InvalidColumn {
+            column: series.name().to_string(),
+            message: format!("expected a time column, got {}", dtype),
+        }),
+    };
+}
+
+/// Columns are picked by `config.columns`, see `OhlcvIoConfig`.
+#[cfg(feature = "polars")]
+pub fn ohlcv_from_polars(df: &DataFrame, config: &OhlcvIoConfig) -> Result<Ohlcv, OhlcvIoError> {
+    let header: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let layout = Layout::new(&header, config)?;
+    let columns = df.get_columns();
+    let open_time = match layout.open_time {
+        Some(i) => polars_time(&columns[i], config.time_format)?,
+        None => vec![None; df.height()],
+    };
+    let close_time = match layout.close_time {
+        Some(i) => polars_time(&columns[i], config.time_format)?,
+        None => close_times_of(&open_time, config.timeframe),
+    };
+    let volume = match layout.volume {
+        Some(i) => polars_f64(&columns[i])?,
+        None => vec![f64::NAN; df.height()],
+    };
+    let mut ohlcv = Ohlcv::from_columns(
+        open_time,
+        close_time,
+        polars_f64(&columns[layout.open])?,
+        polars_f64(&columns[layout.high])?,
+        polars_f64(&columns[layout.low])?,
+        polars_f64(&columns[layout.close])?,
+        volume,
+    );
+    ohlcv.set_timeframe(config.timeframe);
+    return Ok(ohlcv);
+}
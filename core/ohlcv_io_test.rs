@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Duration};
+
+    use crate::{
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        ohlcv_io::{
+            read_csv, read_jsonl, read_ohlcv, write_csv, write_jsonl, write_ohlcv, OhlcvColumns,
+            OhlcvIoConfig, OhlcvIoError, OhlcvTimeFormat,
+        },
+        test_utils::time,
+    };
+
+    fn ohlcv() -> Ohlcv {
+        let mut bars: Vec<OhlcvBar> = (0..5)
+            .map(|i| {
+                let price = 100.0 + i as f64;
+                OhlcvBar::new(
+                    Some(time(i)),
+                    Some(time(i + 1)),
+                    price,
+                    price + 1.25,
+                    price - 1.0,
+                    price + 0.5,
+                    i as f64,
+                )
+            })
+            .collect();
+        bars[2].set_close_time(None);
+        bars[3].set_volume(f64::NAN);
+        return Ohlcv::from_bars(bars);
+    }
+
+    fn assert_same(a: &Ohlcv, b: &Ohlcv) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.bars().iter().zip(b.bars().iter()) {
+            assert_eq!(a.open_time(), b.open_time());
+            assert_eq!(a.close_time(), b.close_time());
+            assert_eq!(a.close(), b.close());
+            assert_eq!(a.volume().is_nan(), b.volume().is_nan());
+        }
+    }
+
+    #[test]
+    pub fn roundtrip() {
+        let ohlcv = ohlcv();
+        for time_format in [
+            OhlcvTimeFormat::Seconds,
+            OhlcvTimeFormat::Milliseconds,
+            OhlcvTimeFormat::Iso8601,
+        ] {
+            let mut config = OhlcvIoConfig::new(time_format);
+            config.delimiter = b';';
+            let mut csv = vec![];
+            write_csv(&mut csv, &ohlcv, &config).unwrap();
+            assert_same(&read_csv(csv.as_slice(), &config).unwrap(), &ohlcv);
+
+            let mut jsonl = vec![];
+            write_jsonl(&mut jsonl, &ohlcv, &config).unwrap();
+            assert_same(&read_jsonl(jsonl.as_slice(), &config).unwrap(), &ohlcv);
+        }
+
+        let path = std::env::temp_dir().join("qpace_ohlcv_io_test.tsv");
+        write_ohlcv(&path, &ohlcv, &OhlcvIoConfig::default()).unwrap();
+        assert_same(
+            &read_ohlcv(&path, &OhlcvIoConfig::default()).unwrap(),
+            &ohlcv,
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    pub fn column_mapping() {
+        let csv = "Date,O,H,L,C,extra\n\
+                   2024-01-01T00:01:00Z,1,2,0.5,\"1,5\",x\n\
+                   2024-01-01 00:02:00,1,2,0.5,,y\n";
+        let mut config = OhlcvIoConfig::new(OhlcvTimeFormat::Iso8601);
+        config.columns = OhlcvColumns {
+            open: "O".to_string(),
+            high: "H".to_string(),
+            low: "L".to_string(),
+            close: "C".to_string(),
+            ..OhlcvColumns::with_time("Date")
+        };
+        let error = read_csv(csv.as_bytes(), &config).unwrap_err();
+        assert!(matches!(error, OhlcvIoError::InvalidValue { line: 2, .. }));
+
+        config.delimiter = b';';
+        let csv = csv.replace(',', ";").replace("\"1;5\"", "1.5");
+        let ohlcv = read_csv(csv.as_bytes(), &config).unwrap();
+        let bar = ohlcv.get(1).unwrap();
+        assert_eq!(bar.open_time(), Some(&time(2)));
+        assert_eq!(bar.close_time(), Some(&time(2)));
+        assert!(bar.close().is_nan());
+        assert!(bar.volume().is_nan());
+        assert_eq!(ohlcv.get(0).unwrap().close(), 1.5);
+
+        let jsonl = "{\"time\": 1704067200000, \"open\": 1, \"high\": 2, \"low\": 0, \"close\": 1, \"volume\": null}\n\
+                     {\"close\": 3, \"low\": 0, \"high\": 4, \"open\": 2, \"time\": 1704067260000}\n";
+        let ohlcv = read_jsonl(
+            jsonl.as_bytes(),
+            &OhlcvIoConfig::new(OhlcvTimeFormat::Milliseconds),
+        )
+        .unwrap();
+        assert_eq!(ohlcv.get(1).unwrap().open_time(), Some(&time(1)));
+        assert_eq!(ohlcv.get(1).unwrap().close(), 3.0);
+        assert!(matches!(
+            read_jsonl("{\"open\": 1}".as_bytes(), &OhlcvIoConfig::default()),
+            Err(OhlcvIoError::MissingColumn(_))
+        ));
+        assert!(matches!(
+            read_jsonl("{\"open\": [1]}".as_bytes(), &OhlcvIoConfig::default()),
+            Err(OhlcvIoError::InvalidLine { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    pub fn time_format_auto() {
+        let auto = OhlcvTimeFormat::Auto;
+        let t = time(1);
+        assert_eq!(auto.parse(&t.timestamp().to_string()), Some(t));
+        assert_eq!(auto.parse(&t.timestamp_millis().to_string()), Some(t));
+        assert_eq!(auto.parse(&t.timestamp_micros().to_string()), Some(t));
+        assert_eq!(
+            auto.parse(&t.timestamp_nanos_opt().unwrap().to_string()),
+            Some(t)
+        );
+        assert_eq!(auto.parse("2024-01-01T00:01:00+00:00"), Some(t));
+        assert_eq!(
+            auto.parse("1704067260.5"),
+            Some(t + Duration::milliseconds(500))
+        );
+        assert_eq!(auto.parse("yesterday"), None);
+        assert_eq!(
+            OhlcvTimeFormat::Milliseconds.parse("1704067260"),
+            DateTime::from_timestamp_millis(1704067260)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "polars")]
+    pub fn polars_schema() {
+        use polars::prelude::{DataFrame, DataType, NamedFrom, Series, TimeUnit};
+
+        use crate::{ohlcv::OhlcvWriter, ohlcv_io::ohlcv_from_polars, timeframe::Timeframe};
+
+        let ms: Vec<i64> = (0..3).map(|i| time(i).timestamp_millis()).collect();
+        let df = DataFrame::new(vec![
+            Series::new("Date", ms.clone())
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+                .unwrap(),
+            Series::new("O", [1.0, 2.0, 3.0]),
+            Series::new("H", [2, 3, 4]),
+            Series::new("L", [0.5, 1.5, 2.5]),
+            Series::new("C", [Some(1.5), None, Some(3.5)]),
+        ])
+        .unwrap();
+        let mut config = OhlcvIoConfig::default();
+        config.timeframe = Timeframe::Minutes(1);
+        config.columns = OhlcvColumns {
+            open: "O".to_string(),
+            high: "H".to_string(),
+            low: "L".to_string(),
+            close: "C".to_string(),
+            ..OhlcvColumns::with_time("Date")
+        };
+        let ohlcv = ohlcv_from_polars(&df, &config).unwrap();
+        assert_eq!(ohlcv.timeframe(), Timeframe::Minutes(1));
+        let bar = ohlcv.get(1).unwrap();
+        assert_eq!(bar.open_time(), Some(&time(1)));
+        assert_eq!(bar.close_time(), Some(&time(2)));
+        assert_eq!(bar.high(), 3.0);
+        assert!(bar.close().is_nan());
+        assert!(bar.volume().is_nan());
+
+        // integer times in any unit
+        let df = DataFrame::new(vec![
+            Series::new("time", ms),
+            Series::new("open", [1.0, 2.0, 3.0]),
+            Series::new("high", [1.0, 2.0, 3.0]),
+            Series::new("low", [1.0, 2.0, 3.0]),
+            Series::new("close", ["1", "2", "x"]),
+        ])
+        .unwrap();
+        let error = ohlcv_from_polars(&df, &OhlcvIoConfig::default()).unwrap_err();
+        assert!(matches!(error, OhlcvIoError::InvalidColumn { column, .. } if column == "close"));
+        let df = df.drop("close").unwrap();
+        assert!(matches!(
+            ohlcv_from_polars(&df, &OhlcvIoConfig::default()),
+            Err(OhlcvIoError::MissingColumn(column)) if column == "close"
+        ));
+        let mut read = Ohlcv::new();
+        assert!(matches!(
+            read.read_polars(&df),
+            Err(OhlcvIoError::MissingColumn(_))
+        ));
+        assert_eq!(read.len(), 0);
+        let df = DataFrame::new(vec![
+            Series::new("time", [time(2).timestamp()]),
+            Series::new("open", [1.0]),
+            Series::new("high", [1.0]),
+            Series::new("low", [1.0]),
+            Series::new("close", [1.0]),
+        ])
+        .unwrap();
+        let ohlcv = ohlcv_from_polars(&df, &OhlcvIoConfig::default()).unwrap();
+        assert_eq!(ohlcv.get(0).unwrap().open_time(), Some(&time(2)));
+        assert_eq!(ohlcv.get(0).unwrap().close_time(), Some(&time(2)));
+    }
+}
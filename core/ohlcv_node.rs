@@ -2,7 +2,12 @@ use crate::ohlcv::{
     zip_ohlcv_bars, ArcOhlcv, Ohlcv, OhlcvBar, OhlcvReader, OhlcvReaderOps, OhlcvWriter,
     OhlcvWriterOps,
 };
+use crate::ohlcv_arrow::{read_ohlcv_ipc, write_ohlcv_ipc};
+use crate::ohlcv_io::{OhlcvIoConfig, OhlcvTimeFormat};
+use crate::timeframe::Timeframe;
 use crate::timeframe_node::NodeTimeframe;
+use crate::quality::{OhlcvQualityConfig, OhlcvRepairConfig};
+use chrono_tz::Tz;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use napi::bindgen_prelude::*;
 use napi::{Error, Result, Status};
@@ -197,6 +202,81 @@ impl NodeOhlcvBar {
     }
 }
 
+/// Unix ms, `i64::MIN` for a missing time.
+#[inline]
+fn times_to_ms(times: &[Option<DateTime<Utc>>]) -> BigInt64Array {
+    let ms: Vec<i64> = times
+        .iter()
+        .map(|x| x.map_or(i64::MIN, |x| x.timestamp_millis()))
+        .collect();
+    BigInt64Array::new(ms)
+}
+
+#[inline]
+fn times_from_ms(ms: Option<&BigInt64Array>, len: usize) -> Result<Vec<Option<DateTime<Utc>>>> {
+    match ms {
+        Some(ms) if ms.len() != len => Err(Error::new(
+            Status::InvalidArg,
+            format!("Expected {} times, got {}", len, ms.len()),
+        )),
+        Some(ms) => Ok(ms
+            .iter()
+            .map(|x| match *x {
+                i64::MIN => None,
+                x => DateTime::from_timestamp_millis(x),
+            })
+            .collect()),
+        None => Ok(vec![None; len]),
+    }
+}
+
+/// Column names and time unit of Arrow data, unset fields keep the defaults of `OhlcvIoConfig`.
+#[napi(object, js_name = "OhlcvSchema")]
+#[derive(Clone, Debug, Default)]
+pub struct NodeOhlcvSchema {
+    pub open_time: Option<String>,
+    pub close_time: Option<String>,
+    pub open: Option<String>,
+    pub high: Option<String>,
+    pub low: Option<String>,
+    pub close: Option<String>,
+    pub volume: Option<String>,
+    /// `auto`, `s`, `ms`, `us`, `ns` or `iso8601`.
+    pub time_unit: Option<String>,
+    /// e.g. `1m`, used to derive missing close times.
+    pub timeframe: Option<String>,
+}
+
+impl TryInto<OhlcvIoConfig> for NodeOhlcvSchema {
+    type Error = Error;
+
+    fn try_into(self) -> Result<OhlcvIoConfig> {
+        let mut config = OhlcvIoConfig::default();
+        if let Some(time_unit) = self.time_unit {
+            config.time_format = OhlcvTimeFormat::try_from(time_unit.as_str())
+                .map_err(|e| Error::new(Status::InvalidArg, e))?;
+        }
+        if let Some(timeframe) = self.timeframe {
+            config.timeframe = Timeframe::from(timeframe);
+        }
+        let fields = [
+            ("open_time", self.open_time),
+            ("close_time", self.close_time),
+            ("open", self.open),
+            ("high", self.high),
+            ("low", self.low),
+            ("close", self.close),
+            ("volume", self.volume),
+        ];
+        for (field, name) in fields {
+            if name.is_some() {
+                config.columns.set(field, name).unwrap();
+            }
+        }
+        Ok(config)
+    }
+}
+
 #[napi]
 #[derive(Clone, Debug)]
 pub struct NodeOhlcv {
@@ -240,6 +320,63 @@ impl NodeOhlcv {
         ArcOhlcv::from_bars(bars).into()
     }
 
+    /// Columns are copied once, missing times are `-(2n ** 63n)`.
+    #[napi(js_name = fromArrays)]
+    pub fn node_from_arrays(
+        open: Float64Array,
+        high: Float64Array,
+        low: Float64Array,
+        close: Float64Array,
+        volume: Option<Float64Array>,
+        open_time: Option<BigInt64Array>,
+        close_time: Option<BigInt64Array>,
+    ) -> Result<Self> {
+        let len = close.len();
+        for column in [&open, &high, &low] {
+            if column.len() != len {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("Expected {} values, got {}", len, column.len()),
+                ));
+            }
+        }
+        let volume = match volume {
+            Some(volume) if volume.len() == len => volume.to_vec(),
+            Some(volume) => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("Expected {} volumes, got {}", len, volume.len()),
+                ))
+            }
+            None => vec![f64::NAN; len],
+        };
+        let ohlcv = Ohlcv::from_columns(
+            times_from_ms(open_time.as_ref(), len)?,
+            times_from_ms(close_time.as_ref(), len)?,
+            open.to_vec(),
+            high.to_vec(),
+            low.to_vec(),
+            close.to_vec(),
+            volume,
+        );
+        Ok(ohlcv.into())
+    }
+
+    #[napi(js_name = fromArrowIPC)]
+    pub fn node_from_arrow_ipc(data: Buffer, schema: Option<NodeOhlcvSchema>) -> Result<Self> {
+        let config: OhlcvIoConfig = schema.unwrap_or_default().try_into()?;
+        let ohlcv =
+            read_ohlcv_ipc(&data, &config).map_err(|e| Error::from_reason(e.to_string()))?;
+        Ok(ohlcv.into())
+    }
+
+    #[napi(js_name = toArrowIPC)]
+    pub fn node_to_arrow_ipc(&self) -> Result<Buffer> {
+        let bytes =
+            write_ohlcv_ipc(&self.inner.read()).map_err(|e| Error::from_reason(e.to_string()))?;
+        Ok(bytes.into())
+    }
+
     #[napi(getter = timeframe)]
     #[inline]
     pub fn node_timeframe(&self) -> NodeTimeframe {
@@ -251,6 +388,21 @@ impl NodeOhlcv {
         self.inner.set_timeframe(timeframe.into());
     }
 
+    #[napi(getter = timezone)]
+    #[inline]
+    pub fn node_timezone(&self) -> String {
+        self.inner.timezone().name().to_string()
+    }
+
+    #[napi(setter = timezone)]
+    pub fn node_set_timezone(&mut self, timezone: String) -> Result<()> {
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+        self.inner.set_timezone(timezone);
+        Ok(())
+    }
+
     #[napi(getter = openTime)]
     #[inline]
     pub fn node_open_time(&self) -> Vec<Option<DateTime<Utc>>> {
@@ -293,6 +445,48 @@ impl NodeOhlcv {
         self.inner.volume()
     }
 
+    #[napi(getter = openTimeArray)]
+    #[inline]
+    pub fn node_open_time_array(&self) -> BigInt64Array {
+        times_to_ms(self.inner.read().open_time_slice())
+    }
+
+    #[napi(getter = closeTimeArray)]
+    #[inline]
+    pub fn node_close_time_array(&self) -> BigInt64Array {
+        times_to_ms(self.inner.read().close_time_slice())
+    }
+
+    #[napi(getter = openArray)]
+    #[inline]
+    pub fn node_open_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.open())
+    }
+
+    #[napi(getter = highArray)]
+    #[inline]
+    pub fn node_high_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.high())
+    }
+
+    #[napi(getter = lowArray)]
+    #[inline]
+    pub fn node_low_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.low())
+    }
+
+    #[napi(getter = closeArray)]
+    #[inline]
+    pub fn node_close_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.close())
+    }
+
+    #[napi(getter = volumeArray)]
+    #[inline]
+    pub fn node_volume_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.volume())
+    }
+
     #[napi(getter = bars)]
     #[inline]
     pub fn node_bars(&self) -> Vec<NodeOhlcvBar> {
@@ -350,8 +544,50 @@ impl NodeOhlcv {
     }
 
     #[napi(js_name = "resample")]
-    pub fn node_resample(&self, timeframe: &NodeTimeframe, align_utc: bool) -> Self {
-        self.inner.resample(timeframe.into(), align_utc).into()
+    pub fn node_resample(
+        &self,
+        timeframe: &NodeTimeframe,
+        align_utc: bool,
+        min_tick: Option<f64>,
+    ) -> Result<Self> {
+        let timeframe: Timeframe = timeframe.into();
+        let min_tick = min_tick.unwrap_or(f64::NAN);
+        self.inner
+            .resample_with_tick(timeframe, align_utc, min_tick)
+            .map(Into::into)
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+    }
+
+    #[napi(js_name = "heikinAshi")]
+    pub fn node_heikin_ashi(&self) -> Self {
+        self.inner.heikin_ashi().into()
+    }
+
+    #[napi(js_name = "qualityReport")]
+    pub fn node_quality_report(
+        &self,
+        spike_zscore: Option<f64>,
+        zero_volume: Option<bool>,
+    ) -> Result<serde_json::Value> {
+        let mut config = OhlcvQualityConfig::new(self.inner.timeframe(), self.inner.timezone());
+        config.spike_zscore = spike_zscore.unwrap_or(config.spike_zscore);
+        config.zero_volume = zero_volume.unwrap_or(config.zero_volume);
+        let report = self.inner.quality_report(&config);
+        serde_json::to_value(&report).map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+    }
+
+    #[napi(js_name = "repair")]
+    pub fn node_repair(
+        &self,
+        fill_missing: Option<bool>,
+        clip_outliers: Option<bool>,
+        spike_zscore: Option<f64>,
+    ) -> Self {
+        let mut config = OhlcvRepairConfig::new(self.inner.timeframe(), self.inner.timezone());
+        config.fill_missing = fill_missing.unwrap_or(false);
+        config.clip_outliers = clip_outliers.unwrap_or(false);
+        config.quality.spike_zscore = spike_zscore.unwrap_or(config.quality.spike_zscore);
+        self.inner.repair(&config).into()
     }
 
     #[napi(js_name = "sort")]
@@ -390,6 +626,11 @@ impl NodeOhlcv {
             .push_many(bars.into_iter().map(|b| b.inner.clone()).collect());
     }
 
+    #[napi(js_name = "set")]
+    pub fn node_set(&mut self, index: u32, bar: &NodeOhlcvBar) {
+        self.inner.set(index as usize, bar.inner.into());
+    }
+
     #[napi(js_name = "toString")]
     pub fn node_to_string(&self) -> String {
         format!("{:?}", self.inner)
@@ -567,26 +808,30 @@ impl NodeOhlcv {
 impl NodeOhlcv {
     #[napi(js_name = "readCsv")]
     #[inline]
-    pub fn node_read_csv(path: String, timeframe: Option<&NodeTimeframe>) -> Self {
+    pub fn node_read_csv(path: String, timeframe: Option<&NodeTimeframe>) -> Result<Self> {
         let mut ohlcv = Ohlcv::new();
-        ohlcv.read_csv(Path::new(&path));
+        ohlcv
+            .read_csv(Path::new(&path))
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
         let ohlcv: ArcOhlcv = ohlcv.into();
         if let Some(tf) = timeframe {
             ohlcv.set_timeframe(tf.into());
         }
-        return ohlcv.into();
+        return Ok(ohlcv.into());
     }
 
     #[napi(js_name = "readParquet")]
     #[inline]
-    pub fn node_read_parquet(path: String, timeframe: Option<&NodeTimeframe>) -> Self {
+    pub fn node_read_parquet(path: String, timeframe: Option<&NodeTimeframe>) -> Result<Self> {
         let mut ohlcv = Ohlcv::new();
-        ohlcv.read_parquet(Path::new(&path));
+        ohlcv
+            .read_parquet(Path::new(&path))
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
         let ohlcv: ArcOhlcv = ohlcv.into();
         if let Some(tf) = timeframe {
             ohlcv.set_timeframe(tf.into());
         }
-        return ohlcv.into();
+        return Ok(ohlcv.into());
     }
 
     #[napi(js_name = "writeCsv")]
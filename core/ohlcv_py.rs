@@ -7,13 +7,33 @@ use crate::{
         zip_ohlcv_bars, ArcOhlcv, OhlcvBar, OhlcvReader, OhlcvReaderOps, OhlcvWriter,
         OhlcvWriterOps,
     },
+    timeframe::Timeframe,
     timeframe_py::PyTimeframe,
     utils::pyslice_to_range,
 };
 use chrono::{DateTime, Utc};
+use crate::align::{align, OhlcvAlignment, OhlcvFill, OhlcvJoin};
+use crate::quality::{OhlcvQualityConfig, OhlcvRepairConfig};
+use crate::ohlcv_arrow::{ohlcv_from_arrow, ohlcv_to_arrow};
+use crate::ohlcv_io::{close_times_of, Layout, OhlcvIoConfig, OhlcvTimeFormat};
+use std::collections::HashMap;
+use arrow2::{
+    array::Array,
+    ffi::{
+        export_iterator, import_array_from_c, import_field_from_c, ArrowArray,
+        ArrowArrayStream, ArrowArrayStreamReader, ArrowSchema,
+    },
+};
+use chrono_tz::Tz;
+use numpy::{
+    datetime::{units::Milliseconds, Datetime},
+    PyArray1,
+};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
-use pyo3::types::PySlice;
+use pyo3::types::{PyCapsule, PySlice};
+use std::ffi::CString;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyfunction, gen_stub_pymethods};
 
 impl IntoPy<PyResult<PyObject>> for OhlcvBar {
@@ -189,6 +209,257 @@ impl OhlcvBar {
     }
 }
 
+#[inline]
+fn datetime_array(py: Python<'_>, column: &[Option<DateTime<Utc>>]) -> PyObject {
+    let values: Vec<Datetime<Milliseconds>> = column
+        .iter()
+        .map(|x| x.map_or(i64::MIN, |x| x.timestamp_millis()).into())
+        .collect();
+    PyArray1::from_vec(py, values).into_any().unbind()
+}
+
+fn py_align_series(series: &[PyOhlcv], join: &str, fill: &str) -> PyResult<OhlcvAlignment> {
+    let join = OhlcvJoin::try_from(join).map_err(PyValueError::new_err)?;
+    let fill = OhlcvFill::try_from(fill).map_err(PyValueError::new_err)?;
+    let readers: Vec<&dyn OhlcvReader> = series
+        .iter()
+        .map(|s| &s.inner as &dyn OhlcvReader)
+        .collect();
+    Ok(align(&readers, join, fill))
+}
+
+/// Moved into a `PyCapsule`, consumers take the stream and leave it released.
+#[repr(transparent)]
+struct ArrowStream(ArrowArrayStream);
+
+unsafe impl Send for ArrowStream {}
+
+#[inline]
+fn arrow_err(e: impl ToString) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn py_io_config(
+    timeframe: Option<PyTimeframe>,
+    columns: Option<HashMap<String, Option<String>>>,
+    time_unit: &str,
+) -> PyResult<OhlcvIoConfig> {
+    let time_format = OhlcvTimeFormat::try_from(time_unit).map_err(PyValueError::new_err)?;
+    let mut config = OhlcvIoConfig::new(time_format);
+    if let Some(timeframe) = timeframe {
+        config.timeframe = timeframe.into();
+    }
+    for (field, name) in columns.unwrap_or_default() {
+        config
+            .columns
+            .set(&field, name)
+            .map_err(PyValueError::new_err)?;
+    }
+    Ok(config)
+}
+
+fn py_column_err(name: &str) -> impl Fn(PyErr) -> PyErr + '_ {
+    move |e| PyValueError::new_err(format!("Invalid column {}: {}", name, e))
+}
+
+/// Datetime columns are read as UTC, naive ones are assumed to be UTC. Other values go through `time_format`.
+fn py_time_column(
+    py: Python<'_>,
+    series: &Bound<'_, PyAny>,
+    name: &str,
+    time_format: OhlcvTimeFormat,
+    polars: bool,
+) -> PyResult<Vec<Option<DateTime<Utc>>>> {
+    let err = py_column_err(name);
+    if polars {
+        let pl = py.import("polars")?;
+        let dtype = series.getattr("dtype")?;
+        if dtype.eq(pl.getattr("Datetime")?)? || dtype.eq(pl.getattr("Date")?)? {
+            let ms: Vec<Option<i64>> = series
+                .getattr("dt")?
+                .call_method1("epoch", ("ms",))?
+                .call_method0("to_list")?
+                .extract()
+                .map_err(&err)?;
+            return Ok(ms
+                .into_iter()
+                .map(|x| x.and_then(|x| OhlcvTimeFormat::Milliseconds.from_i64(x)))
+                .collect());
+        }
+    } else {
+        let pd = py.import("pandas")?;
+        let is_datetime = pd
+            .getattr("api")?
+            .getattr("types")?
+            .call_method1("is_datetime64_any_dtype", (series,))?
+            .is_truthy()?;
+        if is_datetime {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("utc", true)?;
+            let ns: Vec<i64> = pd
+                .call_method("to_datetime", (series,), Some(&kwargs))?
+                .getattr("dt")?
+                .call_method1("tz_localize", (py.None(),))?
+                .call_method1("to_numpy", ("datetime64[ns]",))?
+                .call_method1("view", ("int64",))?
+                .call_method0("tolist")?
+                .extract()
+                .map_err(&err)?;
+            return Ok(ns
+                .into_iter()
+                .map(|x| {
+                    (x != i64::MIN)
+                        .then_some(x)
+                        .and_then(|x| OhlcvTimeFormat::Nanoseconds.from_i64(x))
+                })
+                .collect());
+        }
+    }
+    let values = series.call_method0(if polars { "to_list" } else { "tolist" })?;
+    let mut times = vec![];
+    for (i, value) in values.try_iter()?.enumerate() {
+        let value = value?;
+        let time = if value.is_none() {
+            None
+        } else if let Ok(x) = value.extract::<i64>() {
+            Some(time_format.from_i64(x))
+        } else if let Ok(x) = value.extract::<f64>() {
+            (!x.is_nan()).then(|| time_format.from_f64(x))
+        } else if let Ok(x) = value.extract::<String>() {
+            Some(time_format.parse(&x))
+        } else if let Ok(x) = value.extract::<DateTime<Utc>>() {
+            Some(Some(x))
+        } else {
+            Some(None)
+        };
+        match time {
+            Some(None) => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid column {}: invalid time {} at row {}",
+                    name,
+                    value.repr()?,
+                    i
+                )))
+            }
+            time => times.push(time.flatten()),
+        }
+    }
+    Ok(times)
+}
+
+/// Reads the columns of a pandas or polars DataFrame mapped by `config`. A pandas `DatetimeIndex`
+/// is used as open time when there is no open time column.
+fn py_ohlcv_from_frame(
+    py: Python<'_>,
+    df: &Bound<'_, PyAny>,
+    config: &OhlcvIoConfig,
+    polars: bool,
+) -> PyResult<Ohlcv> {
+    let labels: Vec<Bound<'_, PyAny>> = df.getattr("columns")?.try_iter()?.collect::<PyResult<_>>()?;
+    let header: Vec<String> = labels
+        .iter()
+        .map(|x| Ok(x.str()?.to_string()))
+        .collect::<PyResult<_>>()?;
+    let layout = Layout::new(&header, config).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let column = |i: usize| {
+        if polars {
+            df.call_method1("get_column", (&labels[i],))
+        } else {
+            df.get_item(&labels[i])
+        }
+    };
+    let float = |i: Option<usize>| -> PyResult<Vec<f64>> {
+        let Some(i) = i else {
+            return Ok(vec![f64::NAN; df.len()?]);
+        };
+        let err = py_column_err(&header[i]);
+        let values: Vec<Option<f64>> = if polars {
+            let pl = py.import("polars")?;
+            column(i)?
+                .call_method1("cast", (pl.getattr("Float64")?,))
+                .map_err(&err)?
+                .call_method0("to_list")?
+                .extract()
+                .map_err(&err)?
+        } else {
+            column(i)?
+                .call_method1("astype", ("float64",))
+                .map_err(&err)?
+                .call_method0("tolist")?
+                .extract()
+                .map_err(&err)?
+        };
+        Ok(values.into_iter().map(|x| x.unwrap_or(f64::NAN)).collect())
+    };
+    let time = |i: Option<usize>| -> PyResult<Option<Vec<Option<DateTime<Utc>>>>> {
+        match i {
+            Some(i) => py_time_column(py, &column(i)?, &header[i], config.time_format, polars).map(Some),
+            None => Ok(None),
+        }
+    };
+    let open_time = match time(layout.open_time)? {
+        Some(open_time) => open_time,
+        None if !polars && df.getattr("index")?.hasattr("tz")? => {
+            let index = df.getattr("index")?.call_method0("to_series")?;
+            py_time_column(py, &index, "index", config.time_format, false)?
+        }
+        None => vec![None; df.len()?],
+    };
+    let close_time = match time(layout.close_time)? {
+        Some(close_time) => close_time,
+        None => close_times_of(&open_time, config.timeframe),
+    };
+    let mut ohlcv = Ohlcv::from_columns(
+        open_time,
+        close_time,
+        float(Some(layout.open))?,
+        float(Some(layout.high))?,
+        float(Some(layout.low))?,
+        float(Some(layout.close))?,
+        float(layout.volume)?,
+    );
+    ohlcv.set_timeframe(config.timeframe);
+    Ok(ohlcv)
+}
+
+/// Record batches of an object implementing the Arrow PyCapsule interface, e.g. `pyarrow.Table` or `polars.DataFrame`.
+fn arrow_batches(obj: &Bound<'_, PyAny>) -> PyResult<Vec<Box<dyn Array>>> {
+    if obj.hasattr("__arrow_c_stream__")? {
+        let capsule = obj.call_method0("__arrow_c_stream__")?;
+        let capsule = capsule.downcast::<PyCapsule>()?;
+        if capsule.name()? != Some(c"arrow_array_stream") {
+            return Err(PyValueError::new_err("Expected an arrow_array_stream capsule"));
+        }
+        let stream = unsafe {
+            std::ptr::replace(
+                capsule.pointer() as *mut ArrowArrayStream,
+                ArrowArrayStream::empty(),
+            )
+        };
+        let mut reader =
+            unsafe { ArrowArrayStreamReader::try_new(Box::new(stream)) }.map_err(arrow_err)?;
+        let mut batches = vec![];
+        while let Some(batch) = unsafe { reader.next() } {
+            batches.push(batch.map_err(arrow_err)?);
+        }
+        return Ok(batches);
+    }
+    if obj.hasattr("__arrow_c_array__")? {
+        let (schema, array): (Bound<'_, PyCapsule>, Bound<'_, PyCapsule>) =
+            obj.call_method0("__arrow_c_array__")?.extract()?;
+        let field = unsafe { import_field_from_c(&*(schema.pointer() as *const ArrowSchema)) }
+            .map_err(arrow_err)?;
+        let array = unsafe {
+            std::ptr::replace(array.pointer() as *mut ArrowArray, ArrowArray::empty())
+        };
+        let batch = unsafe { import_array_from_c(array, field.data_type) }.map_err(arrow_err)?;
+        return Ok(vec![batch]);
+    }
+    return Err(PyValueError::new_err(
+        "Expected an object implementing __arrow_c_stream__ or __arrow_c_array__",
+    ));
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "Ohlcv")]
 #[derive(Debug, Clone)]
@@ -250,6 +521,22 @@ impl PyOhlcv {
         self.inner.set_timeframe(timeframe.into());
     }
 
+    #[getter(timezone)]
+    #[inline]
+    pub fn py_timezone(&self) -> String {
+        self.inner.timezone().name().to_string()
+    }
+
+    #[setter(timezone)]
+    #[inline]
+    pub fn py_set_timezone(&mut self, timezone: String) -> PyResult<()> {
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.inner.set_timezone(timezone);
+        Ok(())
+    }
+
     #[getter(open_time)]
     #[inline]
     pub fn py_open_time(&self) -> Vec<Option<DateTime<Utc>>> {
@@ -292,6 +579,55 @@ impl PyOhlcv {
         self.inner.volume()
     }
 
+    #[getter(open_time_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_open_time_array(&self, py: Python<'_>) -> PyObject {
+        datetime_array(py, self.inner.read().open_time_slice())
+    }
+
+    #[getter(close_time_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_close_time_array(&self, py: Python<'_>) -> PyObject {
+        datetime_array(py, self.inner.read().close_time_slice())
+    }
+
+    #[getter(open_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_open_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.read().open_slice())
+    }
+
+    #[getter(high_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_high_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.read().high_slice())
+    }
+
+    #[getter(low_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_low_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.read().low_slice())
+    }
+
+    #[getter(close_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_close_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.read().close_slice())
+    }
+
+    #[getter(volume_array)]
+    #[inline]
+    #[doc = "Numpy copy of the column, bars pushed later are not reflected."]
+    pub fn py_volume_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.read().volume_slice())
+    }
+
     #[getter(bars)]
     #[inline]
     pub fn py_bars(&self) -> Vec<OhlcvBar> {
@@ -347,10 +683,53 @@ impl PyOhlcv {
         self.inner.extend(&other.inner);
     }
 
-    #[pyo3(name = "resample")]
+    #[pyo3(name = "resample", signature = (timeframe, align_utc, min_tick=None))]
     #[inline]
-    pub fn py_resample(&self, timeframe: PyTimeframe, align_utc: bool) -> Self {
-        self.inner.resample(timeframe.into(), align_utc).into()
+    pub fn py_resample(
+        &self,
+        timeframe: PyTimeframe,
+        align_utc: bool,
+        min_tick: Option<f64>,
+    ) -> PyResult<Self> {
+        let timeframe: Timeframe = timeframe.into();
+        let min_tick = min_tick.unwrap_or(f64::NAN);
+        self.inner
+            .resample_with_tick(timeframe, align_utc, min_tick)
+            .map(Into::into)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "heikin_ashi")]
+    #[inline]
+    pub fn py_heikin_ashi(&self) -> Self {
+        self.inner.heikin_ashi().into()
+    }
+
+    #[pyo3(name = "quality_report", signature = (spike_zscore=10.0, zero_volume=true))]
+    #[inline]
+    pub fn py_quality_report(
+        &self,
+        py: Python<'_>,
+        spike_zscore: f64,
+        zero_volume: bool,
+    ) -> PyResult<PyObject> {
+        let mut config = OhlcvQualityConfig::new(self.inner.timeframe(), self.inner.timezone());
+        config.spike_zscore = spike_zscore;
+        config.zero_volume = zero_volume;
+        let report = self.inner.quality_report(&config);
+        let any = pythonize::pythonize(py, &report)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(any.into())
+    }
+
+    #[pyo3(name = "repair", signature = (fill_missing=false, clip_outliers=false, spike_zscore=10.0))]
+    #[inline]
+    pub fn py_repair(&self, fill_missing: bool, clip_outliers: bool, spike_zscore: f64) -> Self {
+        let mut config = OhlcvRepairConfig::new(self.inner.timeframe(), self.inner.timezone());
+        config.fill_missing = fill_missing;
+        config.clip_outliers = clip_outliers;
+        config.quality.spike_zscore = spike_zscore;
+        self.inner.repair(&config).into()
     }
 
     #[pyo3(name = "sort")]
@@ -395,61 +774,117 @@ impl PyOhlcv {
         self.inner.push_many(bars);
     }
 
-    #[staticmethod]
-    #[pyo3(name = "from_pandas", signature = (df, timeframe=None))]
+    #[pyo3(name = "set")]
     #[inline]
+    pub fn py_set(&mut self, index: usize, bar: OhlcvBar) {
+        self.inner.set(index, bar);
+    }
+
+    #[doc = "Exports a copy of the columns, the Arrow arrays own their buffers."]
+    #[pyo3(name = "__arrow_c_stream__", signature = (requested_schema=None))]
+    pub fn py_arrow_c_stream<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let _ = requested_schema;
+        let (field, array) = ohlcv_to_arrow(&self.inner.read());
+        let stream = export_iterator(Box::new(std::iter::once(Ok(array))), field);
+        let name = CString::new("arrow_array_stream").unwrap();
+        Ok(PyCapsule::new(py, ArrowStream(stream), Some(name))?.into_any().unbind())
+    }
+
+    #[staticmethod]
+    #[doc = "Copies the columns of any object implementing the Arrow PyCapsule interface."]
+    #[pyo3(name = "from_arrow", signature = (data, timeframe=None, columns=None, time_unit="auto"))]
+    pub fn py_from_arrow(
+        data: &Bound<'_, PyAny>,
+        timeframe: Option<PyTimeframe>,
+        columns: Option<HashMap<String, Option<String>>>,
+        time_unit: &str,
+    ) -> PyResult<Self> {
+        let config = py_io_config(timeframe, columns, time_unit)?;
+        let ohlcv = ohlcv_from_arrow(arrow_batches(data)?, &config).map_err(arrow_err)?;
+        Ok(ohlcv.into())
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_pandas", signature = (df, timeframe=None, columns=None, time_unit="auto"))]
     pub fn py_from_pandas(
         py: Python<'_>,
         df: &Bound<'_, PyAny>,
         timeframe: Option<PyTimeframe>,
-    ) -> PyResult<PyOhlcv> {
-        let open: Option<Vec<f64>> = if df.hasattr("open")? {
-            df.getattr("open")?.extract()?
-        } else {
-            None
-        };
-        let high: Option<Vec<f64>> = if df.hasattr("high")? {
-            df.getattr("high")?.extract()?
-        } else {
-            None
-        };
-        let low: Option<Vec<f64>> = if df.hasattr("low")? {
-            df.getattr("low")?.extract()?
-        } else {
-            None
-        };
-        let close: Option<Vec<f64>> = if df.hasattr("close")? {
-            df.getattr("close")?.extract()?
-        } else {
-            None
-        };
-        let volume: Option<Vec<f64>> = if df.hasattr("volume")? {
-            df.getattr("volume")?.extract()?
-        } else {
-            None
-        };
-        let open_time: Option<Vec<Option<DateTime<Utc>>>> = if df.hasattr("open_time")? {
-            df.getattr("open_time")?.extract()?
-        } else if df.hasattr("time")? {
-            df.getattr("time")?.extract()?
-        } else {
-            None
-        };
-        let close_time: Option<Vec<Option<DateTime<_>>>> = if df.hasattr("close_time")? {
-            df.getattr("close_time")?.extract()?
-        } else if df.hasattr("time")? {
-            df.getattr("time")?.extract()?
-        } else {
-            None
-        };
+        columns: Option<HashMap<String, Option<String>>>,
+        time_unit: &str,
+    ) -> PyResult<Self> {
+        let config = py_io_config(timeframe, columns, time_unit)?;
+        Ok(py_ohlcv_from_frame(py, df, &config, false)?.into())
+    }
 
-        let ohlcv = ArcOhlcv::from_bars(zip_ohlcv_bars(
-            open_time, close_time, open, high, low, close, volume,
-        ));
-        if let Some(tf) = timeframe {
-            ohlcv.set_timeframe(tf.into());
+    #[staticmethod]
+    #[pyo3(name = "from_polars", signature = (df, timeframe=None, columns=None, time_unit="auto"))]
+    pub fn py_from_polars(
+        py: Python<'_>,
+        df: &Bound<'_, PyAny>,
+        timeframe: Option<PyTimeframe>,
+        columns: Option<HashMap<String, Option<String>>>,
+        time_unit: &str,
+    ) -> PyResult<Self> {
+        let config = py_io_config(timeframe, columns, time_unit)?;
+        // columns arrow2 cannot import, e.g. string views, are read one by one instead
+        if df.hasattr("__arrow_c_stream__")? {
+            if let Ok(ohlcv) = arrow_batches(df)
+                .and_then(|batches| ohlcv_from_arrow(batches, &config).map_err(arrow_err))
+            {
+                return Ok(ohlcv.into());
+            }
         }
-        Ok(ohlcv.into())
+        Ok(py_ohlcv_from_frame(py, df, &config, true)?.into())
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "align", signature = (series, join="inner", fill="ffill"))]
+    #[inline]
+    pub fn py_align(series: Vec<PyOhlcv>, join: &str, fill: &str) -> PyResult<Vec<PyOhlcv>> {
+        let aligned = py_align_series(&series, join, fill)?;
+        Ok(aligned
+            .into_series()
+            .into_iter()
+            .map(|ohlcv| Into::<ArcOhlcv>::into(ohlcv).into())
+            .collect())
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "align_to_pandas", signature = (series, names, join="inner", fill="ffill"))]
+    #[inline]
+    pub fn py_align_to_pandas(
+        py: Python<'_>,
+        series: Vec<PyOhlcv>,
+        names: Vec<String>,
+        join: &str,
+        fill: &str,
+    ) -> PyResult<PandasDataFrame> {
+        if names.len() != series.len() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} names, got {}",
+                series.len(),
+                names.len()
+            )));
+        }
+        let aligned = py_align_series(&series, join, fill)?;
+        let dict = PyDict::new(py);
+        dict.set_item("open_time", aligned.open_time().to_vec())?;
+        for (name, ohlcv) in names.iter().zip(aligned.series()) {
+            dict.set_item(format!("{}_open", name), ohlcv.open())?;
+            dict.set_item(format!("{}_high", name), ohlcv.high())?;
+            dict.set_item(format!("{}_low", name), ohlcv.low())?;
+            dict.set_item(format!("{}_close", name), ohlcv.close())?;
+            dict.set_item(format!("{}_volume", name), ohlcv.volume())?;
+        }
+        let pd = py.import("pandas")?;
+        let df = pd.getattr("DataFrame")?.call1((dict,))?;
+        let py_obj: PyObject = df.into();
+        Ok(py_obj.into())
     }
 
     #[pyo3(name = "to_pandas")]
@@ -480,28 +915,32 @@ impl PyOhlcv {
     #[staticmethod]
     #[pyo3(name = "read_csv", signature = (path, timeframe=None))]
     #[inline]
-    pub fn py_read_csv(path: String, timeframe: Option<PyTimeframe>) -> Self {
+    pub fn py_read_csv(path: String, timeframe: Option<PyTimeframe>) -> PyResult<Self> {
         let mut ohlcv = Ohlcv::new();
-        ohlcv.read_csv(Path::new(&path));
+        ohlcv
+            .read_csv(Path::new(&path))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
         let ohlcv: ArcOhlcv = ohlcv.into();
         if let Some(tf) = timeframe {
             ohlcv.set_timeframe(tf.into());
         }
-        return ohlcv.into();
+        return Ok(ohlcv.into());
     }
 
     #[cfg(feature = "polars")]
     #[staticmethod]
     #[pyo3(name = "read_parquet", signature = (path, timeframe=None))]
     #[inline]
-    pub fn py_read_parquet(path: String, timeframe: Option<PyTimeframe>) -> Self {
+    pub fn py_read_parquet(path: String, timeframe: Option<PyTimeframe>) -> PyResult<Self> {
         let mut ohlcv = Ohlcv::new();
-        ohlcv.read_parquet(Path::new(&path));
+        ohlcv
+            .read_parquet(Path::new(&path))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
         let ohlcv: ArcOhlcv = ohlcv.into();
         if let Some(tf) = timeframe {
             ohlcv.set_timeframe(tf.into());
         }
-        return ohlcv.into();
+        return Ok(ohlcv.into());
     }
 
     #[cfg(feature = "polars")]
@@ -0,0 +1,426 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::{
+    ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvWriter},
+    timeframe::Timeframe,
+};
+
+const MAGIC: &[u8; 8] = b"QPOHLCV\x01";
+const HEADER_SIZE: u64 = MAGIC.len() as u64;
+/// Open and close time in ms, then open, high, low, close and volume, all little endian.
+const RECORD_SIZE: u64 = 7 * 8;
+const EXTENSION: &str = "qpohlcv";
+
+#[derive(Debug, Error)]
+pub enum OhlcvStoreError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Corrupted store file {0:?}: {1}")]
+    Corrupted(PathBuf, String),
+    #[error("Bar {0} has no open time")]
+    MissingOpenTime(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum OhlcvOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Filters and paging of `OhlcvApi.Get`, time bounds are inclusive.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct OhlcvQuery {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub order: OhlcvOrder,
+    pub start_open_time: Option<DateTime<Utc>>,
+    pub end_open_time: Option<DateTime<Utc>>,
+    pub start_close_time: Option<DateTime<Utc>>,
+    pub end_close_time: Option<DateTime<Utc>>,
+}
+
+impl OhlcvQuery {
+    #[inline]
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    #[inline]
+    fn has_close_time(&self) -> bool {
+        return self.start_close_time.is_some() || self.end_close_time.is_some();
+    }
+
+    #[inline]
+    fn matches_close_time(&self, close_time: Option<&DateTime<Utc>>) -> bool {
+        if !self.has_close_time() {
+            return true;
+        }
+        return match close_time {
+            Some(time) => {
+                self.start_close_time.is_none_or(|start| *time >= start)
+                    && self.end_close_time.is_none_or(|end| *time <= end)
+            }
+            None => false,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OhlcvPage {
+    /// In the order of the query.
+    pub ohlcv: Ohlcv,
+    /// Number of bars stored for the symbol and timeframe.
+    pub total: usize,
+    /// Number of bars matching the query after this page.
+    pub remaining: usize,
+}
+
+/// Bars of each symbol and timeframe in their own file under `root`, sorted and unique by open time.
+/// Meant for a single writer, readers see either the old or the new file of a rewrite.
+/// A partial record left by an interrupted append is ignored and cut off by the next append.
+#[derive(Debug, Clone)]
+pub struct OhlcvStore {
+    root: PathBuf,
+}
+
+/// Keeps `[A-Za-z0-9_-]`, everything else is `%XX` encoded, e.g. `BINANCE:BTCUSDT` -> `BINANCE%3ABTCUSDT`.
+fn encode_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for b in name.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    return out;
+}
+
+fn decode_name(name: &str) -> Option<String> {
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    return String::from_utf8(out).ok();
+}
+
+#[inline]
+fn time_to_ms(time: Option<&DateTime<Utc>>) -> i64 {
+    return time.map_or(i64::MIN, |x| x.timestamp_millis());
+}
+
+#[inline]
+fn time_from_ms(ms: i64) -> Option<DateTime<Utc>> {
+    if ms == i64::MIN {
+        return None;
+    }
+    return DateTime::from_timestamp_millis(ms);
+}
+
+fn encode_bar(bar: &OhlcvBar, out: &mut Vec<u8>) {
+    out.extend_from_slice(&time_to_ms(bar.open_time()).to_le_bytes());
+    out.extend_from_slice(&time_to_ms(bar.close_time()).to_le_bytes());
+    for value in [bar.open(), bar.high(), bar.low(), bar.close(), bar.volume()] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn decode_bar(record: &[u8]) -> OhlcvBar {
+    let word = |i: usize| -> [u8; 8] { record[i * 8..i * 8 + 8].try_into().unwrap() };
+    let float = |i: usize| f64::from_le_bytes(word(i));
+    return OhlcvBar::new(
+        time_from_ms(i64::from_le_bytes(word(0))),
+        time_from_ms(i64::from_le_bytes(word(1))),
+        float(2),
+        float(3),
+        float(4),
+        float(5),
+        float(6),
+    );
+}
+
+/// Open store file, positioned anywhere. `len` counts whole records only.
+struct StoreFile {
+    file: File,
+    len: usize,
+}
+
+impl StoreFile {
+    fn open(path: &Path) -> Result<Option<Self>, OhlcvStoreError> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let corrupted =
+            |message: &str| OhlcvStoreError::Corrupted(path.to_path_buf(), message.to_string());
+        let size = file.metadata()?.len();
+        let mut magic = [0u8; 8];
+        if size < HEADER_SIZE || file.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            return Err(corrupted("not a qpace ohlcv store file"));
+        }
+        let len = ((size - HEADER_SIZE) / RECORD_SIZE) as usize;
+        return Ok(Some(Self { file, len }));
+    }
+
+    fn open_time_ms(&mut self, index: usize) -> Result<i64, OhlcvStoreError> {
+        let mut buf = [0u8; 8];
+        self.file
+            .seek(SeekFrom::Start(HEADER_SIZE + index as u64 * RECORD_SIZE))?;
+        self.file.read_exact(&mut buf)?;
+        return Ok(i64::from_le_bytes(buf));
+    }
+
+    /// First index with an open time `>= ms`.
+    fn lower_bound(&mut self, ms: i64) -> Result<usize, OhlcvStoreError> {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.open_time_ms(mid)? < ms {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        return Ok(lo);
+    }
+
+    fn read(&mut self, start: usize, end: usize) -> Result<Vec<OhlcvBar>, OhlcvStoreError> {
+        let mut buf = vec![0u8; (end - start) * RECORD_SIZE as usize];
+        self.file
+            .seek(SeekFrom::Start(HEADER_SIZE + start as u64 * RECORD_SIZE))?;
+        self.file.read_exact(&mut buf)?;
+        return Ok(buf
+            .chunks_exact(RECORD_SIZE as usize)
+            .map(decode_bar)
+            .collect());
+    }
+}
+
+impl OhlcvStore {
+    /// Creates `root` if needed.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, OhlcvStoreError> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        return Ok(Self { root });
+    }
+
+    #[inline]
+    pub fn root(&self) -> &Path {
+        return &self.root;
+    }
+
+    /// `root/<sym_id>/<timeframe>.qpohlcv`, names are escaped to be valid on any file system.
+    pub fn path(&self, sym_id: &str, timeframe: Timeframe) -> PathBuf {
+        let timeframe: String = timeframe.into();
+        return self.root.join(encode_name(sym_id)).join(format!(
+            "{}.{}",
+            encode_name(&timeframe),
+            EXTENSION
+        ));
+    }
+
+    /// Symbols and timeframes with stored bars.
+    pub fn list(&self) -> Result<Vec<(String, Timeframe)>, OhlcvStoreError> {
+        let mut entries = vec![];
+        for sym_dir in fs::read_dir(&self.root)? {
+            let sym_dir = sym_dir?;
+            if !sym_dir.file_type()?.is_dir() {
+                continue;
+            }
+            let Some(sym_id) = sym_dir.file_name().to_str().and_then(decode_name) else {
+                continue;
+            };
+            for file in fs::read_dir(sym_dir.path())? {
+                let path = file?.path();
+                if path.extension().and_then(|x| x.to_str()) != Some(EXTENSION) {
+                    continue;
+                }
+                if let Some(timeframe) = path
+                    .file_stem()
+                    .and_then(|x| x.to_str())
+                    .and_then(decode_name)
+                {
+                    entries.push((sym_id.clone(), Timeframe::from(timeframe)));
+                }
+            }
+        }
+        entries.sort_by(|a, b| (&a.0, a.1.to_string()).cmp(&(&b.0, b.1.to_string())));
+        return Ok(entries);
+    }
+
+    #[inline]
+    pub fn len(&self, sym_id: &str, timeframe: Timeframe) -> Result<usize, OhlcvStoreError> {
+        return Ok(StoreFile::open(&self.path(sym_id, timeframe))?.map_or(0, |x| x.len));
+    }
+
+    /// Writes `bars` and returns how many open times were not stored yet. Bars replace stored bars
+    /// with the same open time. Bars after the last stored one are appended, otherwise the file is rewritten.
+    pub fn append(
+        &self,
+        sym_id: &str,
+        timeframe: Timeframe,
+        bars: &dyn OhlcvReader,
+    ) -> Result<usize, OhlcvStoreError> {
+        let mut incoming: BTreeMap<i64, OhlcvBar> = BTreeMap::new();
+        for (i, bar) in bars.bars().into_iter().enumerate() {
+            let open_time = bar.open_time().ok_or(OhlcvStoreError::MissingOpenTime(i))?;
+            incoming.insert(open_time.timestamp_millis(), bar);
+        }
+        if incoming.is_empty() {
+            return Ok(0);
+        }
+        let path = self.path(sym_id, timeframe);
+        let mut stored = match StoreFile::open(&path)? {
+            Some(file) => file,
+            None => {
+                let len = incoming.len();
+                self.write(&path, incoming.values())?;
+                return Ok(len);
+            }
+        };
+        let first = *incoming.keys().next().unwrap();
+        if stored.len == 0 || stored.open_time_ms(stored.len - 1)? < first {
+            let mut buf = Vec::with_capacity(incoming.len() * RECORD_SIZE as usize);
+            for bar in incoming.values() {
+                encode_bar(bar, &mut buf);
+            }
+            let end = HEADER_SIZE + stored.len as u64 * RECORD_SIZE;
+            let mut file = OpenOptions::new().write(true).open(&path)?;
+            file.set_len(end)?;
+            file.seek(SeekFrom::Start(end))?;
+            file.write_all(&buf)?;
+            file.sync_data()?;
+            return Ok(incoming.len());
+        }
+        let len = stored.len;
+        let mut merged: BTreeMap<i64, OhlcvBar> = stored
+            .read(0, len)?
+            .into_iter()
+            .map(|bar| (time_to_ms(bar.open_time()), bar))
+            .collect();
+        merged.extend(incoming);
+        self.write(&path, merged.values())?;
+        return Ok(merged.len() - len);
+    }
+
+    /// Writes to a temporary file first and renames it over `path`.
+    fn write<'a>(
+        &self,
+        path: &Path,
+        bars: impl Iterator<Item = &'a OhlcvBar>,
+    ) -> Result<(), OhlcvStoreError> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        let tmp = path.with_extension(format!("{}.tmp", EXTENSION));
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            writer.write_all(MAGIC)?;
+            let mut buf = Vec::with_capacity(RECORD_SIZE as usize);
+            for bar in bars {
+                buf.clear();
+                encode_bar(bar, &mut buf);
+                writer.write_all(&buf)?;
+            }
+            writer
+                .into_inner()
+                .map_err(|e| e.into_error())?
+                .sync_data()?;
+        }
+        fs::rename(&tmp, path)?;
+        return Ok(());
+    }
+
+    /// Bars matching `query`, open time bounds are looked up by binary search.
+    /// Without close time bounds only the bars of the page are read.
+    pub fn get(
+        &self,
+        sym_id: &str,
+        timeframe: Timeframe,
+        query: &OhlcvQuery,
+    ) -> Result<OhlcvPage, OhlcvStoreError> {
+        let mut ohlcv = Ohlcv::new();
+        ohlcv.set_timeframe(timeframe);
+        let Some(mut file) = StoreFile::open(&self.path(sym_id, timeframe))? else {
+            return Ok(OhlcvPage {
+                ohlcv,
+                total: 0,
+                remaining: 0,
+            });
+        };
+        let start = match query.start_open_time {
+            Some(time) => file.lower_bound(time.timestamp_millis())?,
+            None => 0,
+        };
+        let end = match query.end_open_time {
+            Some(time) => file.lower_bound(time.timestamp_millis().saturating_add(1))?,
+            None => file.len,
+        };
+        let end = end.max(start);
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(usize::MAX);
+        if !query.has_close_time() {
+            // only the requested page is read
+            let offset = offset.min(end - start);
+            let limit = limit.min(end - start - offset);
+            let mut bars = match query.order {
+                OhlcvOrder::Asc => file.read(start + offset, start + offset + limit)?,
+                OhlcvOrder::Desc => file.read(end - offset - limit, end - offset)?,
+            };
+            if query.order == OhlcvOrder::Desc {
+                bars.reverse();
+            }
+            ohlcv.push_many(bars);
+            return Ok(OhlcvPage {
+                ohlcv,
+                total: file.len,
+                remaining: end - start - offset - limit,
+            });
+        }
+        let mut bars = file.read(start, end)?;
+        bars.retain(|bar| query.matches_close_time(bar.close_time()));
+        if query.order == OhlcvOrder::Desc {
+            bars.reverse();
+        }
+        let offset = offset.min(bars.len());
+        let limit = limit.min(bars.len() - offset);
+        ohlcv.push_many(bars[offset..offset + limit].to_vec());
+        return Ok(OhlcvPage {
+            ohlcv,
+            total: file.len,
+            remaining: bars.len() - offset - limit,
+        });
+    }
+
+    /// Every stored bar in ascending order, e.g. for `Ctx::set_ohlcv`.
+    pub fn read(&self, sym_id: &str, timeframe: Timeframe) -> Result<Ohlcv, OhlcvStoreError> {
+        return Ok(self.get(sym_id, timeframe, &OhlcvQuery::default())?.ohlcv);
+    }
+
+    /// Returns whether there was anything to remove.
+    pub fn remove(&self, sym_id: &str, timeframe: Timeframe) -> Result<bool, OhlcvStoreError> {
+        return match fs::remove_file(self.path(sym_id, timeframe)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        };
+    }
+}
@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod test {
+    use crate::{
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        ohlcv_store::{OhlcvOrder, OhlcvQuery, OhlcvStore, OhlcvStoreError},
+        test_utils::time,
+        timeframe::Timeframe,
+    };
+
+    fn ohlcv(minutes: std::ops::Range<i64>, close: f64) -> Ohlcv {
+        return Ohlcv::from_bars(
+            minutes
+                .map(|i| {
+                    OhlcvBar::new(
+                        Some(time(i)),
+                        Some(time(i + 1)),
+                        close,
+                        close,
+                        close,
+                        close,
+                        i as f64,
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    fn store(name: &str) -> OhlcvStore {
+        let root = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&root);
+        return OhlcvStore::open(root).unwrap();
+    }
+
+    #[test]
+    pub fn append_dedupes_on_open_time() {
+        let store = store("qpace_ohlcv_store_test_append");
+        let tf = Timeframe::Minutes(1);
+        assert_eq!(
+            store
+                .append("BINANCE:BTCUSDT", tf, &ohlcv(0..10, 1.0))
+                .unwrap(),
+            10
+        );
+        // an append interrupted halfway through a record
+        let path = store.path("BINANCE:BTCUSDT", tf);
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, &[1u8; 20]).unwrap();
+        assert_eq!(store.len("BINANCE:BTCUSDT", tf).unwrap(), 10);
+        assert_eq!(
+            store
+                .append("BINANCE:BTCUSDT", tf, &ohlcv(10..20, 1.0))
+                .unwrap(),
+            10
+        );
+        // overlaps the stored range, newer bars win
+        assert_eq!(
+            store
+                .append("BINANCE:BTCUSDT", tf, &ohlcv(15..25, 2.0))
+                .unwrap(),
+            5
+        );
+        assert_eq!(store.len("BINANCE:BTCUSDT", tf).unwrap(), 25);
+
+        let read = store.read("BINANCE:BTCUSDT", tf).unwrap();
+        assert_eq!(read.timeframe(), tf);
+        assert_eq!(read.get(14).unwrap().close(), 1.0);
+        assert_eq!(read.get(15).unwrap().close(), 2.0);
+        assert_eq!(read.at(-1).unwrap().open_time(), Some(&time(24)));
+        assert_eq!(
+            store.list().unwrap(),
+            vec![("BINANCE:BTCUSDT".to_string(), tf)]
+        );
+
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(read.into_box());
+        let mut volume = 0.0;
+        while ctx.next().is_some() {
+            volume += ctx.bar().volume();
+        }
+        assert_eq!(volume, (0..25).sum::<i64>() as f64);
+
+        let mut bar = OhlcvBar::default();
+        bar.set_close(1.0);
+        assert!(matches!(
+            store.append("X", tf, &Ohlcv::from_bars(vec![bar])),
+            Err(OhlcvStoreError::MissingOpenTime(0))
+        ));
+        assert!(store.remove("BINANCE:BTCUSDT", tf).unwrap());
+        assert_eq!(store.len("BINANCE:BTCUSDT", tf).unwrap(), 0);
+        let _ = std::fs::remove_dir_all(store.root());
+    }
+
+    #[test]
+    pub fn get_matches_request_semantics() {
+        let store = store("qpace_ohlcv_store_test_get");
+        let tf = Timeframe::Minutes(1);
+        store.append("ETH", tf, &ohlcv(0..100, 1.0)).unwrap();
+
+        let page = store
+            .get(
+                "ETH",
+                tf,
+                &OhlcvQuery {
+                    start_open_time: Some(time(10)),
+                    end_open_time: Some(time(29)),
+                    limit: Some(5),
+                    offset: Some(2),
+                    ..OhlcvQuery::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(page.total, 100);
+        assert_eq!(page.remaining, 13);
+        assert_eq!(page.ohlcv.len(), 5);
+        assert_eq!(page.ohlcv.get(0).unwrap().open_time(), Some(&time(12)));
+
+        let page = store
+            .get(
+                "ETH",
+                tf,
+                &OhlcvQuery {
+                    end_close_time: Some(time(50)),
+                    order: OhlcvOrder::Desc,
+                    limit: Some(3),
+                    ..OhlcvQuery::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(page.remaining, 47);
+        assert_eq!(page.ohlcv.get(0).unwrap().open_time(), Some(&time(49)));
+        assert_eq!(page.ohlcv.get(2).unwrap().open_time(), Some(&time(47)));
+
+        let page = store
+            .get(
+                "ETH",
+                tf,
+                &OhlcvQuery {
+                    start_open_time: Some(time(90)),
+                    order: OhlcvOrder::Desc,
+                    limit: Some(4),
+                    offset: Some(3),
+                    ..OhlcvQuery::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(page.remaining, 3);
+        assert_eq!(page.ohlcv.len(), 4);
+        assert_eq!(page.ohlcv.get(0).unwrap().open_time(), Some(&time(96)));
+        assert_eq!(page.ohlcv.get(3).unwrap().open_time(), Some(&time(93)));
+        let page = store
+            .get(
+                "ETH",
+                tf,
+                &OhlcvQuery {
+                    offset: Some(200),
+                    ..OhlcvQuery::default()
+                },
+            )
+            .unwrap();
+        assert_eq!((page.ohlcv.len(), page.remaining), (0, 0));
+
+        let page = store.get("SOL", tf, &OhlcvQuery::default()).unwrap();
+        assert_eq!((page.total, page.ohlcv.len()), (0, 0));
+        let _ = std::fs::remove_dir_all(store.root());
+    }
+}
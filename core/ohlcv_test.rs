@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod test {
+    use crate::{
+        ohlcv::{
+            ArcOhlcv, Ohlcv, OhlcvBar, OhlcvReader, OhlcvReaderOps, OhlcvWriter, OhlcvWriterOps,
+        },
+        test_utils::time,
+    };
+
+    fn bar(minutes: i64, close: f64) -> OhlcvBar {
+        return OhlcvBar::new(
+            Some(time(minutes)),
+            Some(time(minutes + 1)),
+            close - 0.25,
+            close + 1.0,
+            close - 1.0,
+            close,
+            minutes as f64,
+        );
+    }
+
+    #[test]
+    pub fn columns_match_bars() {
+        let bars: Vec<OhlcvBar> = (0..5).map(|i| bar(i, 10.0 + i as f64)).collect();
+        let mut ohlcv = Ohlcv::from_bars(bars.clone());
+        assert_eq!(ohlcv.bars(), bars);
+        assert_eq!(ohlcv.close_slice(), &[10.0, 11.0, 12.0, 13.0, 14.0]);
+        assert_eq!(ohlcv.volume(), ohlcv.volume_slice().to_vec());
+        assert_eq!(ohlcv.open_time_slice()[2], Some(time(2)));
+        assert_eq!(ohlcv.slice(1..3), bars[1..3].to_vec());
+
+        let same = Ohlcv::from_columns(
+            ohlcv.open_time(),
+            ohlcv.close_time(),
+            ohlcv.open(),
+            ohlcv.high(),
+            ohlcv.low(),
+            ohlcv.close(),
+            ohlcv.volume(),
+        );
+        assert_eq!(same.bars(), bars);
+
+        ohlcv.set(1, bar(1, 20.0));
+        assert_eq!(ohlcv.close_slice()[1], 20.0);
+        assert_eq!(ohlcv.pop(), Some(bars[4]));
+        assert_eq!(ohlcv.shift(), Some(bars[0]));
+        ohlcv.reverse();
+        assert_eq!(ohlcv.close_slice(), &[13.0, 12.0, 20.0]);
+        ohlcv.sort(true);
+        assert_eq!(ohlcv.close_slice(), &[20.0, 12.0, 13.0]);
+        ohlcv.extend(&same);
+        assert_eq!(ohlcv.len(), 8);
+        assert_eq!(ohlcv.at(-1), Some(bars[4]));
+        assert_eq!(ohlcv.tail(2), bars[3..5].to_vec());
+        ohlcv.clear();
+        assert!(ohlcv.get(0).is_none());
+
+        let shared: ArcOhlcv = same.into();
+        assert_eq!(shared.read().high_slice()[0], 11.0);
+    }
+}
@@ -3,7 +3,12 @@ use crate::ohlcv::OhlcvReaderOps;
 use crate::ohlcv::OhlcvWriterOps;
 use crate::ohlcv::RcOhlcv;
 use crate::ohlcv::{OhlcvBar, OhlcvReader, OhlcvWriter};
+use crate::ohlcv_arrow::{read_ohlcv_ipc, write_ohlcv_ipc};
+use crate::ohlcv_io::{read_csv, read_jsonl, write_csv, write_jsonl, OhlcvIoConfig, OhlcvTimeFormat};
+use crate::timeframe::Timeframe;
 use crate::timeframe_wasm::WasmTimeframe;
+use crate::quality::{OhlcvQualityConfig, OhlcvRepairConfig};
+use chrono_tz::Tz;
 use wasm_bindgen::prelude::*;
 cfg_if::cfg_if! { if #[cfg(target_arch = "wasm32")] {
 use crate::ohlcv::zip_ohlcv_bars;
@@ -182,6 +187,52 @@ impl OhlcvBar {
     }
 }
 
+fn io_config(time_format: Option<String>, delimiter: Option<String>) -> Result<OhlcvIoConfig, JsError> {
+    let mut config = OhlcvIoConfig::default();
+    if let Some(time_format) = time_format {
+        config.time_format =
+            OhlcvTimeFormat::try_from(time_format.as_str()).map_err(|e| JsError::new(&e))?;
+    }
+    if let Some(delimiter) = delimiter {
+        match delimiter.as_bytes() {
+            [delimiter] => config.delimiter = *delimiter,
+            _ => return Err(JsError::new("Delimiter must be a single ASCII character")),
+        }
+    }
+    Ok(config)
+}
+
+/// Unix ms, `i64::MIN` for a missing time.
+#[inline]
+fn times_to_ms(times: &[Option<chrono::DateTime<chrono::Utc>>]) -> Vec<i64> {
+    times
+        .iter()
+        .map(|x| x.map_or(i64::MIN, |x| x.timestamp_millis()))
+        .collect()
+}
+
+#[inline]
+fn times_from_ms(
+    ms: Option<Vec<i64>>,
+    len: usize,
+) -> Result<Vec<Option<chrono::DateTime<chrono::Utc>>>, JsError> {
+    match ms {
+        Some(ms) if ms.len() != len => Err(JsError::new(&format!(
+            "Expected {} times, got {}",
+            len,
+            ms.len()
+        ))),
+        Some(ms) => Ok(ms
+            .into_iter()
+            .map(|x| match x {
+                i64::MIN => None,
+                x => chrono::DateTime::from_timestamp_millis(x),
+            })
+            .collect()),
+        None => Ok(vec![None; len]),
+    }
+}
+
 #[wasm_bindgen(js_name = "Ohlcv")]
 #[derive(Clone, Debug)]
 pub struct WasmOhlcv {
@@ -237,6 +288,110 @@ impl WasmOhlcv {
         Ohlcv::from_bars(bars).into()
     }
 
+    /// Missing times are `-(2n ** 63n)`.
+    #[wasm_bindgen(js_name = "fromArrays")]
+    pub fn wasm_from_arrays(
+        open: Vec<f64>,
+        high: Vec<f64>,
+        low: Vec<f64>,
+        close: Vec<f64>,
+        volume: Option<Vec<f64>>,
+        open_time: Option<Vec<i64>>,
+        close_time: Option<Vec<i64>>,
+    ) -> Result<WasmOhlcv, JsError> {
+        let len = close.len();
+        for column in [&open, &high, &low] {
+            if column.len() != len {
+                return Err(JsError::new(&format!(
+                    "Expected {} values, got {}",
+                    len,
+                    column.len()
+                )));
+            }
+        }
+        let volume = volume.unwrap_or_else(|| vec![f64::NAN; len]);
+        if volume.len() != len {
+            return Err(JsError::new(&format!(
+                "Expected {} volumes, got {}",
+                len,
+                volume.len()
+            )));
+        }
+        let ohlcv = Ohlcv::from_columns(
+            times_from_ms(open_time, len)?,
+            times_from_ms(close_time, len)?,
+            open,
+            high,
+            low,
+            close,
+            volume,
+        );
+        Ok(ohlcv.into())
+    }
+
+    #[wasm_bindgen(js_name = "fromArrowIPC")]
+    pub fn wasm_from_arrow_ipc(data: &[u8]) -> Result<WasmOhlcv, JsError> {
+        let ohlcv = read_ohlcv_ipc(data, &OhlcvIoConfig::default()).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(ohlcv.into())
+    }
+
+    #[wasm_bindgen(js_name = "toArrowIPC")]
+    pub fn wasm_to_arrow_ipc(&self) -> Result<Vec<u8>, JsError> {
+        write_ohlcv_ipc(&self.inner.borrow()).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "fromCSV")]
+    pub fn wasm_from_csv(
+        text: &str,
+        time_format: Option<String>,
+        delimiter: Option<String>,
+    ) -> Result<WasmOhlcv, JsError> {
+        let config = io_config(time_format, delimiter)?;
+        let ohlcv = read_csv(text.as_bytes(), &config).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(ohlcv.into())
+    }
+
+    #[wasm_bindgen(js_name = "toCSV")]
+    pub fn wasm_to_csv(
+        &self,
+        time_format: Option<String>,
+        delimiter: Option<String>,
+    ) -> Result<String, JsError> {
+        let config = io_config(time_format, delimiter)?;
+        let mut out = vec![];
+        write_csv(&mut out, &*self.inner.borrow(), &config)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[wasm_bindgen(js_name = "fromJSONL")]
+    pub fn wasm_from_jsonl(text: &str, time_format: Option<String>) -> Result<WasmOhlcv, JsError> {
+        let config = io_config(time_format, None)?;
+        let ohlcv = read_jsonl(text.as_bytes(), &config).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(ohlcv.into())
+    }
+
+    #[wasm_bindgen(js_name = "toJSONL")]
+    pub fn wasm_to_jsonl(&self, time_format: Option<String>) -> Result<String, JsError> {
+        let config = io_config(time_format, None)?;
+        let mut out = vec![];
+        write_jsonl(&mut out, &*self.inner.borrow(), &config)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[wasm_bindgen(getter = openTimeArray)]
+    #[inline]
+    pub fn wasm_open_time_array(&self) -> Vec<i64> {
+        times_to_ms(self.inner.borrow().open_time_slice())
+    }
+
+    #[wasm_bindgen(getter = closeTimeArray)]
+    #[inline]
+    pub fn wasm_close_time_array(&self) -> Vec<i64> {
+        times_to_ms(self.inner.borrow().close_time_slice())
+    }
+
     #[wasm_bindgen(getter = timeframe)]
     #[inline]
     pub fn wasm_timeframe(&self) -> WasmTimeframe {
@@ -249,6 +404,22 @@ impl WasmOhlcv {
         self.inner.set_timeframe(timeframe.into());
     }
 
+    #[wasm_bindgen(getter = timezone)]
+    #[inline]
+    pub fn wasm_timezone(&self) -> String {
+        self.inner.timezone().name().to_string()
+    }
+
+    #[wasm_bindgen(setter = timezone)]
+    #[inline]
+    pub fn wasm_set_timezone(&mut self, timezone: String) -> Result<(), JsError> {
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        self.inner.set_timezone(timezone);
+        Ok(())
+    }
+
     #[cfg(target_arch = "wasm32")]
     #[wasm_bindgen(getter = openTime)]
     pub fn wasm_open_time(&self) -> Array {
@@ -363,8 +534,52 @@ impl WasmOhlcv {
 
     #[wasm_bindgen(js_name = "resample")]
     #[inline]
-    pub fn wasm_resample(&self, timeframe: WasmTimeframe, align_utc: bool) -> Self {
-        self.inner.resample(timeframe.into(), align_utc).into()
+    pub fn wasm_resample(
+        &self,
+        timeframe: WasmTimeframe,
+        align_utc: bool,
+        min_tick: Option<f64>,
+    ) -> Result<WasmOhlcv, JsError> {
+        let timeframe: Timeframe = timeframe.into();
+        let min_tick = min_tick.unwrap_or(f64::NAN);
+        self.inner
+            .resample_with_tick(timeframe, align_utc, min_tick)
+            .map(Into::into)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "heikinAshi")]
+    #[inline]
+    pub fn wasm_heikin_ashi(&self) -> Self {
+        self.inner.heikin_ashi().into()
+    }
+
+    #[wasm_bindgen(js_name = "qualityReport")]
+    pub fn wasm_quality_report(
+        &self,
+        spike_zscore: Option<f64>,
+        zero_volume: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        let mut config = OhlcvQualityConfig::new(self.inner.timeframe(), self.inner.timezone());
+        config.spike_zscore = spike_zscore.unwrap_or(config.spike_zscore);
+        config.zero_volume = zero_volume.unwrap_or(config.zero_volume);
+        let report = self.inner.quality_report(&config);
+        let json = serde_json::to_string(&report).map_err(|e| JsError::new(&e.to_string()))?;
+        js_sys::JSON::parse(&json).map_err(|_| JsError::new("Invalid quality report"))
+    }
+
+    #[wasm_bindgen(js_name = "repair")]
+    pub fn wasm_repair(
+        &self,
+        fill_missing: Option<bool>,
+        clip_outliers: Option<bool>,
+        spike_zscore: Option<f64>,
+    ) -> Self {
+        let mut config = OhlcvRepairConfig::new(self.inner.timeframe(), self.inner.timezone());
+        config.fill_missing = fill_missing.unwrap_or(false);
+        config.clip_outliers = clip_outliers.unwrap_or(false);
+        config.quality.spike_zscore = spike_zscore.unwrap_or(config.quality.spike_zscore);
+        self.inner.repair(&config).into()
     }
 
     #[wasm_bindgen(js_name = "sort")]
@@ -409,6 +624,12 @@ impl WasmOhlcv {
         self.inner.push_many(bars);
     }
 
+    #[wasm_bindgen(js_name = "set")]
+    #[inline]
+    pub fn wasm_set(&mut self, index: usize, bar: OhlcvBar) {
+        self.inner.set(index, bar);
+    }
+
     #[wasm_bindgen(js_name = "toString")]
     #[inline]
     pub fn wasm_to_string(&self) -> String {
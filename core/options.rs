@@ -0,0 +1,485 @@
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    ctx::Ctx,
+    ohlcv::{OhlcvBar, OhlcvReader},
+    sym::{OptionType, Sym},
+};
+
+const YEAR_MS: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OptionModel {
+    /// European options on a spot underlying with a continuous dividend yield.
+    #[default]
+    BlackScholes,
+    /// European options on futures, `underlying` is the futures price and the dividend yield is ignored.
+    Black76,
+    /// Cox-Ross-Rubinstein tree on a spot underlying, European or American exercise.
+    Binomial { steps: usize, american: bool },
+}
+
+impl OptionModel {
+    /// `black_scholes`, `black76` or `binomial`, the tree options are only used by `binomial`.
+    pub fn from_name(model: &str, steps: usize, american: bool) -> Result<Self, String> {
+        match model.to_lowercase().as_str() {
+            "black_scholes" | "bs" => Ok(OptionModel::BlackScholes),
+            "black76" | "black_76" => Ok(OptionModel::Black76),
+            "binomial" | "crr" => Ok(OptionModel::Binomial { steps, american }),
+            _ => Err(format!("Invalid option model {:?}", model)),
+        }
+    }
+}
+
+/// Inputs of a single valuation. `time` is in years, `rate`, `dividend_yield` and `volatility` are annualized and
+/// continuously compounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionParams {
+    pub option_type: OptionType,
+    pub underlying: f64,
+    pub strike: f64,
+    pub time: f64,
+    pub rate: f64,
+    pub volatility: f64,
+    pub dividend_yield: f64,
+}
+
+impl OptionParams {
+    #[inline]
+    pub fn new(
+        option_type: OptionType,
+        underlying: f64,
+        strike: f64,
+        time: f64,
+        rate: f64,
+        volatility: f64,
+    ) -> Self {
+        return Self {
+            option_type,
+            underlying,
+            strike,
+            time,
+            rate,
+            volatility,
+            dividend_yield: 0.0,
+        };
+    }
+
+    #[inline]
+    fn with_volatility(&self, volatility: f64) -> Self {
+        return Self {
+            volatility,
+            ..*self
+        };
+    }
+
+    #[inline]
+    fn intrinsic(&self, underlying: f64) -> f64 {
+        return match self.option_type {
+            OptionType::Call => (underlying - self.strike).max(0.0),
+            OptionType::Put => (self.strike - underlying).max(0.0),
+        };
+    }
+}
+
+/// Sensitivities per unit change: `vega` per 1.0 of volatility, `rho` per 1.0 of rate and `theta` per year.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Greeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub vega: f64,
+    pub theta: f64,
+    pub rho: f64,
+}
+
+#[inline]
+fn norm_cdf(x: f64) -> f64 {
+    return 0.5 * libm::erfc(-x * FRAC_1_SQRT_2);
+}
+
+#[inline]
+fn norm_pdf(x: f64) -> f64 {
+    return (-0.5 * x * x).exp() / (2.0 * PI).sqrt();
+}
+
+/// Cost of carry of the generalized Black-Scholes model.
+#[inline]
+fn carry(params: &OptionParams, model: OptionModel) -> f64 {
+    return match model {
+        OptionModel::Black76 => 0.0,
+        _ => params.rate - params.dividend_yield,
+    };
+}
+
+fn black_scholes(params: &OptionParams, carry: f64) -> f64 {
+    let OptionParams {
+        underlying: s,
+        strike: k,
+        time: t,
+        rate: r,
+        volatility: v,
+        ..
+    } = *params;
+    if t <= 0.0 {
+        return params.intrinsic(s);
+    }
+    let forward = s * ((carry - r) * t).exp();
+    let discount = (-r * t).exp();
+    if v <= 0.0 {
+        return match params.option_type {
+            OptionType::Call => (forward - k * discount).max(0.0),
+            OptionType::Put => (k * discount - forward).max(0.0),
+        };
+    }
+    let d1 = ((s / k).ln() + (carry + v * v / 2.0) * t) / (v * t.sqrt());
+    let d2 = d1 - v * t.sqrt();
+    return match params.option_type {
+        OptionType::Call => forward * norm_cdf(d1) - k * discount * norm_cdf(d2),
+        OptionType::Put => k * discount * norm_cdf(-d2) - forward * norm_cdf(-d1),
+    };
+}
+
+fn binomial(params: &OptionParams, carry: f64, steps: usize, american: bool) -> f64 {
+    let OptionParams {
+        underlying: s,
+        time: t,
+        rate: r,
+        volatility: v,
+        ..
+    } = *params;
+    if t <= 0.0 || v <= 0.0 || steps == 0 {
+        return black_scholes(params, carry);
+    }
+    let dt = t / steps as f64;
+    let up = (v * dt.sqrt()).exp();
+    let down = 1.0 / up;
+    let p = ((carry * dt).exp() - down) / (up - down);
+    let discount = (-r * dt).exp();
+    if !(0.0..=1.0).contains(&p) {
+        // volatility too low for the step size, the underlying path is almost deterministic
+        let european = black_scholes(params, carry);
+        return if american {
+            european.max(params.intrinsic(s))
+        } else {
+            european
+        };
+    }
+    let mut values: Vec<f64> = (0..=steps)
+        .map(|i| params.intrinsic(s * up.powi(i as i32) * down.powi((steps - i) as i32)))
+        .collect();
+    for step in (0..steps).rev() {
+        for i in 0..=step {
+            let value = discount * (p * values[i + 1] + (1.0 - p) * values[i]);
+            values[i] = if american {
+                value.max(params.intrinsic(s * up.powi(i as i32) * down.powi((step - i) as i32)))
+            } else {
+                value
+            };
+        }
+    }
+    return values[0];
+}
+
+pub fn option_price(params: &OptionParams, model: OptionModel) -> f64 {
+    let carry = carry(params, model);
+    return match model {
+        OptionModel::BlackScholes | OptionModel::Black76 => black_scholes(params, carry),
+        OptionModel::Binomial { steps, american } => binomial(params, carry, steps, american),
+    };
+}
+
+/// Closed form for Black-Scholes and Black-76, central differences for the binomial model.
+pub fn option_greeks(params: &OptionParams, model: OptionModel) -> Greeks {
+    if let OptionModel::Binomial { .. } = model {
+        return numerical_greeks(params, model);
+    }
+    let OptionParams {
+        underlying: s,
+        strike: k,
+        time: t,
+        rate: r,
+        volatility: v,
+        ..
+    } = *params;
+    let b = carry(params, model);
+    if t <= 0.0 || v <= 0.0 {
+        return numerical_greeks(params, model);
+    }
+    let sqrt_t = t.sqrt();
+    let d1 = ((s / k).ln() + (b + v * v / 2.0) * t) / (v * sqrt_t);
+    let d2 = d1 - v * sqrt_t;
+    let carry_discount = ((b - r) * t).exp();
+    let discount = (-r * t).exp();
+    let gamma = carry_discount * norm_pdf(d1) / (s * v * sqrt_t);
+    let vega = s * carry_discount * norm_pdf(d1) * sqrt_t;
+    let decay = -s * carry_discount * norm_pdf(d1) * v / (2.0 * sqrt_t);
+    let (delta, theta, rho) = match params.option_type {
+        OptionType::Call => (
+            carry_discount * norm_cdf(d1),
+            decay - (b - r) * s * carry_discount * norm_cdf(d1) - r * k * discount * norm_cdf(d2),
+            t * k * discount * norm_cdf(d2),
+        ),
+        OptionType::Put => (
+            carry_discount * (norm_cdf(d1) - 1.0),
+            decay + (b - r) * s * carry_discount * norm_cdf(-d1) + r * k * discount * norm_cdf(-d2),
+            -t * k * discount * norm_cdf(-d2),
+        ),
+    };
+    // the futures price doesn't depend on the rate, only the discounting does
+    let rho = match model {
+        OptionModel::Black76 => -t * black_scholes(params, b),
+        _ => rho,
+    };
+    return Greeks {
+        delta,
+        gamma,
+        vega,
+        theta,
+        rho,
+    };
+}
+
+fn numerical_greeks(params: &OptionParams, model: OptionModel) -> Greeks {
+    let price = |params: OptionParams| option_price(&params, model);
+    let ds = params.underlying * 1e-3;
+    let dv: f64 = 1e-3;
+    let dr = 1e-4;
+    let dt = (1.0f64 / 365.0).min(params.time / 2.0);
+    let up = price(OptionParams {
+        underlying: params.underlying + ds,
+        ..*params
+    });
+    let down = price(OptionParams {
+        underlying: params.underlying - ds,
+        ..*params
+    });
+    let mid = price(*params);
+    let bump = |f: &dyn Fn(f64) -> OptionParams, h: f64| {
+        if h <= 0.0 {
+            return 0.0;
+        }
+        return (price(f(h)) - price(f(-h))) / (2.0 * h);
+    };
+    return Greeks {
+        delta: (up - down) / (2.0 * ds),
+        gamma: (up - 2.0 * mid + down) / (ds * ds),
+        vega: bump(
+            &|h| params.with_volatility((params.volatility + h).max(0.0)),
+            dv.min(params.volatility),
+        ),
+        theta: -bump(
+            &|h| OptionParams {
+                time: params.time + h,
+                ..*params
+            },
+            dt,
+        ),
+        rho: bump(
+            &|h| OptionParams {
+                rate: params.rate + h,
+                ..*params
+            },
+            dr,
+        ),
+    };
+}
+
+/// Volatility at which the model price matches `price`, `params.volatility` is ignored.
+/// `None` if the price is outside of the no-arbitrage bounds or the solver doesn't converge.
+pub fn implied_volatility(price: f64, params: &OptionParams, model: OptionModel) -> Option<f64> {
+    const MIN: f64 = 1e-6;
+    const MAX: f64 = 10.0;
+    const TOLERANCE: f64 = 1e-10;
+    if !price.is_finite() || params.time <= 0.0 {
+        return None;
+    }
+    let error = |volatility: f64| option_price(&params.with_volatility(volatility), model) - price;
+    let (mut low, mut high) = (MIN, MAX);
+    let (error_low, error_high) = (error(low), error(high));
+    if error_low > TOLERANCE || error_high < -TOLERANCE {
+        return None;
+    }
+    if error_low.abs() <= TOLERANCE {
+        return Some(low);
+    }
+    let newton = !matches!(model, OptionModel::Binomial { .. });
+    let mut volatility = 0.2;
+    for _ in 0..200 {
+        let e = error(volatility);
+        if e.abs() <= TOLERANCE * price.max(1.0) {
+            return Some(volatility);
+        }
+        if e > 0.0 {
+            high = volatility;
+        } else {
+            low = volatility;
+        }
+        let vega = if newton {
+            option_greeks(&params.with_volatility(volatility), model).vega
+        } else {
+            0.0
+        };
+        let next = volatility - e / vega;
+        // Newton step inside the bracket, bisection otherwise
+        volatility = if vega > 0.0 && next > low && next < high {
+            next
+        } else {
+            (low + high) / 2.0
+        };
+        if high - low <= TOLERANCE {
+            return Some(volatility);
+        }
+    }
+    return None;
+}
+
+/// Model and market inputs shared by all valuations of an option overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OptionPricer {
+    pub model: OptionModel,
+    pub rate: f64,
+    pub dividend_yield: f64,
+}
+
+impl OptionPricer {
+    #[inline]
+    pub fn new(model: OptionModel, rate: f64, dividend_yield: f64) -> Self {
+        return Self {
+            model,
+            rate,
+            dividend_yield,
+        };
+    }
+
+    /// From the derivative spec of `option` at `time`, `None` without strike, option type or expiry.
+    pub fn params(
+        &self,
+        option: &Sym,
+        underlying: f64,
+        time: &DateTime<Utc>,
+        volatility: f64,
+    ) -> Option<OptionParams> {
+        let derivative = option.derivative()?;
+        let expiry = derivative.expiry?;
+        let years = (expiry - *time).num_milliseconds() as f64 / YEAR_MS;
+        return Some(OptionParams {
+            option_type: derivative.option_type?,
+            underlying,
+            strike: derivative.strike?,
+            time: years.max(0.0),
+            rate: self.rate,
+            volatility,
+            dividend_yield: self.dividend_yield,
+        });
+    }
+
+    /// At the close of an underlying bar.
+    #[inline]
+    pub fn bar_params(
+        &self,
+        option: &Sym,
+        bar: &OhlcvBar,
+        volatility: f64,
+    ) -> Option<OptionParams> {
+        let time = bar.close_time().or(bar.open_time())?;
+        return self.params(option, bar.close(), time, volatility);
+    }
+
+    /// At the current bar of `ctx`, which iterates the underlying.
+    #[inline]
+    pub fn ctx_params(&self, ctx: &Ctx, option: &Sym, volatility: f64) -> Option<OptionParams> {
+        return self.bar_params(option, &ctx.bar(), volatility);
+    }
+
+    #[inline]
+    pub fn price(&self, params: &OptionParams) -> f64 {
+        return option_price(params, self.model);
+    }
+
+    #[inline]
+    pub fn greeks(&self, params: &OptionParams) -> Greeks {
+        return option_greeks(params, self.model);
+    }
+
+    #[inline]
+    pub fn implied_volatility(&self, price: f64, params: &OptionParams) -> Option<f64> {
+        return implied_volatility(price, params, self.model);
+    }
+
+    /// `volatility` has one value per bar or a single value for all bars. NaN where the option can't be valued.
+    pub fn price_series(
+        &self,
+        underlying: &dyn OhlcvReader,
+        option: &Sym,
+        volatility: &[f64],
+    ) -> Vec<f64> {
+        return self.series(
+            underlying,
+            option,
+            volatility,
+            |params| self.price(params),
+            f64::NAN,
+        );
+    }
+
+    pub fn greeks_series(
+        &self,
+        underlying: &dyn OhlcvReader,
+        option: &Sym,
+        volatility: &[f64],
+    ) -> Vec<Greeks> {
+        let nan = Greeks {
+            delta: f64::NAN,
+            gamma: f64::NAN,
+            vega: f64::NAN,
+            theta: f64::NAN,
+            rho: f64::NAN,
+        };
+        return self.series(
+            underlying,
+            option,
+            volatility,
+            |params| self.greeks(params),
+            nan,
+        );
+    }
+
+    /// `prices` are option prices aligned with the underlying bars.
+    pub fn implied_volatility_series(
+        &self,
+        underlying: &dyn OhlcvReader,
+        option: &Sym,
+        prices: &[f64],
+    ) -> Vec<f64> {
+        return (0..underlying.len())
+            .map(|i| {
+                let bar = underlying.get(i)?;
+                let params = self.bar_params(option, &bar, f64::NAN)?;
+                self.implied_volatility(*prices.get(i)?, &params)
+            })
+            .map(|x| x.unwrap_or(f64::NAN))
+            .collect();
+    }
+
+    fn series<T: Copy>(
+        &self,
+        underlying: &dyn OhlcvReader,
+        option: &Sym,
+        volatility: &[f64],
+        f: impl Fn(&OptionParams) -> T,
+        default: T,
+    ) -> Vec<T> {
+        return (0..underlying.len())
+            .map(|i| {
+                let bar = underlying.get(i)?;
+                let volatility = *volatility.get(i).or(volatility.first())?;
+                self.bar_params(option, &bar, volatility)
+                    .map(|params| f(&params))
+            })
+            .map(|x| x.unwrap_or(default))
+            .collect();
+    }
+}
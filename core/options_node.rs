@@ -0,0 +1,211 @@
+use crate::{
+    ohlcv::ArcOhlcv,
+    ohlcv_node::NodeOhlcv,
+    options::{Greeks, OptionModel, OptionParams, OptionPricer},
+    sym::OptionType,
+    sym_node::NodeSym,
+};
+use napi::{Error, Result};
+use napi_derive::napi;
+
+#[napi(object, js_name = "OptionGreeks")]
+#[derive(Debug, Clone, Copy)]
+pub struct NodeOptionGreeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub vega: f64,
+    pub theta: f64,
+    pub rho: f64,
+}
+
+impl From<Greeks> for NodeOptionGreeks {
+    #[inline]
+    fn from(greeks: Greeks) -> Self {
+        NodeOptionGreeks {
+            delta: greeks.delta,
+            gamma: greeks.gamma,
+            vega: greeks.vega,
+            theta: greeks.theta,
+            rho: greeks.rho,
+        }
+    }
+}
+
+#[inline]
+fn model(model: Option<String>, steps: Option<u32>, american: Option<bool>) -> Result<OptionModel> {
+    OptionModel::from_name(
+        model.as_deref().unwrap_or("black_scholes"),
+        steps.unwrap_or(200) as usize,
+        american.unwrap_or(false),
+    )
+    .map_err(Error::from_reason)
+}
+
+#[inline]
+fn params(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: Option<f64>,
+) -> Result<OptionParams> {
+    let option_type = OptionType::try_from(option_type).map_err(Error::from_reason)?;
+    Ok(OptionParams {
+        dividend_yield: dividend_yield.unwrap_or(0.0),
+        ..OptionParams::new(option_type, underlying, strike, time, rate, volatility)
+    })
+}
+
+#[napi(js_name = "optionPrice")]
+#[inline]
+pub fn node_option_price(
+    option_type: String,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<u32>,
+    american: Option<bool>,
+) -> Result<f64> {
+    let params = params(
+        &option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        volatility,
+        dividend_yield,
+    )?;
+    Ok(crate::options::option_price(
+        &params,
+        self::model(model, steps, american)?,
+    ))
+}
+
+#[napi(js_name = "optionGreeks")]
+#[inline]
+pub fn node_option_greeks(
+    option_type: String,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<u32>,
+    american: Option<bool>,
+) -> Result<NodeOptionGreeks> {
+    let params = params(
+        &option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        volatility,
+        dividend_yield,
+    )?;
+    Ok(crate::options::option_greeks(&params, self::model(model, steps, american)?).into())
+}
+
+#[napi(js_name = "impliedVolatility")]
+#[inline]
+pub fn node_implied_volatility(
+    price: f64,
+    option_type: String,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<u32>,
+    american: Option<bool>,
+) -> Result<Option<f64>> {
+    let params = params(
+        &option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        f64::NAN,
+        dividend_yield,
+    )?;
+    Ok(crate::options::implied_volatility(
+        price,
+        &params,
+        self::model(model, steps, american)?,
+    ))
+}
+
+#[napi(js_name = "optionPriceSeries")]
+#[inline]
+pub fn node_option_price_series(
+    ohlcv: &NodeOhlcv,
+    sym: &NodeSym,
+    volatility: Vec<f64>,
+    rate: Option<f64>,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<u32>,
+    american: Option<bool>,
+) -> Result<Vec<f64>> {
+    let pricer = OptionPricer::new(
+        self::model(model, steps, american)?,
+        rate.unwrap_or(0.0),
+        dividend_yield.unwrap_or(0.0),
+    );
+    let ohlcv: ArcOhlcv = ohlcv.into();
+    Ok(pricer.price_series(&ohlcv, &sym.clone().into(), &volatility))
+}
+
+#[napi(js_name = "optionGreeksSeries")]
+#[inline]
+pub fn node_option_greeks_series(
+    ohlcv: &NodeOhlcv,
+    sym: &NodeSym,
+    volatility: Vec<f64>,
+    rate: Option<f64>,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<u32>,
+    american: Option<bool>,
+) -> Result<Vec<NodeOptionGreeks>> {
+    let pricer = OptionPricer::new(
+        self::model(model, steps, american)?,
+        rate.unwrap_or(0.0),
+        dividend_yield.unwrap_or(0.0),
+    );
+    let ohlcv: ArcOhlcv = ohlcv.into();
+    Ok(pricer
+        .greeks_series(&ohlcv, &sym.clone().into(), &volatility)
+        .into_iter()
+        .map(|x| x.into())
+        .collect())
+}
+
+#[napi(js_name = "impliedVolatilitySeries")]
+#[inline]
+pub fn node_implied_volatility_series(
+    ohlcv: &NodeOhlcv,
+    sym: &NodeSym,
+    prices: Vec<f64>,
+    rate: Option<f64>,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<u32>,
+    american: Option<bool>,
+) -> Result<Vec<f64>> {
+    let pricer = OptionPricer::new(
+        self::model(model, steps, american)?,
+        rate.unwrap_or(0.0),
+        dividend_yield.unwrap_or(0.0),
+    );
+    let ohlcv: ArcOhlcv = ohlcv.into();
+    Ok(pricer.implied_volatility_series(&ohlcv, &sym.clone().into(), &prices))
+}
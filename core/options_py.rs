@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::{
+    ohlcv::ArcOhlcv,
+    ohlcv_py::PyOhlcv,
+    options::{Greeks, OptionModel, OptionParams, OptionPricer},
+    sym::OptionType,
+    sym_py::PySym,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use pyo3_stub_gen::derive::gen_stub_pyfunction;
+
+#[inline]
+fn model(model: &str, steps: usize, american: bool) -> PyResult<OptionModel> {
+    return OptionModel::from_name(model, steps, american).map_err(PyValueError::new_err);
+}
+
+#[inline]
+fn params(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: f64,
+) -> PyResult<OptionParams> {
+    let option_type = OptionType::try_from(option_type).map_err(PyValueError::new_err)?;
+    return Ok(OptionParams {
+        dividend_yield,
+        ..OptionParams::new(option_type, underlying, strike, time, rate, volatility)
+    });
+}
+
+#[inline]
+fn greeks_dict(greeks: &Greeks) -> HashMap<String, f64> {
+    return HashMap::from([
+        ("delta".to_string(), greeks.delta),
+        ("gamma".to_string(), greeks.gamma),
+        ("vega".to_string(), greeks.vega),
+        ("theta".to_string(), greeks.theta),
+        ("rho".to_string(), greeks.rho),
+    ]);
+}
+
+#[gen_stub_pyfunction]
+#[pyfunction(name = "option_price")]
+#[pyo3(signature = (option_type, underlying, strike, time, rate, volatility, dividend_yield=0.0, model="black_scholes", steps=200, american=false))]
+#[inline]
+pub fn py_option_price(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: f64,
+    model: &str,
+    steps: usize,
+    american: bool,
+) -> PyResult<f64> {
+    let params = params(
+        option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        volatility,
+        dividend_yield,
+    )?;
+    return Ok(crate::options::option_price(
+        &params,
+        self::model(model, steps, american)?,
+    ));
+}
+
+#[gen_stub_pyfunction]
+#[pyfunction(name = "option_greeks")]
+#[pyo3(signature = (option_type, underlying, strike, time, rate, volatility, dividend_yield=0.0, model="black_scholes", steps=200, american=false))]
+#[inline]
+pub fn py_option_greeks(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: f64,
+    model: &str,
+    steps: usize,
+    american: bool,
+) -> PyResult<HashMap<String, f64>> {
+    let params = params(
+        option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        volatility,
+        dividend_yield,
+    )?;
+    let greeks = crate::options::option_greeks(&params, self::model(model, steps, american)?);
+    return Ok(greeks_dict(&greeks));
+}
+
+#[gen_stub_pyfunction]
+#[pyfunction(name = "implied_volatility")]
+#[pyo3(signature = (price, option_type, underlying, strike, time, rate, dividend_yield=0.0, model="black_scholes", steps=200, american=false))]
+#[inline]
+pub fn py_implied_volatility(
+    price: f64,
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    dividend_yield: f64,
+    model: &str,
+    steps: usize,
+    american: bool,
+) -> PyResult<Option<f64>> {
+    let params = params(
+        option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        f64::NAN,
+        dividend_yield,
+    )?;
+    return Ok(crate::options::implied_volatility(
+        price,
+        &params,
+        self::model(model, steps, american)?,
+    ));
+}
+
+#[gen_stub_pyfunction]
+#[pyfunction(name = "option_price_series")]
+#[pyo3(signature = (ohlcv, sym, volatility, rate=0.0, dividend_yield=0.0, model="black_scholes", steps=200, american=false))]
+#[inline]
+pub fn py_option_price_series(
+    ohlcv: PyOhlcv,
+    sym: PySym,
+    volatility: Vec<f64>,
+    rate: f64,
+    dividend_yield: f64,
+    model: &str,
+    steps: usize,
+    american: bool,
+) -> PyResult<Vec<f64>> {
+    let pricer = OptionPricer::new(self::model(model, steps, american)?, rate, dividend_yield);
+    let ohlcv: ArcOhlcv = ohlcv.into();
+    return Ok(pricer.price_series(&ohlcv, &sym.into(), &volatility));
+}
+
+#[gen_stub_pyfunction]
+#[pyfunction(name = "option_greeks_series")]
+#[pyo3(signature = (ohlcv, sym, volatility, rate=0.0, dividend_yield=0.0, model="black_scholes", steps=200, american=false))]
+#[inline]
+pub fn py_option_greeks_series(
+    ohlcv: PyOhlcv,
+    sym: PySym,
+    volatility: Vec<f64>,
+    rate: f64,
+    dividend_yield: f64,
+    model: &str,
+    steps: usize,
+    american: bool,
+) -> PyResult<HashMap<String, Vec<f64>>> {
+    let pricer = OptionPricer::new(self::model(model, steps, american)?, rate, dividend_yield);
+    let ohlcv: ArcOhlcv = ohlcv.into();
+    let greeks = pricer.greeks_series(&ohlcv, &sym.into(), &volatility);
+    let column = |f: fn(&Greeks) -> f64| greeks.iter().map(f).collect::<Vec<f64>>();
+    return Ok(HashMap::from([
+        ("delta".to_string(), column(|x| x.delta)),
+        ("gamma".to_string(), column(|x| x.gamma)),
+        ("vega".to_string(), column(|x| x.vega)),
+        ("theta".to_string(), column(|x| x.theta)),
+        ("rho".to_string(), column(|x| x.rho)),
+    ]));
+}
+
+#[gen_stub_pyfunction]
+#[pyfunction(name = "implied_volatility_series")]
+#[pyo3(signature = (ohlcv, sym, prices, rate=0.0, dividend_yield=0.0, model="black_scholes", steps=200, american=false))]
+#[inline]
+pub fn py_implied_volatility_series(
+    ohlcv: PyOhlcv,
+    sym: PySym,
+    prices: Vec<f64>,
+    rate: f64,
+    dividend_yield: f64,
+    model: &str,
+    steps: usize,
+    american: bool,
+) -> PyResult<Vec<f64>> {
+    let pricer = OptionPricer::new(self::model(model, steps, american)?, rate, dividend_yield);
+    let ohlcv: ArcOhlcv = ohlcv.into();
+    return Ok(pricer.implied_volatility_series(&ohlcv, &sym.into(), &prices));
+}
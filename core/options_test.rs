@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        options::{
+            implied_volatility, option_greeks, option_price, OptionModel, OptionParams,
+            OptionPricer,
+        },
+        sym::{OptionType, Sym, SymDerivative},
+    };
+
+    fn params(option_type: OptionType) -> OptionParams {
+        return OptionParams::new(option_type, 100.0, 100.0, 1.0, 0.05, 0.2);
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    pub fn prices_and_greeks() {
+        let call = params(OptionType::Call);
+        let put = params(OptionType::Put);
+        assert_close(
+            option_price(&call, OptionModel::BlackScholes),
+            10.4506,
+            1e-4,
+        );
+        assert_close(option_price(&put, OptionModel::BlackScholes), 5.5735, 1e-4);
+        // put-call parity
+        let parity = option_price(&call, OptionModel::BlackScholes)
+            - option_price(&put, OptionModel::BlackScholes);
+        assert_close(parity, 100.0 - 100.0 * (-0.05f64).exp(), 1e-9);
+
+        // Black-76 is Black-Scholes with the dividend yield equal to the rate
+        let on_future = OptionParams {
+            dividend_yield: 0.05,
+            ..call
+        };
+        assert_close(
+            option_price(&call, OptionModel::Black76),
+            option_price(&on_future, OptionModel::BlackScholes),
+            1e-12,
+        );
+
+        let european = OptionModel::Binomial {
+            steps: 500,
+            american: false,
+        };
+        let american = OptionModel::Binomial {
+            steps: 500,
+            american: true,
+        };
+        assert_close(option_price(&put, european), 5.5735, 1e-2);
+        assert!(option_price(&put, american) > option_price(&put, european) + 0.1);
+        assert_close(
+            option_price(&call, american),
+            option_price(&call, european),
+            1e-9,
+        );
+
+        let greeks = option_greeks(&call, OptionModel::BlackScholes);
+        assert_close(greeks.delta, 0.6368, 1e-4);
+        assert_close(greeks.gamma, 0.018762, 1e-6);
+        assert_close(greeks.vega, 37.524, 1e-3);
+        assert_close(greeks.theta, -6.414, 1e-3);
+        assert_close(greeks.rho, 53.232, 1e-3);
+        let numerical = option_greeks(&call, european);
+        assert_close(numerical.delta, greeks.delta, 1e-2);
+        assert_close(numerical.vega, greeks.vega, 0.5);
+
+        let expired = OptionParams { time: 0.0, ..put };
+        assert_eq!(option_price(&expired, OptionModel::BlackScholes), 0.0);
+    }
+
+    #[test]
+    pub fn implied_volatility_roundtrip() {
+        for model in [
+            OptionModel::BlackScholes,
+            OptionModel::Black76,
+            OptionModel::Binomial {
+                steps: 100,
+                american: true,
+            },
+        ] {
+            for (option_type, volatility) in [(OptionType::Call, 0.35), (OptionType::Put, 0.08)] {
+                let params = OptionParams {
+                    volatility,
+                    ..params(option_type)
+                };
+                let price = option_price(&params, model);
+                assert_close(
+                    implied_volatility(price, &params, model).unwrap(),
+                    volatility,
+                    1e-6,
+                );
+            }
+        }
+        let call = params(OptionType::Call);
+        assert_eq!(
+            implied_volatility(101.0, &call, OptionModel::BlackScholes),
+            None
+        );
+        assert_eq!(
+            implied_volatility(1.0, &call, OptionModel::BlackScholes),
+            None
+        );
+    }
+
+    #[test]
+    pub fn ctx_and_series() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut option = Sym::default();
+        option.set_derivative(Some(SymDerivative::option(
+            start + Duration::days(366),
+            None,
+            100.0,
+            OptionType::Call,
+            1.0,
+        )));
+        let ohlcv = Ohlcv::from_bars(
+            (0..3)
+                .map(|i| {
+                    let close = 100.0 + i as f64;
+                    OhlcvBar::new(
+                        Some(start + Duration::days(i)),
+                        Some(start + Duration::days(i + 1)),
+                        close,
+                        close,
+                        close,
+                        close,
+                        1.0,
+                    )
+                })
+                .collect(),
+        );
+        let pricer = OptionPricer::new(OptionModel::BlackScholes, 0.05, 0.0);
+
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.clone().into_box());
+        ctx.next();
+        let params = pricer.ctx_params(&ctx, &option, 0.2).unwrap();
+        assert_eq!(params.time, 1.0);
+        assert_close(pricer.price(&params), 10.4506, 1e-4);
+        assert!(pricer.ctx_params(&ctx, &Sym::default(), 0.2).is_none());
+
+        let prices = pricer.price_series(&ohlcv, &option, &[0.2]);
+        assert_eq!(prices.len(), 3);
+        assert_close(prices[0], 10.4506, 1e-4);
+        assert!(prices[2] > prices[1]);
+        let greeks = pricer.greeks_series(&ohlcv, &option, &[0.2, 0.3, 0.4]);
+        assert_close(greeks[0].delta, 0.6368, 1e-4);
+        let volatility = pricer.implied_volatility_series(&ohlcv, &option, &prices);
+        for x in volatility {
+            assert_close(x, 0.2, 1e-6);
+        }
+        assert!(pricer
+            .price_series(&ohlcv, &option, &[])
+            .iter()
+            .all(|x| x.is_nan()));
+    }
+}
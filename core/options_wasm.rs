@@ -0,0 +1,215 @@
+use crate::{
+    ohlcv::RcOhlcv,
+    ohlcv_wasm::WasmOhlcv,
+    options::{Greeks, OptionModel, OptionParams, OptionPricer},
+    sym::OptionType,
+    sym_wasm::WasmSym,
+};
+use js_sys::{Float64Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+#[inline]
+fn model(
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<OptionModel, JsError> {
+    OptionModel::from_name(
+        model.as_deref().unwrap_or("black_scholes"),
+        steps.unwrap_or(200),
+        american.unwrap_or(false),
+    )
+    .map_err(|e| JsError::new(&e))
+}
+
+#[inline]
+fn params(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: Option<f64>,
+) -> Result<OptionParams, JsError> {
+    let option_type = OptionType::try_from(option_type).map_err(|e| JsError::new(&e))?;
+    Ok(OptionParams {
+        dividend_yield: dividend_yield.unwrap_or(0.0),
+        ..OptionParams::new(option_type, underlying, strike, time, rate, volatility)
+    })
+}
+
+/// `{ delta, gamma, vega, theta, rho }` with one value or one array per field.
+fn greeks_object(fields: [(&str, JsValue); 5]) -> JsValue {
+    let obj = Object::new();
+    for (key, value) in fields {
+        let _ = Reflect::set(&obj, &JsValue::from_str(key), &value);
+    }
+    obj.into()
+}
+
+#[wasm_bindgen(js_name = "optionPrice")]
+#[inline]
+pub fn wasm_option_price(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<f64, JsError> {
+    let params = params(
+        option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        volatility,
+        dividend_yield,
+    )?;
+    Ok(crate::options::option_price(
+        &params,
+        self::model(model, steps, american)?,
+    ))
+}
+
+#[wasm_bindgen(js_name = "optionGreeks")]
+#[inline]
+pub fn wasm_option_greeks(
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    volatility: f64,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<JsValue, JsError> {
+    let params = params(
+        option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        volatility,
+        dividend_yield,
+    )?;
+    let greeks = crate::options::option_greeks(&params, self::model(model, steps, american)?);
+    Ok(greeks_object([
+        ("delta", greeks.delta.into()),
+        ("gamma", greeks.gamma.into()),
+        ("vega", greeks.vega.into()),
+        ("theta", greeks.theta.into()),
+        ("rho", greeks.rho.into()),
+    ]))
+}
+
+#[wasm_bindgen(js_name = "impliedVolatility")]
+#[inline]
+pub fn wasm_implied_volatility(
+    price: f64,
+    option_type: &str,
+    underlying: f64,
+    strike: f64,
+    time: f64,
+    rate: f64,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<Option<f64>, JsError> {
+    let params = params(
+        option_type,
+        underlying,
+        strike,
+        time,
+        rate,
+        f64::NAN,
+        dividend_yield,
+    )?;
+    Ok(crate::options::implied_volatility(
+        price,
+        &params,
+        self::model(model, steps, american)?,
+    ))
+}
+
+#[wasm_bindgen(js_name = "optionPriceSeries")]
+#[inline]
+pub fn wasm_option_price_series(
+    ohlcv: &WasmOhlcv,
+    sym: &WasmSym,
+    volatility: Vec<f64>,
+    rate: Option<f64>,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<Vec<f64>, JsError> {
+    let pricer = OptionPricer::new(
+        self::model(model, steps, american)?,
+        rate.unwrap_or(0.0),
+        dividend_yield.unwrap_or(0.0),
+    );
+    let ohlcv: RcOhlcv = ohlcv.clone().into();
+    Ok(pricer.price_series(&ohlcv, &sym.clone().into(), &volatility))
+}
+
+#[wasm_bindgen(js_name = "optionGreeksSeries")]
+#[inline]
+pub fn wasm_option_greeks_series(
+    ohlcv: &WasmOhlcv,
+    sym: &WasmSym,
+    volatility: Vec<f64>,
+    rate: Option<f64>,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<JsValue, JsError> {
+    let pricer = OptionPricer::new(
+        self::model(model, steps, american)?,
+        rate.unwrap_or(0.0),
+        dividend_yield.unwrap_or(0.0),
+    );
+    let ohlcv: RcOhlcv = ohlcv.clone().into();
+    let greeks = pricer.greeks_series(&ohlcv, &sym.clone().into(), &volatility);
+    let column = |f: fn(&Greeks) -> f64| -> JsValue {
+        let values: Vec<f64> = greeks.iter().map(f).collect();
+        Float64Array::from(&values[..]).into()
+    };
+    Ok(greeks_object([
+        ("delta", column(|x| x.delta)),
+        ("gamma", column(|x| x.gamma)),
+        ("vega", column(|x| x.vega)),
+        ("theta", column(|x| x.theta)),
+        ("rho", column(|x| x.rho)),
+    ]))
+}
+
+#[wasm_bindgen(js_name = "impliedVolatilitySeries")]
+#[inline]
+pub fn wasm_implied_volatility_series(
+    ohlcv: &WasmOhlcv,
+    sym: &WasmSym,
+    prices: Vec<f64>,
+    rate: Option<f64>,
+    dividend_yield: Option<f64>,
+    model: Option<String>,
+    steps: Option<usize>,
+    american: Option<bool>,
+) -> Result<Vec<f64>, JsError> {
+    let pricer = OptionPricer::new(
+        self::model(model, steps, american)?,
+        rate.unwrap_or(0.0),
+        dividend_yield.unwrap_or(0.0),
+    );
+    let ohlcv: RcOhlcv = ohlcv.clone().into();
+    Ok(pricer.implied_volatility_series(&ohlcv, &sym.clone().into(), &prices))
+}
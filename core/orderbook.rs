@@ -25,6 +25,17 @@ pub fn round_contracts(size: f64, min_qty: f64, qty_scale: f64) -> f64 {
     return ((size * qty_scale) + f64::EPSILON).round() / qty_scale;
 }
 
+/// Order quantity rounded towards zero to `min_qty`, like TradingView does for order sizes.
+/// Float noise below `1e-9` of a `min_qty` step is still rounded up, so `0.0064409999` stays `0.006441`.
+#[inline]
+pub fn truncate_contracts(size: f64, min_qty: f64, qty_scale: f64) -> f64 {
+    if min_qty.is_nan() {
+        return size;
+    }
+    let steps = size * qty_scale;
+    return (steps + steps.signum() * 1e-9).trunc() / qty_scale;
+}
+
 #[inline]
 pub fn validate_contracts(size: f64, min_qty: f64) -> bool {
     return min_qty.is_nan() || !size.is_nan() && size.abs() >= min_qty;
@@ -66,6 +77,7 @@ pub fn order_size_for_equity_pct(
 pub struct OrderConfig {
     size: f64,
     tag: Option<String>,
+    comment: Option<String>,
 }
 
 impl Default for OrderConfig {
@@ -73,28 +85,56 @@ impl Default for OrderConfig {
         Self {
             size: f64::NAN,
             tag: None,
+            comment: None,
         }
     }
 }
 
 impl OrderConfig {
     pub fn new(size: f64, tag: Option<String>) -> Self {
-        Self { size, tag }
+        Self {
+            size,
+            tag,
+            comment: None,
+        }
+    }
+
+    pub fn size(&self) -> f64 {
+        return self.size;
     }
 
     pub fn set_tag(&mut self, tag: Option<String>) {
         self.tag = tag;
     }
+
+    /// Copied to the trade events filled by the order.
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct Order {
     id: usize,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     size: f64,
     tag: Option<String>,
+    #[cfg_attr(feature = "json", serde(default))]
+    comment: Option<String>,
 }
 
 impl Order {
+    #[inline]
+    pub fn new(id: usize, size: f64, tag: Option<String>) -> Self {
+        Self {
+            id,
+            size,
+            tag,
+            comment: None,
+        }
+    }
+
     #[inline]
     pub fn id(&self) -> usize {
         return self.id;
@@ -110,6 +150,11 @@ impl Order {
         return &self.tag;
     }
 
+    #[inline]
+    pub fn comment(&self) -> &Option<String> {
+        return &self.comment;
+    }
+
     #[inline]
     pub fn direction(&self) -> TradeDirection {
         return TradeDirection::from(self.size);
@@ -117,8 +162,11 @@ impl Order {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderBookConfig {
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     pub min_qty: f64,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     pub qty_scale: f64,
     pub debug: bool,
 }
@@ -133,11 +181,14 @@ impl Default for OrderBookConfig {
     }
 }
 
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderBook {
     config: OrderBookConfig,
     orders: HashMap<usize, Order>,
     queue: VecDeque<usize>,
     id_counter: usize,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     price: f64,
 }
 
@@ -185,8 +236,9 @@ impl OrderBook {
         let id = self.create_id();
         let order = Order {
             id,
-            size: round_contracts(order_opts.size, self.config.min_qty, self.config.qty_scale),
+            size: truncate_contracts(order_opts.size, self.config.min_qty, self.config.qty_scale),
             tag: order_opts.tag,
+            comment: order_opts.comment,
         };
         self.orders.insert(id, order);
         self.queue.push_back(id);
@@ -202,4 +254,21 @@ impl OrderBook {
     pub fn get_order(&self, id: usize) -> Option<&Order> {
         return self.orders.get(&id);
     }
+
+    /// Removes a queued order. Returns `None` if the order does not exist or was already popped.
+    #[inline]
+    pub fn cancel(&mut self, id: usize) -> Option<Order> {
+        let index = self.queue.iter().position(|queued_id| *queued_id == id)?;
+        self.queue.remove(index);
+        return self.orders.remove(&id);
+    }
+
+    #[inline]
+    pub fn queued_orders(&self) -> Vec<&Order> {
+        return self
+            .queue
+            .iter()
+            .filter_map(|id| self.orders.get(id))
+            .collect();
+    }
 }
@@ -0,0 +1,503 @@
+use thiserror::Error;
+
+use crate::{
+    backtest::Backtest,
+    ctx::Ctx,
+    plot::{LineStyle, Pane, Position},
+    sym::SymFees,
+    trade::{Trade, TradeEvent},
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PineError {
+    #[error("Series {name:?} has {len} values for {bars} bars")]
+    SeriesLength {
+        name: String,
+        len: usize,
+        bars: usize,
+    },
+    #[error("Bar index {bar_index} is out of range for {bars} bars")]
+    BarIndex { bar_index: usize, bars: usize },
+    #[error("Bar {0} has no open time")]
+    MissingOpenTime(usize),
+}
+
+/// Commission of the exported strategy, see `strategy(commission_type=...)`. TradingView supports only one kind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(tag = "kind", content = "value", rename_all = "snake_case")
+)]
+pub enum PineCommission {
+    None,
+    /// Percent of the order value, `0.1` is 0.1%.
+    Percent(f64),
+    CashPerContract(f64),
+    CashPerOrder(f64),
+}
+
+impl PineCommission {
+    /// Taker fee as percent if set, otherwise the per contract fee.
+    pub fn from_fees(fees: &SymFees) -> Self {
+        if fees.taker > 0.0 {
+            return PineCommission::Percent(fees.taker * 100.0);
+        }
+        if fees.per_qty > 0.0 {
+            return PineCommission::CashPerContract(fees.per_qty);
+        }
+        return PineCommission::None;
+    }
+
+    fn to_pine(self) -> Option<String> {
+        let (kind, value) = match self {
+            PineCommission::None => return None,
+            PineCommission::Percent(value) => ("percent", value),
+            PineCommission::CashPerContract(value) => ("cash_per_contract", value),
+            PineCommission::CashPerOrder(value) => ("cash_per_order", value),
+        };
+        return Some(format!(
+            "commission_type=strategy.commission.{kind}, commission_value={value}"
+        ));
+    }
+}
+
+/// How trades are replayed on the chart.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum PineOrders {
+    /// `strategy.order` in both directions, the legacy export.
+    Order,
+    /// `strategy.entry` and `strategy.close` with one id per trade.
+    #[default]
+    EntryClose,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(default))]
+pub struct PineExportConfig {
+    pub title: String,
+    /// `None` derives it from the fees of the ctx sym.
+    pub commission: Option<PineCommission>,
+    /// In ticks.
+    pub slippage: u32,
+    /// `None` uses the max number of trades open at the same time.
+    pub pyramiding: Option<usize>,
+    pub orders: PineOrders,
+    /// Signal comments on the entry and exit orders.
+    pub comments: bool,
+    /// Trades per `array.from` call, long argument lists fail to compile.
+    pub chunk_size: usize,
+    /// Table on the last bar comparing net profit and closed trades of qpace and TradingView.
+    pub verification_table: bool,
+}
+
+impl Default for PineExportConfig {
+    fn default() -> Self {
+        Self {
+            title: "Strategy export".to_string(),
+            commission: None,
+            slippage: 0,
+            pyramiding: None,
+            orders: PineOrders::EntryClose,
+            comments: true,
+            chunk_size: 500,
+            verification_table: true,
+        }
+    }
+}
+
+/// Quoted Pine string literal, `na` for `None`.
+pub fn pine_string(value: Option<&str>) -> String {
+    return match value {
+        Some(value) => format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        ),
+        None => "na".to_string(),
+    };
+}
+
+/// Open time of the bar in Unix milliseconds, comparable with the bar `time` in any chart time zone.
+pub fn pine_time(ctx: &Ctx, bar_index: usize) -> Result<String, PineError> {
+    let bars = ctx.ohlcv().len();
+    let bar = ctx
+        .ohlcv()
+        .get(bar_index)
+        .ok_or(PineError::BarIndex { bar_index, bars })?;
+    let time = bar
+        .open_time()
+        .ok_or(PineError::MissingOpenTime(bar_index))?;
+    return Ok(time.timestamp_millis().to_string());
+}
+
+/// Max number of trades open at once. Exits are counted before entries on the same bar, like the orders are filled.
+fn max_open_trades(trades: &[&Trade]) -> usize {
+    let mut events: Vec<(usize, i32)> = vec![];
+    for trade in trades {
+        if let Some(entry) = trade.entry() {
+            events.push((entry.order_bar_index(), 1));
+        }
+        if let Some(exit) = trade.exit() {
+            events.push((exit.order_bar_index(), -1));
+        }
+    }
+    events.sort();
+    let mut open = 0;
+    let mut max = 0;
+    for (_, delta) in events {
+        open += delta;
+        max = max.max(open);
+    }
+    return max as usize;
+}
+
+/// Fails if a bar with an order has no open time.
+pub fn backtest_to_pine(bt: &Backtest, config: &PineExportConfig) -> Result<String, PineError> {
+    let ctx = bt.ctx();
+    let ctx = ctx.borrow();
+    let trades = bt.trades();
+    let commission = config
+        .commission
+        .unwrap_or_else(|| PineCommission::from_fees(ctx.sym().fees()));
+    let pyramiding = config
+        .pyramiding
+        .unwrap_or_else(|| max_open_trades(&trades).max(1));
+
+    let mut settings = vec![
+        pine_string(Some(&config.title)),
+        "overlay=true".to_string(),
+        format!("initial_capital={}", bt.initial_capital()),
+        "default_qty_type=strategy.percent_of_equity".to_string(),
+        "default_qty_value=100".to_string(),
+        format!("pyramiding={pyramiding}"),
+        format!("slippage={}", config.slippage),
+        format!(
+            "process_orders_on_close={}",
+            bt.config().process_orders_on_close()
+        ),
+    ];
+    settings.extend(commission.to_pine());
+
+    let mut pine = "".to_string();
+    pine += "//@version=5";
+    pine += &format!("\nstrategy({})", settings.join(", "));
+    pine += &format!("\n// Generated at {:?}", chrono::offset::Utc::now());
+    pine += &format!("\n// Time zone: {}", ctx.timezone().name());
+    pine += "\n// by https://qpace.dev - The technical analysis framework\n";
+    pine += "
+type Trade
+    string id
+    int entry_open_time_ms
+    int exit_open_time_ms
+    float size
+    string entry_comment
+    string exit_comment
+";
+
+    let comment = |event: Option<&TradeEvent>| {
+        let comment = event.and_then(|x| x.comment()).filter(|_| config.comments);
+        pine_string(comment.map(|x| x.as_str()))
+    };
+    let pine_trades: Vec<String> = trades
+        .iter()
+        .enumerate()
+        .map(|(i, trade)| {
+            let id = match trade.entry().and_then(|x| x.id()) {
+                Some(id) => format!("{id} #{i}"),
+                None => format!("#{i}"),
+            };
+            let time = |event: Option<&TradeEvent>| match event {
+                Some(event) => pine_time(&ctx, event.order_bar_index()),
+                None => Ok("na".to_string()),
+            };
+            Ok(format!(
+                "Trade.new(id={}, entry_open_time_ms={}, exit_open_time_ms={}, size={}, entry_comment={}, exit_comment={})",
+                pine_string(Some(&id)),
+                time(trade.entry())?,
+                time(trade.exit())?,
+                trade.size(),
+                comment(trade.entry()),
+                comment(trade.exit()),
+            ))
+        })
+        .collect::<Result<_, PineError>>()?;
+
+    pine += "\nvar trades = array.new<Trade>()";
+    pine += "\nif barstate.isfirst";
+    if pine_trades.is_empty() {
+        pine += "\n    array.clear(trades)";
+    }
+    for chunk in pine_trades.chunks(config.chunk_size.max(1)) {
+        pine += &format!(
+            "\n    trades := array.concat(trades, array.from<Trade>({}))",
+            chunk.join(", ")
+        );
+    }
+    pine += "\n";
+
+    match config.orders {
+        PineOrders::Order => {
+            pine += "
+for item in trades
+    qty = math.abs(item.size)
+
+    if item.entry_open_time_ms == time
+        _dir = item.size > 0 ? strategy.long : strategy.short
+        strategy.order(id=item.id, direction=_dir, qty=qty, comment=item.entry_comment)
+
+    if item.exit_open_time_ms == time
+        _dir = item.size > 0 ? strategy.short : strategy.long
+        strategy.order(id=item.id, direction=_dir, qty=qty, comment=item.exit_comment)
+";
+        }
+        PineOrders::EntryClose => {
+            // exits first, so a reversal on the same bar closes the old trades before the new entry
+            pine += "
+for item in trades
+    if item.exit_open_time_ms == time
+        strategy.close(item.id, qty=math.abs(item.size), comment=item.exit_comment)
+
+for item in trades
+    if item.entry_open_time_ms == time
+        _dir = item.size > 0 ? strategy.long : strategy.short
+        strategy.entry(item.id, _dir, qty=math.abs(item.size), comment=item.entry_comment)
+";
+        }
+    }
+
+    if config.verification_table {
+        let net_profit = pine_float(bt.net_profit());
+        let closed_trades = bt.closed_trades().len();
+        pine += &format!(
+            "
+var verification = table.new(position.top_right, 3, 4, bgcolor=color.new(color.gray, 80), border_width=1)
+if barstate.islast
+    table.cell(verification, 0, 0, \"\")
+    table.cell(verification, 1, 0, \"qpace\")
+    table.cell(verification, 2, 0, \"TradingView\")
+    table.cell(verification, 0, 1, \"Net profit\")
+    table.cell(verification, 1, 1, str.tostring({net_profit}, \"#.##\"))
+    table.cell(verification, 2, 1, str.tostring(strategy.netprofit, \"#.##\"))
+    table.cell(verification, 0, 2, \"Closed trades\")
+    table.cell(verification, 1, 2, str.tostring({closed_trades}))
+    table.cell(verification, 2, 2, str.tostring(strategy.closedtrades))
+    table.cell(verification, 0, 3, \"Difference\")
+    table.cell(verification, 2, 3, str.tostring(strategy.netprofit - {net_profit}, \"#.##\"))
+"
+        );
+    }
+
+    return Ok(pine);
+}
+
+/// Named indicator output, one value per ctx bar, NaN for no value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PineSeries {
+    pub name: String,
+    pub values: Vec<f64>,
+    pub color: Option<String>,
+    pub line_width: usize,
+}
+
+impl PineSeries {
+    #[inline]
+    pub fn new(name: impl Into<String>, values: Vec<f64>) -> Self {
+        return Self {
+            name: name.into(),
+            values,
+            color: None,
+            line_width: 1,
+        };
+    }
+
+    #[inline]
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        return self;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PineIndicatorConfig {
+    pub title: String,
+    pub overlay: bool,
+    /// Values per `array.from` call, long argument lists fail to compile.
+    pub chunk_size: usize,
+}
+
+impl Default for PineIndicatorConfig {
+    fn default() -> Self {
+        Self {
+            title: "Indicator export".to_string(),
+            overlay: true,
+            chunk_size: 500,
+        }
+    }
+}
+
+/// `#rrggbb` hex colors as literals, anything else as a built-in color name, e.g. `red` to `color.red`.
+pub fn pine_color(color: &str) -> String {
+    if color.starts_with('#') {
+        return color.to_string();
+    }
+    return format!("color.{}", color.trim_start_matches("color."));
+}
+
+/// Float literal, typed `na` for NaN so `array.from` of missing values still compiles.
+fn pine_float(value: f64) -> String {
+    if !value.is_finite() {
+        return "float(na)".to_string();
+    }
+    let value = value.to_string();
+    if value.contains('.') {
+        return value;
+    }
+    return format!("{value}.0");
+}
+
+fn pine_array(name: &str, kind: &str, values: &[String], chunk_size: usize) -> String {
+    let mut pine = format!("\nvar {name} = array.new<{kind}>()");
+    pine += "\nif barstate.isfirst";
+    if values.is_empty() {
+        pine += &format!("\n    array.clear({name})");
+    }
+    for chunk in values.chunks(chunk_size.max(1)) {
+        pine += &format!(
+            "\n    {name} := array.concat({name}, array.from({}))",
+            chunk.join(", ")
+        );
+    }
+    return pine;
+}
+
+/// Pine `indicator()` with a `plot` per series and the boxes and labels of the pane.
+/// Bars are matched by open time, so the script works on charts with more history than the ctx.
+pub fn pane_to_pine(
+    ctx: &Ctx,
+    pane: &Pane,
+    series: &[PineSeries],
+    config: &PineIndicatorConfig,
+) -> Result<String, PineError> {
+    let bars = ctx.len();
+    let times: Vec<i64> = ctx
+        .ohlcv()
+        .open_time()
+        .iter()
+        .enumerate()
+        .map(|(i, time)| {
+            time.map(|x| x.timestamp_millis())
+                .ok_or(PineError::MissingOpenTime(i))
+        })
+        .collect::<Result<_, _>>()?;
+    let time = |bar_index: usize| {
+        times
+            .get(bar_index)
+            .map(|x| x.to_string())
+            .ok_or(PineError::BarIndex { bar_index, bars })
+    };
+    for item in series {
+        if item.values.len() != bars {
+            return Err(PineError::SeriesLength {
+                name: item.name.clone(),
+                len: item.values.len(),
+                bars,
+            });
+        }
+    }
+
+    let mut pine = "".to_string();
+    pine += "//@version=5";
+    pine += &format!(
+        "\nindicator({}, overlay={}, max_boxes_count=500, max_labels_count=500)",
+        pine_string(Some(&config.title)),
+        config.overlay
+    );
+    pine += &format!("\n// Generated at {:?}", chrono::offset::Utc::now());
+    pine += &format!("\n// Time zone: {}", ctx.timezone().name());
+    pine += "\n// by https://qpace.dev - The technical analysis framework\n";
+
+    if !series.is_empty() {
+        let times: Vec<String> = times.iter().map(|x| x.to_string()).collect();
+        pine += &pine_array("open_times", "int", &times, config.chunk_size);
+        for (i, item) in series.iter().enumerate() {
+            let values: Vec<String> = item.values.iter().map(|x| pine_float(*x)).collect();
+            pine += &pine_array(&format!("series_{i}"), "float", &values, config.chunk_size);
+        }
+        pine += "
+
+var int cursor = 0
+while cursor < array.size(open_times) and array.get(open_times, cursor) < time
+    cursor += 1
+matched = cursor < array.size(open_times) and array.get(open_times, cursor) == time
+";
+        for (i, item) in series.iter().enumerate() {
+            let mut args = vec![
+                format!("matched ? array.get(series_{i}, cursor) : na"),
+                format!("title={}", pine_string(Some(&item.name))),
+                format!("linewidth={}", item.line_width),
+            ];
+            if let Some(color) = &item.color {
+                args.push(format!("color={}", pine_color(color)));
+            }
+            pine += &format!("\nplot({})", args.join(", "));
+        }
+        pine += "\n";
+    }
+
+    if !pane.boxes.is_empty() || !pane.labels.is_empty() {
+        pine += "\nif barstate.isfirst";
+        for item in &pane.boxes {
+            let style = match item.line_style {
+                LineStyle::Solid => "line.style_solid",
+                LineStyle::Dashed => "line.style_dashed",
+                LineStyle::Dotted => "line.style_dotted",
+            };
+            let mut args = vec![
+                format!("left={}", time(item.start_bar_index)?),
+                format!("top={}", pine_float(item.start_value.max(item.end_value))),
+                format!("right={}", time(item.end_bar_index)?),
+                format!(
+                    "bottom={}",
+                    pine_float(item.start_value.min(item.end_value))
+                ),
+                "xloc=xloc.bar_time".to_string(),
+                format!("border_style={style}"),
+                format!("border_width={}", item.line_width),
+            ];
+            if let Some(color) = &item.line_color {
+                args.push(format!("border_color={}", pine_color(color)));
+            }
+            if let Some(color) = &item.fill_color {
+                args.push(format!("bgcolor={}", pine_color(color)));
+            }
+            pine += &format!("\n    box.new({})", args.join(", "));
+        }
+        for item in &pane.labels {
+            let (yloc, style) = match item.position {
+                Position::TopCenter => ("yloc.abovebar", "label.style_label_down"),
+                Position::BottomCenter => ("yloc.belowbar", "label.style_label_up"),
+            };
+            let mut args = vec![
+                format!("x={}", time(item.bar_index)?),
+                "y=na".to_string(),
+                format!("text={}", pine_string(Some(&item.text))),
+                "xloc=xloc.bar_time".to_string(),
+                format!("yloc={yloc}"),
+                format!("style={style}"),
+            ];
+            if let Some(color) = &item.color {
+                args.push(format!("color={}", pine_color(color)));
+            }
+            pine += &format!("\n    label.new({})", args.join(", "));
+        }
+        pine += "\n";
+    }
+
+    return Ok(pine);
+}
@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        pine::{
+            pine_time, PineCommission, PineError, PineExportConfig, PineIndicatorConfig,
+            PineOrders, PineSeries,
+        },
+        plot::{Box, Label, LineStyle, Pane, Position},
+        signal::Signal,
+        sym::{Sym, SymFees},
+        timeframe::Timeframe,
+    };
+
+    fn signal(size: f64, id: &str, comment: &str) -> Option<Signal> {
+        let mut signal = Signal::size(size);
+        signal
+            .set_id(Some(id.to_string()))
+            .set_comment(Some(comment.to_string()));
+        return Some(signal);
+    }
+
+    fn backtest() -> Backtest {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut ohlcv = Ohlcv::from_bars(
+            (0..10)
+                .map(|i| {
+                    let close = 100.0 + i as f64;
+                    OhlcvBar::new(
+                        Some(start + Duration::days(i)),
+                        Some(start + Duration::days(i + 1)),
+                        close,
+                        close,
+                        close,
+                        close,
+                        1.0,
+                    )
+                })
+                .collect(),
+        );
+        ohlcv.set_timeframe(Timeframe::Days(1));
+        let mut sym = Sym::default();
+        sym.set_min_tick(0.01);
+        sym.set_min_qty(1.0);
+        sym.set_fees(SymFees {
+            taker: 0.001,
+            ..SymFees::default()
+        });
+        let mut ctx = Ctx::new();
+        ctx.set_sym(sym);
+        ctx.set_ohlcv(ohlcv.into_box());
+        let mut bt = Backtest::new(Rc::new(RefCell::new(ctx)), BacktestConfig::default());
+        let mut signals = vec![None; 10];
+        signals[1] = signal(1.0, "long", "first \"leg\"");
+        signals[2] = signal(1.0, "long", "second leg");
+        signals[5] = signal(-4.0, "short", "reverse");
+        signals[7] = Some(Signal::close_all());
+        bt.signal_list(signals);
+        return bt;
+    }
+
+    #[test]
+    pub fn strategy_export() {
+        let bt = backtest();
+        assert_eq!(bt.closed_trades().len(), 3);
+        let exit = bt.closed_trades()[0].exit().unwrap();
+        assert_eq!(exit.comment().map(|x| x.as_str()), Some("reverse"));
+
+        let pine = bt.to_pine().unwrap();
+        assert!(pine.contains(
+            "strategy(\"Strategy export\", overlay=true, initial_capital=1000, default_qty_type=strategy.percent_of_equity, default_qty_value=100, pyramiding=2, slippage=0, process_orders_on_close=false, commission_type=strategy.commission.percent, commission_value=0.1)"
+        ));
+        assert!(pine.contains("id=\"long #0\", entry_open_time_ms=1704153600000"));
+        assert!(pine.contains("entry_comment=\"first \\\"leg\\\"\""));
+        assert!(pine.contains("exit_comment=\"reverse\""));
+        assert!(pine.contains("strategy.entry(item.id, _dir"));
+        assert!(pine.contains("strategy.close(item.id"));
+        assert!(!pine.contains("strategy.order("));
+        assert_eq!(pine.matches("array.concat(").count(), 1);
+        assert!(pine.contains("str.tostring(strategy.netprofit - "));
+
+        let pine = bt
+            .to_pine_with(&PineExportConfig {
+                title: "Legacy".to_string(),
+                commission: Some(PineCommission::CashPerOrder(2.5)),
+                slippage: 3,
+                pyramiding: Some(5),
+                orders: PineOrders::Order,
+                comments: false,
+                chunk_size: 2,
+                verification_table: false,
+            })
+            .unwrap();
+        assert!(pine.contains("pyramiding=5, slippage=3"));
+        assert!(pine
+            .contains("commission_type=strategy.commission.cash_per_order, commission_value=2.5"));
+        assert!(pine.contains("strategy.order(id=item.id"));
+        assert!(!pine.contains("reverse"));
+        assert_eq!(pine.matches("array.concat(").count(), 2);
+        assert!(!pine.contains("table.new"));
+    }
+
+    #[test]
+    pub fn time_errors() {
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(
+            Ohlcv::from_bars(vec![OhlcvBar::new(None, None, 1.0, 1.0, 1.0, 1.0, 1.0)]).into_box(),
+        );
+        assert_eq!(
+            pine_time(&ctx, 0).unwrap_err(),
+            PineError::MissingOpenTime(0)
+        );
+        assert_eq!(
+            pine_time(&ctx, 3).unwrap_err(),
+            PineError::BarIndex {
+                bar_index: 3,
+                bars: 1
+            }
+        );
+    }
+
+    #[test]
+    pub fn indicator_export() {
+        let bt = backtest();
+        let ctx = bt.ctx();
+        let ctx = ctx.borrow();
+        let mut pane = Pane::new();
+        pane.add_box(Box {
+            start_bar_index: 1,
+            start_value: 105.0,
+            end_bar_index: 3,
+            end_value: 101.5,
+            fill_color: Some("#ff000033".to_string()),
+            line_style: LineStyle::Dashed,
+            ..Box::default()
+        });
+        pane.add_label(Label {
+            bar_index: 2,
+            text: "breakout".to_string(),
+            color: Some("green".to_string()),
+            position: Position::TopCenter,
+        });
+        let mut values = vec![f64::NAN; 10];
+        values[9] = 2.0;
+        let series = [
+            PineSeries::new("sma", (0..10).map(|i| 100.5 + i as f64).collect()).with_color("blue"),
+            PineSeries::new("signal", values),
+        ];
+        let config = PineIndicatorConfig {
+            chunk_size: 4,
+            ..PineIndicatorConfig::default()
+        };
+
+        let pine = pane.to_pine(&ctx, &series, &config).unwrap();
+        assert!(pine.contains(
+            "indicator(\"Indicator export\", overlay=true, max_boxes_count=500, max_labels_count=500)"
+        ));
+        assert!(pine.contains(
+            "open_times := array.concat(open_times, array.from(1704067200000, 1704153600000, 1704240000000, 1704326400000))"
+        ));
+        assert_eq!(pine.matches("series_0 := array.concat(").count(), 3);
+        assert!(pine.contains("array.from(float(na), 2.0)"));
+        assert!(pine.contains(
+            "plot(matched ? array.get(series_0, cursor) : na, title=\"sma\", linewidth=1, color=color.blue)"
+        ));
+        assert!(pine.contains(
+            "box.new(left=1704153600000, top=105.0, right=1704326400000, bottom=101.5, xloc=xloc.bar_time, border_style=line.style_dashed, border_width=1, bgcolor=#ff000033)"
+        ));
+        assert!(pine.contains(
+            "label.new(x=1704240000000, y=na, text=\"breakout\", xloc=xloc.bar_time, yloc=yloc.abovebar, style=label.style_label_down, color=color.green)"
+        ));
+
+        let short = [PineSeries::new("short", vec![1.0])];
+        assert_eq!(
+            pane.to_pine(&ctx, &short, &config).unwrap_err(),
+            PineError::SeriesLength {
+                name: "short".to_string(),
+                len: 1,
+                bars: 10
+            }
+        );
+        pane.add_label(Label {
+            bar_index: 10,
+            ..Label::default()
+        });
+        assert_eq!(
+            pane.to_pine(&ctx, &[], &config).unwrap_err(),
+            PineError::BarIndex {
+                bar_index: 10,
+                bars: 10
+            }
+        );
+    }
+}
@@ -1,3 +1,8 @@
+use crate::{
+    ctx::Ctx,
+    pine::{pane_to_pine, PineError, PineIndicatorConfig, PineSeries},
+};
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum LineStyle {
     Solid,
@@ -51,6 +56,16 @@ impl Pane {
     pub fn add_label(&mut self, label: Label) {
         self.labels.push(label);
     }
+
+    /// Pine `indicator()` script, see `pine::pane_to_pine`.
+    pub fn to_pine(
+        &self,
+        ctx: &Ctx,
+        series: &[PineSeries],
+        config: &PineIndicatorConfig,
+    ) -> Result<String, PineError> {
+        return pane_to_pine(ctx, self, series, config);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,8 +1,15 @@
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
-use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyfunction, gen_stub_pymethods};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::plot::{Label, LineStyle, Position};
+use crate::{
+    ctx::Ctx,
+    ctx_py::PyCtx,
+    pine::{PineIndicatorConfig, PineSeries},
+    plot::{Label, LineStyle, Pane, Position},
+};
 
 #[gen_stub_pyclass]
 #[pyclass(name = "LineStyle")]
@@ -342,3 +349,48 @@ impl PyBox {
         self.inner.line_width = value;
     }
 }
+
+/// `series` maps plot titles to one value per ctx bar, `colors` maps plot titles to colors.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "pane_to_pine")]
+#[pyo3(signature = (ctx, series=None, boxes=None, labels=None, colors=None, title="Indicator export".to_string(), overlay=true))]
+pub fn py_pane_to_pine(
+    ctx: PyCtx,
+    series: Option<&Bound<'_, PyDict>>,
+    boxes: Option<Vec<PyBox>>,
+    labels: Option<Vec<PyLabel>>,
+    colors: Option<HashMap<String, String>>,
+    title: String,
+    overlay: bool,
+) -> PyResult<String> {
+    let mut pane = Pane::new();
+    pane.boxes = boxes
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| x.into())
+        .collect();
+    pane.labels = labels
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| x.into())
+        .collect();
+    let colors = colors.unwrap_or_default();
+    let mut plots: Vec<PineSeries> = vec![];
+    if let Some(series) = series {
+        for (name, values) in series.iter() {
+            let name: String = name.extract()?;
+            let mut plot = PineSeries::new(name.clone(), values.extract()?);
+            plot.color = colors.get(&name).cloned();
+            plots.push(plot);
+        }
+    }
+    let config = PineIndicatorConfig {
+        title,
+        overlay,
+        ..PineIndicatorConfig::default()
+    };
+    let ctx: Rc<RefCell<Ctx>> = ctx.into();
+    return pane
+        .to_pine(&ctx.borrow(), &plots, &config)
+        .map_err(|e| PyValueError::new_err(e.to_string()));
+}
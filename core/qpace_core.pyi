@@ -3,6 +3,8 @@
 
 import builtins
 import datetime
+import numpy
+import numpy.typing
 import pandas
 import typing
 from enum import Enum, auto
@@ -13,8 +15,10 @@ class Backtest:
     ctx: Ctx
     equity: builtins.float
     equity_list: builtins.list[builtins.float]
+    equity_array: numpy.typing.NDArray[numpy.float64]
     net_equity: builtins.float
     net_equity_list: builtins.list[builtins.float]
+    net_equity_array: numpy.typing.NDArray[numpy.float64]
     pnl_list: builtins.list[builtins.float]
     open_profit: builtins.float
     net_profit: builtins.float
@@ -30,6 +34,7 @@ class Backtest:
     avg_losing_trade: builtins.float
     avg_win_loss_ratio: builtins.float
     returns_list: builtins.list[builtins.float]
+    returns_array: numpy.typing.NDArray[numpy.float64]
     position_size: builtins.float
     open_trades: builtins.list[Trade]
     closed_trades: builtins.list[Trade]
@@ -51,6 +56,23 @@ class Backtest:
     def on_bar_close(self) -> None:
         ...
 
+    def on_bar_update(self) -> None:
+        ...
+
+    def snapshot_json(self) -> builtins.str:
+        ...
+
+    def snapshot_bytes(self) -> builtins.bytes:
+        ...
+
+    @staticmethod
+    def restore_json(ctx:Ctx, json:builtins.str) -> Backtest:
+        ...
+
+    @staticmethod
+    def restore_bytes(ctx:Ctx, data:builtins.bytes) -> Backtest:
+        ...
+
     def signal(self, signal:Signal) -> None:
         ...
 
@@ -72,7 +94,7 @@ class Backtest:
     def __iter__(self) -> Backtest:
         ...
 
-    def to_pine(self) -> builtins.str:
+    def to_pine(self, config:typing.Optional[builtins.dict[builtins.str, typing.Any]]=None) -> builtins.str:
         ...
 
     def display(self) -> None:
@@ -118,6 +140,10 @@ class Ctx:
     bar_index: builtins.int
     bar: OhlcvBar
     is_initialized: builtins.bool
+    is_last_bar: builtins.bool
+    is_session_open: builtins.bool
+    is_first_bar_of_session: builtins.bool
+    is_last_bar_of_session: builtins.bool
     sym: Sym
     ohlcv: Ohlcv
     annualization_factor: builtins.float
@@ -207,6 +233,7 @@ class LineStyle:
 
 class Ohlcv:
     timeframe: Timeframe
+    timezone: builtins.str
     open_time: builtins.list[typing.Optional[datetime.datetime]]
     close_time: builtins.list[typing.Optional[datetime.datetime]]
     open: builtins.list[builtins.float]
@@ -214,12 +241,27 @@ class Ohlcv:
     low: builtins.list[builtins.float]
     close: builtins.list[builtins.float]
     volume: builtins.list[builtins.float]
+    open_time_array: numpy.typing.NDArray[numpy.datetime64]
+    close_time_array: numpy.typing.NDArray[numpy.datetime64]
+    open_array: numpy.typing.NDArray[numpy.float64]
+    high_array: numpy.typing.NDArray[numpy.float64]
+    low_array: numpy.typing.NDArray[numpy.float64]
+    close_array: numpy.typing.NDArray[numpy.float64]
+    volume_array: numpy.typing.NDArray[numpy.float64]
     bars: builtins.list[OhlcvBar]
     def __new__(cls,): ...
     @staticmethod
     def from_bars(bars:typing.Sequence[OhlcvBar]) -> Ohlcv:
         ...
 
+    @staticmethod
+    def align(series:typing.Sequence[Ohlcv], join:builtins.str='inner', fill:builtins.str='ffill') -> builtins.list[Ohlcv]:
+        ...
+
+    @staticmethod
+    def align_to_pandas(series:typing.Sequence[Ohlcv], names:typing.Sequence[builtins.str], join:builtins.str='inner', fill:builtins.str='ffill') -> pandas.DataFrame:
+        ...
+
     def py_set_timeframe(self, timeframe:Timeframe) -> None:
         ...
 
@@ -244,7 +286,16 @@ class Ohlcv:
     def extend(self, other:Ohlcv) -> None:
         ...
 
-    def resample(self, timeframe:Timeframe, align_utc:builtins.bool) -> Ohlcv:
+    def resample(self, timeframe:Timeframe, align_utc:builtins.bool, min_tick:typing.Optional[builtins.float]=None) -> Ohlcv:
+        ...
+
+    def heikin_ashi(self) -> Ohlcv:
+        ...
+
+    def quality_report(self, spike_zscore:builtins.float=10.0, zero_volume:builtins.bool=True) -> typing.Any:
+        ...
+
+    def repair(self, fill_missing:builtins.bool=False, clip_outliers:builtins.bool=False, spike_zscore:builtins.float=10.0) -> Ohlcv:
         ...
 
     def sort(self, ascending:builtins.bool) -> None:
@@ -268,8 +319,22 @@ class Ohlcv:
     def push_many(self, bars:typing.Sequence[OhlcvBar]) -> None:
         ...
 
+    def set(self, index:builtins.int, bar:OhlcvBar) -> None:
+        ...
+
+    def __arrow_c_stream__(self, requested_schema:typing.Optional[typing.Any]=None) -> typing.Any:
+        ...
+
     @staticmethod
-    def from_pandas(df:typing.Any, timeframe:typing.Optional[Timeframe]=None) -> Ohlcv:
+    def from_arrow(data:typing.Any, timeframe:typing.Optional[Timeframe]=None, columns:typing.Optional[typing.Mapping[builtins.str, typing.Optional[builtins.str]]]=None, time_unit:builtins.str='auto') -> Ohlcv:
+        ...
+
+    @staticmethod
+    def from_pandas(df:typing.Any, timeframe:typing.Optional[Timeframe]=None, columns:typing.Optional[typing.Mapping[builtins.str, typing.Optional[builtins.str]]]=None, time_unit:builtins.str='auto') -> Ohlcv:
+        ...
+
+    @staticmethod
+    def from_polars(df:typing.Any, timeframe:typing.Optional[Timeframe]=None, columns:typing.Optional[typing.Mapping[builtins.str, typing.Optional[builtins.str]]]=None, time_unit:builtins.str='auto') -> Ohlcv:
         ...
 
     def to_pandas(self) -> pandas.DataFrame:
@@ -421,6 +486,14 @@ class Sym:
     price_scale: builtins.float
     point_value: builtins.float
     metadata: typing.Optional[builtins.str]
+    timezone: typing.Optional[builtins.str]
+    expiry: typing.Optional[datetime.datetime]
+    underlying: typing.Optional[builtins.str]
+    strike: typing.Optional[builtins.float]
+    option_type: typing.Optional[builtins.str]
+    multiplier: builtins.float
+    settlement: builtins.str
+    settlement_price: typing.Optional[builtins.float]
     qty_scale: builtins.float
     def __new__(cls,): ...
     def __str__(self) -> builtins.str:
@@ -543,6 +616,9 @@ class Timeframe:
     seconds: typing.Optional[builtins.int]
     ticks: typing.Optional[builtins.int]
     ranges: typing.Optional[builtins.int]
+    volume: typing.Optional[builtins.int]
+    dollars: typing.Optional[builtins.int]
+    renko: typing.Optional[builtins.int]
     unknown: builtins.bool
     duration: datetime.timedelta
     def __str__(self) -> builtins.str:
@@ -591,6 +667,18 @@ class Timeframe:
     def Ranges(value:builtins.int) -> Timeframe:
         ...
 
+    @staticmethod
+    def Volume(value:builtins.int) -> Timeframe:
+        ...
+
+    @staticmethod
+    def Dollars(value:builtins.int) -> Timeframe:
+        ...
+
+    @staticmethod
+    def Renko(value:builtins.int) -> Timeframe:
+        ...
+
     @staticmethod
     def Unknown() -> Timeframe:
         ...
@@ -605,6 +693,7 @@ class Trade:
     entry: typing.Optional[TradeEvent]
     exit: typing.Optional[TradeEvent]
     pnl: builtins.float
+    commission: builtins.float
     direction: TradeDirection
     is_active: builtins.bool
     is_closed: builtins.bool
@@ -659,6 +748,12 @@ def gross_loss_pct(gross_loss:builtins.float, initial_capital:builtins.float) ->
 def gross_profit_pct(gross_profit:builtins.float, initial_capital:builtins.float) -> builtins.float:
     ...
 
+def implied_volatility(price:builtins.float, option_type:builtins.str, underlying:builtins.float, strike:builtins.float, time:builtins.float, rate:builtins.float, dividend_yield:builtins.float=0.0, model:builtins.str='black_scholes', steps:builtins.int=200, american:builtins.bool=False) -> typing.Optional[builtins.float]:
+    ...
+
+def implied_volatility_series(ohlcv:Ohlcv, sym:Sym, prices:typing.Sequence[builtins.float], rate:builtins.float=0.0, dividend_yield:builtins.float=0.0, model:builtins.str='black_scholes', steps:builtins.int=200, american:builtins.bool=False) -> builtins.list[builtins.float]:
+    ...
+
 def long_net_profit_pct(long_net_profit:builtins.float, initial_capital:builtins.float) -> builtins.float:
     ...
 
@@ -677,12 +772,27 @@ def omega_ratio(positive_returns_sum:builtins.float, negative_returns_sum:builti
 def omega_ratio_from_returns(returns:typing.Sequence[builtins.float], risk_free_rate:builtins.float) -> builtins.float:
     ...
 
+def option_greeks(option_type:builtins.str, underlying:builtins.float, strike:builtins.float, time:builtins.float, rate:builtins.float, volatility:builtins.float, dividend_yield:builtins.float=0.0, model:builtins.str='black_scholes', steps:builtins.int=200, american:builtins.bool=False) -> builtins.dict[builtins.str, builtins.float]:
+    ...
+
+def option_greeks_series(ohlcv:Ohlcv, sym:Sym, volatility:typing.Sequence[builtins.float], rate:builtins.float=0.0, dividend_yield:builtins.float=0.0, model:builtins.str='black_scholes', steps:builtins.int=200, american:builtins.bool=False) -> builtins.dict[builtins.str, builtins.list[builtins.float]]:
+    ...
+
+def option_price(option_type:builtins.str, underlying:builtins.float, strike:builtins.float, time:builtins.float, rate:builtins.float, volatility:builtins.float, dividend_yield:builtins.float=0.0, model:builtins.str='black_scholes', steps:builtins.int=200, american:builtins.bool=False) -> builtins.float:
+    ...
+
+def option_price_series(ohlcv:Ohlcv, sym:Sym, volatility:typing.Sequence[builtins.float], rate:builtins.float=0.0, dividend_yield:builtins.float=0.0, model:builtins.str='black_scholes', steps:builtins.int=200, american:builtins.bool=False) -> builtins.list[builtins.float]:
+    ...
+
 def order_size(equity_pct:builtins.float, equity:builtins.float, exchange_rate:builtins.float, instrument_price:builtins.float, point_value:builtins.float) -> builtins.float:
     ...
 
 def order_size_for_equity_pct(equity_pct:builtins.float, equity:builtins.float, current_position:builtins.float, instrument_price:builtins.float, point_value:builtins.float, exchange_rate:builtins.float) -> builtins.float:
     ...
 
+def pane_to_pine(ctx:Ctx, series:typing.Optional[typing.Mapping[builtins.str, typing.Sequence[builtins.float]]]=None, boxes:typing.Optional[typing.Sequence[Box]]=None, labels:typing.Optional[typing.Sequence[Label]]=None, colors:typing.Optional[typing.Mapping[builtins.str, builtins.str]]=None, title:builtins.str='Indicator export', overlay:builtins.bool=True) -> builtins.str:
+    ...
+
 def pnl(qty:builtins.float, entry_price:builtins.float, current_price:builtins.float) -> builtins.float:
     ...
 
@@ -0,0 +1,507 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::{
+    ohlcv::{next_timeframe_boundary, OhlcvBar},
+    session::Session,
+    timeframe::Timeframe,
+};
+
+/// Data-quality problem of a single bar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum OhlcvIssueKind {
+    /// `missing` bars of the timeframe are absent right before this bar, at most `MAX_GAP_BARS`.
+    Gap {
+        missing: usize,
+    },
+    /// Same open time as the previous bar.
+    Duplicate,
+    /// Opens before the previous bar.
+    OutOfOrder,
+    MissingTime,
+    /// `high < low`, or open / close outside of the high-low range.
+    InvalidRange,
+    /// Any of the prices or the volume is NaN.
+    NaN,
+    ZeroVolume,
+    /// Close to close log return that is `zscore` robust standard deviations away from the median.
+    Spike {
+        zscore: f64,
+    },
+}
+
+impl OhlcvIssueKind {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            OhlcvIssueKind::Gap { .. } => "gap",
+            OhlcvIssueKind::Duplicate => "duplicate",
+            OhlcvIssueKind::OutOfOrder => "out_of_order",
+            OhlcvIssueKind::MissingTime => "missing_time",
+            OhlcvIssueKind::InvalidRange => "invalid_range",
+            OhlcvIssueKind::NaN => "nan",
+            OhlcvIssueKind::ZeroVolume => "zero_volume",
+            OhlcvIssueKind::Spike { .. } => "spike",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct OhlcvIssue {
+    pub bar_index: usize,
+    pub open_time: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "json", serde(flatten))]
+    pub kind: OhlcvIssueKind,
+}
+
+impl fmt::Display for OhlcvIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bar[{} | {:?}]: ", self.bar_index, self.open_time)?;
+        match self.kind {
+            OhlcvIssueKind::Gap { missing } => write!(f, "{} missing bars before", missing),
+            OhlcvIssueKind::Spike { zscore } => write!(f, "spike, z-score {:.2}", zscore),
+            kind => write!(f, "{}", kind.name().replace('_', " ")),
+        }
+    }
+}
+
+/// What `check` looks for.
+#[derive(Debug, Clone)]
+pub struct OhlcvQualityConfig {
+    /// Timeframe the bars should follow. Gaps are only detected for time based timeframes.
+    pub timeframe: Timeframe,
+    /// Time zone of day, week and month boundaries.
+    pub timezone: Tz,
+    /// Expected times outside of the session are not gaps, e.g. weekends of a stock. Extended hours count as open.
+    pub session: Option<Session>,
+    /// Robust z-score above which a return is a spike. `NaN` disables spike detection.
+    pub spike_zscore: f64,
+    pub zero_volume: bool,
+}
+
+impl Default for OhlcvQualityConfig {
+    fn default() -> Self {
+        Self {
+            timeframe: Timeframe::Unknown(),
+            timezone: Tz::UTC,
+            session: None,
+            spike_zscore: 10.0,
+            zero_volume: true,
+        }
+    }
+}
+
+impl OhlcvQualityConfig {
+    #[inline]
+    pub fn new(timeframe: Timeframe, timezone: Tz) -> Self {
+        Self {
+            timeframe,
+            timezone,
+            ..Default::default()
+        }
+    }
+}
+
+/// All issues found by `check`, in bar order.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct OhlcvQualityReport {
+    pub bars: usize,
+    pub issues: Vec<OhlcvIssue>,
+}
+
+impl OhlcvQualityReport {
+    /// Issues listed by `Display`, the rest is only counted.
+    pub const MAX_LISTED: usize = 25;
+
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues of the same kind as `kind`, ignoring its data.
+    #[inline]
+    pub fn count(&self, kind: OhlcvIssueKind) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.kind.name() == kind.name())
+            .count()
+    }
+
+    /// Total number of missing bars over all gaps.
+    #[inline]
+    pub fn missing_bars(&self) -> usize {
+        self.issues
+            .iter()
+            .map(|issue| match issue.kind {
+                OhlcvIssueKind::Gap { missing } => missing,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for OhlcvQualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "{} bars, no issues", self.bars);
+        }
+        let mut counts: Vec<(&'static str, usize)> = vec![];
+        for issue in &self.issues {
+            match counts
+                .iter_mut()
+                .find(|(name, _)| *name == issue.kind.name())
+            {
+                Some((_, count)) => *count += 1,
+                None => counts.push((issue.kind.name(), 1)),
+            }
+        }
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(name, count)| format!("{} {}", count, name))
+            .collect();
+        write!(
+            f,
+            "{} bars, {} issues ({})",
+            self.bars,
+            self.issues.len(),
+            counts.join(", ")
+        )?;
+        for issue in self.issues.iter().take(Self::MAX_LISTED) {
+            write!(f, "\n{}", issue)?;
+        }
+        if self.issues.len() > Self::MAX_LISTED {
+            write!(f, "\n... {} more", self.issues.len() - Self::MAX_LISTED)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds every issue of `bars`, unlike `OhlcvReader::sanity_check` which stops at the first one.
+pub fn check(bars: &[OhlcvBar], config: &OhlcvQualityConfig) -> OhlcvQualityReport {
+    let mut issues = vec![];
+    let mut push = |bar_index: usize, kind: OhlcvIssueKind| {
+        issues.push(OhlcvIssue {
+            bar_index,
+            open_time: bars[bar_index].open_time().copied(),
+            kind,
+        });
+    };
+    let spikes = if config.spike_zscore.is_nan() {
+        vec![]
+    } else {
+        spikes(bars, config.spike_zscore)
+    };
+    let mut spikes = spikes.into_iter().peekable();
+    let mut prev_time: Option<DateTime<Utc>> = None;
+    for (bar_index, bar) in bars.iter().enumerate() {
+        match (bar.open_time().copied(), prev_time) {
+            (None, _) => push(bar_index, OhlcvIssueKind::MissingTime),
+            (Some(time), Some(prev)) if time == prev => push(bar_index, OhlcvIssueKind::Duplicate),
+            (Some(time), Some(prev)) if time < prev => push(bar_index, OhlcvIssueKind::OutOfOrder),
+            (Some(time), Some(prev)) => {
+                let missing = missing_count(prev, time, config);
+                if missing > 0 {
+                    push(bar_index, OhlcvIssueKind::Gap { missing });
+                }
+            }
+            _ => {}
+        }
+        if let Some(time) = bar.open_time() {
+            prev_time = Some(prev_time.map_or(*time, |prev| prev.max(*time)));
+        }
+        let prices = [bar.open(), bar.high(), bar.low(), bar.close()];
+        if prices.iter().any(|x| x.is_nan()) || bar.volume().is_nan() {
+            push(bar_index, OhlcvIssueKind::NaN);
+        } else if bar.high() < bar.low() || prices.iter().any(|x| *x > bar.high() || *x < bar.low())
+        {
+            push(bar_index, OhlcvIssueKind::InvalidRange);
+        }
+        if config.zero_volume && bar.volume() == 0.0 {
+            push(bar_index, OhlcvIssueKind::ZeroVolume);
+        }
+        if let Some((_, zscore)) = spikes.next_if(|(i, _)| *i == bar_index) {
+            push(bar_index, OhlcvIssueKind::Spike { zscore });
+        }
+    }
+    return OhlcvQualityReport {
+        bars: bars.len(),
+        issues,
+    };
+}
+
+/// Repair operations applied by `repair`, in field order.
+#[derive(Debug, Clone)]
+pub struct OhlcvRepairConfig {
+    pub quality: OhlcvQualityConfig,
+    /// Drops bars without an open time.
+    pub drop_missing_time: bool,
+    pub sort: bool,
+    /// Keeps the last bar of each open time, as it is usually the corrected one.
+    pub dedupe: bool,
+    /// NaN prices take the previous close, NaN volume becomes 0.
+    pub fill_nan: bool,
+    /// Widens high and low to contain open and close, swaps them if inverted.
+    pub fix_range: bool,
+    /// Clips prices so no close to close return exceeds `quality.spike_zscore`.
+    pub clip_outliers: bool,
+    /// Inserts flat bars at the previous close with zero volume for every missing bar.
+    /// Gaps of `MAX_GAP_BARS` or more are left as they are.
+    pub fill_missing: bool,
+}
+
+impl Default for OhlcvRepairConfig {
+    fn default() -> Self {
+        Self {
+            quality: OhlcvQualityConfig::default(),
+            drop_missing_time: true,
+            sort: true,
+            dedupe: true,
+            fill_nan: true,
+            fix_range: true,
+            clip_outliers: false,
+            fill_missing: false,
+        }
+    }
+}
+
+impl OhlcvRepairConfig {
+    #[inline]
+    pub fn new(timeframe: Timeframe, timezone: Tz) -> Self {
+        Self {
+            quality: OhlcvQualityConfig::new(timeframe, timezone),
+            ..Default::default()
+        }
+    }
+}
+
+/// Fixed copy of `bars`.
+pub fn repair(bars: &[OhlcvBar], config: &OhlcvRepairConfig) -> Vec<OhlcvBar> {
+    let mut bars = bars.to_vec();
+    if config.drop_missing_time {
+        bars.retain(|bar| bar.open_time().is_some());
+    }
+    if config.sort {
+        // stable, so duplicates keep their order
+        bars.sort_by(|a, b| a.open_time().cmp(&b.open_time()));
+    }
+    if config.dedupe {
+        let mut deduped: Vec<OhlcvBar> = Vec::with_capacity(bars.len());
+        for bar in bars {
+            match deduped.last_mut() {
+                Some(last) if last.open_time().is_some() && last.open_time() == bar.open_time() => {
+                    *last = bar
+                }
+                _ => deduped.push(bar),
+            }
+        }
+        bars = deduped;
+    }
+    if config.fill_nan {
+        let mut prev_close = f64::NAN;
+        for bar in bars.iter_mut() {
+            // the first bar has no previous close, use any of its own prices
+            let fallback = if prev_close.is_nan() {
+                [bar.close(), bar.open(), bar.high(), bar.low()]
+                    .into_iter()
+                    .find(|x| !x.is_nan())
+                    .unwrap_or(f64::NAN)
+            } else {
+                prev_close
+            };
+            if bar.open().is_nan() {
+                bar.set_open(fallback);
+            }
+            if bar.close().is_nan() {
+                bar.set_close(fallback);
+            }
+            if bar.high().is_nan() {
+                bar.set_high(bar.open().max(bar.close()));
+            }
+            if bar.low().is_nan() {
+                bar.set_low(bar.open().min(bar.close()));
+            }
+            if bar.volume().is_nan() {
+                bar.set_volume(0.0);
+            }
+            prev_close = bar.close();
+        }
+    }
+    if config.fix_range {
+        for bar in bars.iter_mut() {
+            fix_range(bar);
+        }
+    }
+    if config.clip_outliers && !config.quality.spike_zscore.is_nan() {
+        clip_outliers(&mut bars, config.quality.spike_zscore);
+    }
+    if config.fill_missing {
+        let mut filled: Vec<OhlcvBar> = Vec::with_capacity(bars.len());
+        for bar in bars {
+            if let (Some(prev), Some(time)) = (filled.last().copied(), bar.open_time()) {
+                if let Some(prev_time) = prev.open_time() {
+                    let mut missing = vec![];
+                    let count = visit_missing_times(
+                        *prev_time,
+                        *time,
+                        &config.quality,
+                        MAX_GAP_BARS,
+                        |open_time| missing.push(open_time),
+                    );
+                    if count == MAX_GAP_BARS {
+                        missing.clear();
+                    }
+                    for open_time in missing {
+                        let close_time = next_timeframe_boundary(
+                            open_time,
+                            &config.quality.timeframe,
+                            &config.quality.timezone,
+                        );
+                        let close = prev.close();
+                        filled.push(OhlcvBar::new(
+                            Some(open_time),
+                            Some(close_time),
+                            close,
+                            close,
+                            close,
+                            close,
+                            0.0,
+                        ));
+                    }
+                }
+            }
+            filled.push(bar);
+        }
+        bars = filled;
+    }
+    return bars;
+}
+
+#[inline]
+fn fix_range(bar: &mut OhlcvBar) {
+    let prices = [bar.open(), bar.high(), bar.low(), bar.close()];
+    bar.set_high(prices.iter().copied().fold(f64::NAN, f64::max));
+    bar.set_low(prices.iter().copied().fold(f64::NAN, f64::min));
+}
+
+/// Gaps are enumerated bar by bar, so a corrupt far-future open time would take forever.
+/// Longer gaps are reported with `missing` equal to this value and are not filled by `repair`.
+pub const MAX_GAP_BARS: usize = 1_000_000;
+
+/// Open times between `prev` and `time`, both excluded, where a bar of the timeframe is expected.
+/// Calls `f` for each of them and stops after `limit`, returns how many were visited.
+fn visit_missing_times(
+    prev: DateTime<Utc>,
+    time: DateTime<Utc>,
+    config: &OhlcvQualityConfig,
+    limit: usize,
+    mut f: impl FnMut(DateTime<Utc>),
+) -> usize {
+    if !config.timeframe.is_time_based() || config.timeframe.unknown() {
+        return 0;
+    }
+    let intraday = matches!(
+        config.timeframe,
+        Timeframe::Hours(_) | Timeframe::Minutes(_) | Timeframe::Seconds(_)
+    );
+    let mut count = 0;
+    let mut next = next_timeframe_boundary(prev, &config.timeframe, &config.timezone);
+    while next < time && count < limit {
+        let expected = match &config.session {
+            None => true,
+            Some(session) if intraday => session.is_open(next, true),
+            Some(session) => {
+                session.is_trading_day(next.with_timezone(&config.timezone).date_naive())
+            }
+        };
+        if expected {
+            f(next);
+            count += 1;
+        }
+        next = next_timeframe_boundary(next, &config.timeframe, &config.timezone);
+    }
+    return count;
+}
+
+/// Number of missing bars between `prev` and `time`, at most `MAX_GAP_BARS`.
+#[inline]
+fn missing_count(prev: DateTime<Utc>, time: DateTime<Utc>, config: &OhlcvQualityConfig) -> usize {
+    return visit_missing_times(prev, time, config, MAX_GAP_BARS, |_| {});
+}
+
+/// Median and scaled median absolute deviation of the finite values.
+fn median_mad(values: &[f64]) -> Option<(f64, f64)> {
+    let median = |values: &mut Vec<f64>| {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = values.len();
+        if n % 2 == 1 {
+            values[n / 2]
+        } else {
+            (values[n / 2 - 1] + values[n / 2]) / 2.0
+        }
+    };
+    let mut finite: Vec<f64> = values.iter().copied().filter(|x| x.is_finite()).collect();
+    if finite.len() < 3 {
+        return None;
+    }
+    let m = median(&mut finite);
+    let mut deviations: Vec<f64> = finite.iter().map(|x| (x - m).abs()).collect();
+    // 1.4826 scales the MAD to the standard deviation of a normal distribution
+    let mad = median(&mut deviations) * 1.4826;
+    if mad <= 0.0 {
+        return None;
+    }
+    return Some((m, mad));
+}
+
+#[inline]
+fn log_returns(bars: &[OhlcvBar]) -> Vec<f64> {
+    return bars
+        .windows(2)
+        .map(|w| (w[1].close() / w[0].close()).ln())
+        .collect();
+}
+
+/// Bar indices whose close to close log return has a robust z-score above `threshold`.
+fn spikes(bars: &[OhlcvBar], threshold: f64) -> Vec<(usize, f64)> {
+    let returns = log_returns(bars);
+    let Some((median, mad)) = median_mad(&returns) else {
+        return vec![];
+    };
+    return returns
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| {
+            let zscore = (r - median) / mad;
+            (zscore.abs() > threshold).then_some((i + 1, zscore))
+        })
+        .collect();
+}
+
+fn clip_outliers(bars: &mut [OhlcvBar], threshold: f64) {
+    if threshold <= 0.0 {
+        return;
+    }
+    let Some((median, mad)) = median_mad(&log_returns(bars)) else {
+        return;
+    };
+    for i in 1..bars.len() {
+        let prev_close = bars[i - 1].close();
+        if !prev_close.is_finite() || prev_close <= 0.0 {
+            continue;
+        }
+        let min = prev_close * (median - threshold * mad).exp();
+        let max = prev_close * (median + threshold * mad).exp();
+        let bar = &mut bars[i];
+        bar.set_open(bar.open().clamp(min, max));
+        bar.set_high(bar.high().clamp(min, max));
+        bar.set_low(bar.low().clamp(min, max));
+        bar.set_close(bar.close().clamp(min, max));
+        fix_range(bar);
+    }
+}
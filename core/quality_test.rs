@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::Tz;
+
+    use crate::{
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvReaderOps},
+        quality::{OhlcvIssueKind, OhlcvQualityConfig, OhlcvRepairConfig, MAX_GAP_BARS},
+        test_utils::time,
+        timeframe::Timeframe,
+    };
+
+    fn bar(minutes: i64, close: f64, volume: f64) -> OhlcvBar {
+        return OhlcvBar::new(
+            Some(time(minutes)),
+            Some(time(minutes + 1)),
+            close,
+            close + 0.5,
+            close - 0.5,
+            close,
+            volume,
+        );
+    }
+
+    fn ohlcv() -> Ohlcv {
+        let mut bars: Vec<OhlcvBar> = (0..40)
+            .map(|i| bar(i, 100.0 + ((i * i * 37) % 11) as f64 * 0.1, 1.0))
+            .collect();
+        // 2 missing bars at 10 and 11
+        bars.retain(|b| !matches!(b.open_time(), Some(t) if *t == time(10) || *t == time(11)));
+        // duplicate of 20, later one wins
+        bars.insert(19, bar(20, 100.3, 2.0));
+        // out of order, which also leaves a gap before the earlier bar
+        bars.swap(30, 31);
+        // high < low
+        bars[33].set_high(90.0);
+        bars[34].set_close(f64::NAN);
+        bars[35].set_volume(0.0);
+        // bad tick
+        bars[25].set_close(150.0);
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Minutes(1));
+        return ohlcv;
+    }
+
+    #[test]
+    pub fn report_lists_all_issues() {
+        let ohlcv = ohlcv();
+        let report = ohlcv.quality_report(&OhlcvQualityConfig::new(Timeframe::Minutes(1), Tz::UTC));
+        assert_eq!(report.bars, 39);
+        assert_eq!(report.count(OhlcvIssueKind::Gap { missing: 0 }), 2);
+        assert_eq!(report.missing_bars(), 3);
+        assert_eq!(report.count(OhlcvIssueKind::Duplicate), 1);
+        assert_eq!(report.count(OhlcvIssueKind::OutOfOrder), 1);
+        assert_eq!(report.count(OhlcvIssueKind::InvalidRange), 2);
+        assert_eq!(report.count(OhlcvIssueKind::NaN), 1);
+        assert_eq!(report.count(OhlcvIssueKind::ZeroVolume), 1);
+        // jump up to 150 and back down
+        let spikes: Vec<usize> = report
+            .issues
+            .iter()
+            .filter(|i| matches!(i.kind, OhlcvIssueKind::Spike { .. }))
+            .map(|i| i.bar_index)
+            .collect();
+        assert_eq!(spikes, vec![25, 26]);
+        assert!(report.to_string().starts_with("39 bars, 10 issues"));
+    }
+
+    #[test]
+    pub fn repair_fixes_issues() {
+        let ohlcv = ohlcv();
+        let mut config = OhlcvRepairConfig::new(Timeframe::Minutes(1), Tz::UTC);
+        config.fill_missing = true;
+        config.clip_outliers = true;
+        let fixed = ohlcv.repair(&config);
+        assert_eq!(fixed.len(), 40);
+        assert_eq!(fixed.timeframe(), Timeframe::Minutes(1));
+        let filled = fixed.get(10).unwrap();
+        assert_eq!(filled.open_time(), Some(&time(10)));
+        assert_eq!(filled.close(), fixed.get(9).unwrap().close());
+        assert_eq!(filled.volume(), 0.0);
+        assert_eq!(fixed.get(20).unwrap().volume(), 2.0);
+        // the bad tick at minute 26
+        assert!(fixed.get(26).unwrap().close() < 110.0);
+
+        let mut config = OhlcvQualityConfig::new(Timeframe::Minutes(1), Tz::UTC);
+        config.zero_volume = false;
+        // clipped returns sit right at the threshold
+        config.spike_zscore = f64::NAN;
+        let report = fixed.quality_report(&config);
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    pub fn far_future_time_is_bounded() {
+        let mut bars = vec![bar(0, 100.0, 1.0), bar(1, 100.0, 1.0)];
+        // corrupt timestamp, about 500 million minutes later
+        bars.push(OhlcvBar::new(
+            Some(Utc.with_ymd_and_hms(3000, 1, 1, 0, 0, 0).unwrap()),
+            None,
+            100.0,
+            100.0,
+            100.0,
+            100.0,
+            1.0,
+        ));
+        let ohlcv = Ohlcv::from_bars(bars);
+        let report = ohlcv.quality_report(&OhlcvQualityConfig::new(Timeframe::Minutes(1), Tz::UTC));
+        assert_eq!(report.missing_bars(), MAX_GAP_BARS);
+
+        let mut config = OhlcvRepairConfig::new(Timeframe::Minutes(1), Tz::UTC);
+        config.fill_missing = true;
+        assert_eq!(ohlcv.repair(&config).len(), 3);
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+
+use crate::{
+    ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvWriter},
+    sym::Sym,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RollError {
+    #[error("No contracts to roll")]
+    Empty,
+    #[error("Contract {0} has no expiry")]
+    MissingExpiry(String),
+    #[error("Contract {0} has a bar without open time")]
+    MissingOpenTime(String),
+    #[error("Contract {0} has no open interest")]
+    MissingOpenInterest(String),
+    #[error("Contract {id} has {open_interest} open interest values for {bars} bars")]
+    OpenInterestLength {
+        id: String,
+        open_interest: usize,
+        bars: usize,
+    },
+}
+
+/// When to switch from a contract to the next one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RollRule {
+    /// At the first bar opening `n` days before expiry of the front contract.
+    DaysBeforeExpiry(i64),
+    /// On the bar after the next contract traded more volume than the front one.
+    Volume,
+    /// On the bar after the next contract had more open interest than the front one.
+    OpenInterest,
+}
+
+/// How prices of earlier contracts are adjusted for the gap at each roll, so the series has no artificial jumps.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RollAdjustment {
+    /// Raw contract prices.
+    #[default]
+    None,
+    /// Earlier bars are shifted by the price difference between the contracts.
+    Difference,
+    /// Earlier bars are scaled by the price ratio between the contracts, keeps returns.
+    Ratio,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollConfig {
+    pub rule: RollRule,
+    pub adjustment: RollAdjustment,
+}
+
+impl Default for RollConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            rule: RollRule::DaysBeforeExpiry(5),
+            adjustment: RollAdjustment::Difference,
+        }
+    }
+}
+
+/// Single contract month. The expiry comes from `Sym::expiry`.
+#[derive(Debug, Clone)]
+pub struct RollContract {
+    sym: Sym,
+    ohlcv: Ohlcv,
+    open_interest: Option<Vec<f64>>,
+}
+
+impl RollContract {
+    #[inline]
+    pub fn new(sym: Sym, ohlcv: Ohlcv) -> Self {
+        return Self {
+            sym,
+            ohlcv,
+            open_interest: None,
+        };
+    }
+
+    /// One value per bar, required by `RollRule::OpenInterest`.
+    #[inline]
+    pub fn with_open_interest(mut self, open_interest: Vec<f64>) -> Self {
+        self.open_interest = Some(open_interest);
+        return self;
+    }
+
+    #[inline]
+    pub fn sym(&self) -> &Sym {
+        return &self.sym;
+    }
+
+    #[inline]
+    pub fn ohlcv(&self) -> &Ohlcv {
+        return &self.ohlcv;
+    }
+
+    #[inline]
+    pub fn open_interest(&self) -> Option<&[f64]> {
+        return self.open_interest.as_deref();
+    }
+
+    #[inline]
+    fn id(&self) -> String {
+        return self
+            .sym
+            .id()
+            .or(self.sym.ticker_id())
+            .unwrap_or("?")
+            .to_string();
+    }
+}
+
+/// Switch from one contract to the next in a continuous series.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Roll {
+    /// First bar of the new contract in the continuous series.
+    pub bar_index: usize,
+    pub time: DateTime<Utc>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Price of the old contract at the roll, adjusted like the series.
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
+    pub exit_price: f64,
+    /// Price of the new contract at the roll, adjusted like the series.
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
+    pub entry_price: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContinuousOhlcv {
+    pub ohlcv: Ohlcv,
+    pub rolls: Vec<Roll>,
+}
+
+/// Stitches contract months into a continuous series, ordered by expiry.
+/// Roll prices are the closes of both contracts on the last bar before the roll,
+/// or the nearest bars around it if the contracts don't share that bar.
+/// Rolls without a finite price on both sides are left out, trades are carried over to the new contract as is.
+pub fn roll_contracts(
+    contracts: &[RollContract],
+    config: &RollConfig,
+) -> Result<ContinuousOhlcv, RollError> {
+    if contracts.is_empty() {
+        return Err(RollError::Empty);
+    }
+    let mut contracts: Vec<(&RollContract, DateTime<Utc>, Vec<OhlcvBar>)> = contracts
+        .iter()
+        .map(|contract| {
+            let expiry = *contract
+                .sym
+                .expiry()
+                .ok_or_else(|| RollError::MissingExpiry(contract.id()))?;
+            let bars = contract.ohlcv.bars();
+            if bars.iter().any(|bar| bar.open_time().is_none()) {
+                return Err(RollError::MissingOpenTime(contract.id()));
+            }
+            if config.rule == RollRule::OpenInterest {
+                let open_interest = contract
+                    .open_interest()
+                    .ok_or_else(|| RollError::MissingOpenInterest(contract.id()))?;
+                if open_interest.len() != bars.len() {
+                    return Err(RollError::OpenInterestLength {
+                        id: contract.id(),
+                        open_interest: open_interest.len(),
+                        bars: bars.len(),
+                    });
+                }
+            }
+            return Ok((contract, expiry, bars));
+        })
+        .collect::<Result<_, _>>()?;
+    contracts.sort_by_key(|(_, expiry, _)| *expiry);
+
+    // open time of the first bar taken from contract i + 1
+    let mut roll_times: Vec<DateTime<Utc>> = Vec::with_capacity(contracts.len() - 1);
+    for i in 0..contracts.len() - 1 {
+        let (front, expiry, front_bars) = &contracts[i];
+        let (next, _, next_bars) = &contracts[i + 1];
+        let start = roll_times.last().copied();
+        let time = match config.rule {
+            RollRule::DaysBeforeExpiry(days) => *expiry - Duration::days(days),
+            RollRule::Volume | RollRule::OpenInterest => {
+                let metric =
+                    |contract: &RollContract, bars: &[OhlcvBar], i: usize| match config.rule {
+                        RollRule::OpenInterest => contract.open_interest().unwrap()[i],
+                        _ => bars[i].volume(),
+                    };
+                let next_index: HashMap<DateTime<Utc>, usize> = next_bars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, bar)| (*bar.open_time().unwrap(), i))
+                    .collect();
+                front_bars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, bar)| (i, *bar.open_time().unwrap()))
+                    .filter(|(_, time)| start.is_none_or(|start| *time >= start) && time < expiry)
+                    .find_map(|(i, time)| {
+                        let j = *next_index.get(&time)?;
+                        (metric(next, next_bars, j) > metric(front, front_bars, i))
+                            .then(|| next_bars.get(j + 1).map(|bar| *bar.open_time().unwrap()))
+                            .flatten()
+                    })
+                    .unwrap_or(*expiry)
+            }
+        };
+        roll_times.push(start.map_or(time, |start| time.max(start)));
+    }
+
+    let mut segments: Vec<Vec<OhlcvBar>> = contracts
+        .iter()
+        .enumerate()
+        .map(|(i, (_, _, bars))| {
+            bars.iter()
+                .filter(|bar| {
+                    let time = bar.open_time().unwrap();
+                    (i == 0 || *time >= roll_times[i - 1])
+                        && (i == roll_times.len() || *time < roll_times[i])
+                })
+                .cloned()
+                .collect()
+        })
+        .collect();
+
+    // raw (old, new) prices of each roll
+    let prices: Vec<(f64, f64)> = roll_times
+        .iter()
+        .enumerate()
+        .map(|(i, roll_time)| {
+            let front = &contracts[i].2;
+            let next = &contracts[i + 1].2;
+            let Some(old) = front
+                .iter()
+                .rev()
+                .find(|bar| bar.open_time().unwrap() < roll_time)
+            else {
+                return (f64::NAN, f64::NAN);
+            };
+            let new = next
+                .iter()
+                .find(|bar| bar.open_time() == old.open_time())
+                .or_else(|| {
+                    next.iter()
+                        .rev()
+                        .find(|bar| bar.open_time().unwrap() < roll_time)
+                })
+                .map(|bar| bar.close())
+                .or_else(|| next.first().map(|bar| bar.open()))
+                .unwrap_or(f64::NAN);
+            return (old.close(), new);
+        })
+        .collect();
+
+    // adjustment of each segment, accumulated from the last roll backwards
+    let mut adjustments = vec![0.0; contracts.len()];
+    if config.adjustment == RollAdjustment::Ratio {
+        adjustments.fill(1.0);
+    }
+    for i in (0..prices.len()).rev() {
+        let (old, new) = prices[i];
+        adjustments[i] = match config.adjustment {
+            RollAdjustment::None => 0.0,
+            RollAdjustment::Difference => {
+                let diff = new - old;
+                adjustments[i + 1] + if diff.is_finite() { diff } else { 0.0 }
+            }
+            RollAdjustment::Ratio => {
+                let ratio = new / old;
+                adjustments[i + 1]
+                    * if ratio.is_finite() && ratio > 0.0 {
+                        ratio
+                    } else {
+                        1.0
+                    }
+            }
+        };
+    }
+    let adjust = |price: f64, i: usize| match config.adjustment {
+        RollAdjustment::None => price,
+        RollAdjustment::Difference => price + adjustments[i],
+        RollAdjustment::Ratio => price * adjustments[i],
+    };
+    for (i, segment) in segments.iter_mut().enumerate() {
+        for bar in segment.iter_mut() {
+            bar.set_open(adjust(bar.open(), i));
+            bar.set_high(adjust(bar.high(), i));
+            bar.set_low(adjust(bar.low(), i));
+            bar.set_close(adjust(bar.close(), i));
+        }
+    }
+
+    let mut ohlcv = Ohlcv::with_capacity(segments.iter().map(|x| x.len()).sum());
+    ohlcv.set_timeframe(contracts[0].0.ohlcv.timeframe());
+    let mut rolls = vec![];
+    for (i, segment) in segments.into_iter().enumerate() {
+        if i > 0 && !segment.is_empty() {
+            let (old, new) = prices[i - 1];
+            let exit_price = Some(adjust(old, i - 1))
+                .filter(|x| x.is_finite())
+                .or_else(|| ohlcv.at(-1).map(|bar| bar.close()))
+                .filter(|x| x.is_finite());
+            let entry_price = Some(adjust(new, i))
+                .filter(|x| x.is_finite())
+                .or(Some(segment[0].open()))
+                .filter(|x| x.is_finite());
+            if let (Some(exit_price), Some(entry_price)) = (exit_price, entry_price) {
+                rolls.push(Roll {
+                    bar_index: ohlcv.len(),
+                    time: *segment[0].open_time().unwrap(),
+                    from: contracts[i - 1].0.sym.id().map(|x| x.to_string()),
+                    to: contracts[i].0.sym.id().map(|x| x.to_string()),
+                    exit_price,
+                    entry_price,
+                });
+            }
+        }
+        ohlcv.push_many(segment);
+    }
+    return Ok(ContinuousOhlcv { ohlcv, rolls });
+}
@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        roll::{
+            roll_contracts, Roll, RollAdjustment, RollConfig, RollContract, RollError, RollRule,
+        },
+        signal::Signal,
+        sym::{Sym, SymDerivative},
+        timeframe::Timeframe,
+    };
+
+    fn day(i: i64) -> DateTime<Utc> {
+        return Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(i);
+    }
+
+    /// Daily bars up to expiry, `close = base + day`.
+    fn contract(id: &str, expiry: i64, base: f64, volume: impl Fn(i64) -> f64) -> RollContract {
+        let mut sym = Sym::default();
+        sym.set_id(Some(id.to_string()));
+        sym.set_derivative(Some(SymDerivative::future(day(expiry), None, 50.0)));
+        let mut ohlcv = Ohlcv::from_bars(
+            (0..expiry)
+                .map(|i| {
+                    let close = base + i as f64;
+                    OhlcvBar::new(
+                        Some(day(i)),
+                        Some(day(i + 1)),
+                        close - 1.0,
+                        close,
+                        close - 1.0,
+                        close,
+                        volume(i),
+                    )
+                })
+                .collect(),
+        );
+        ohlcv.set_timeframe(Timeframe::Days(1));
+        return RollContract::new(sym, ohlcv);
+    }
+
+    fn contracts() -> Vec<RollContract> {
+        // out of order on purpose
+        return vec![
+            contract("ESH", 20, 110.0, |i| 10.0 * i as f64),
+            contract("ESZ", 10, 100.0, |i| 100.0 - 10.0 * i as f64),
+        ];
+    }
+
+    fn closes(ohlcv: &Ohlcv) -> Vec<f64> {
+        return ohlcv.bars().iter().map(|bar| bar.close()).collect();
+    }
+
+    #[test]
+    pub fn rules_and_adjustments() {
+        let config = |rule, adjustment| RollConfig { rule, adjustment };
+
+        let series = roll_contracts(
+            &contracts(),
+            &config(RollRule::DaysBeforeExpiry(4), RollAdjustment::None),
+        )
+        .unwrap();
+        assert_eq!(series.ohlcv.len(), 20);
+        assert_eq!(series.ohlcv.timeframe(), Timeframe::Days(1));
+        assert_eq!(series.rolls.len(), 1);
+        let roll = &series.rolls[0];
+        assert_eq!((roll.bar_index, roll.time), (6, day(6)));
+        assert_eq!(
+            (roll.from.as_deref(), roll.to.as_deref()),
+            (Some("ESZ"), Some("ESH"))
+        );
+        assert_eq!((roll.exit_price, roll.entry_price), (105.0, 115.0));
+        assert_eq!(&closes(&series.ohlcv)[5..7], &[105.0, 116.0]);
+
+        let series = roll_contracts(
+            &contracts(),
+            &config(RollRule::DaysBeforeExpiry(4), RollAdjustment::Difference),
+        )
+        .unwrap();
+        let expected: Vec<f64> = (0..20).map(|i| 110.0 + i as f64).collect();
+        assert_eq!(closes(&series.ohlcv), expected);
+        assert_eq!(
+            (series.rolls[0].exit_price, series.rolls[0].entry_price),
+            (115.0, 115.0)
+        );
+
+        let series = roll_contracts(
+            &contracts(),
+            &config(RollRule::DaysBeforeExpiry(4), RollAdjustment::Ratio),
+        )
+        .unwrap();
+        let bar = series.ohlcv.get(0).unwrap();
+        assert!((bar.close() - 100.0 * 115.0 / 105.0).abs() < 1e-9);
+        assert_eq!(series.ohlcv.get(6).unwrap().close(), 116.0);
+
+        // volumes cross on day 6, the roll happens on the next bar
+        let series = roll_contracts(
+            &contracts(),
+            &config(RollRule::Volume, RollAdjustment::None),
+        )
+        .unwrap();
+        assert_eq!(series.rolls[0].bar_index, 7);
+
+        let with_open_interest = |contract: RollContract, open_interest: fn(i64) -> f64| {
+            let len = contract.ohlcv().len() as i64;
+            contract.with_open_interest((0..len).map(open_interest).collect())
+        };
+        let mut contracts = contracts();
+        let last = contracts.pop().unwrap();
+        contracts.push(with_open_interest(last, |i| 10.0 - i as f64));
+        assert_eq!(
+            roll_contracts(
+                &contracts,
+                &config(RollRule::OpenInterest, RollAdjustment::None)
+            )
+            .unwrap_err(),
+            RollError::MissingOpenInterest("ESH".to_string())
+        );
+        let first = contracts.remove(0);
+        contracts.push(with_open_interest(first, |i| 2.0 * i as f64));
+        let series = roll_contracts(
+            &contracts,
+            &config(RollRule::OpenInterest, RollAdjustment::None),
+        )
+        .unwrap();
+        assert_eq!(series.rolls[0].bar_index, 5);
+
+        assert_eq!(
+            roll_contracts(&[], &RollConfig::default()).unwrap_err(),
+            RollError::Empty
+        );
+        let undated = RollContract::new(Sym::default(), Ohlcv::new());
+        assert!(matches!(
+            roll_contracts(&[undated], &RollConfig::default()),
+            Err(RollError::MissingExpiry(_))
+        ));
+    }
+
+    fn series(adjustment: RollAdjustment) -> (Rc<RefCell<Ctx>>, Vec<Roll>) {
+        let series = roll_contracts(
+            &contracts(),
+            &RollConfig {
+                rule: RollRule::DaysBeforeExpiry(4),
+                adjustment,
+            },
+        )
+        .unwrap();
+        let mut sym = Sym::default();
+        sym.set_id(Some("ES1!".to_string()));
+        sym.set_min_tick(0.25);
+        sym.set_min_qty(1.0);
+        let mut ctx = Ctx::new();
+        ctx.set_sym(sym);
+        ctx.set_ohlcv(series.ohlcv.into_box());
+        return (Rc::new(RefCell::new(ctx)), series.rolls);
+    }
+
+    fn signals() -> Vec<Option<Signal>> {
+        return (0..20)
+            .map(|i| {
+                (i == 1).then(|| {
+                    let mut signal = Signal::size(1.0);
+                    signal.set_id(Some("long".to_string()));
+                    signal
+                })
+            })
+            .collect();
+    }
+
+    fn backtest(adjustment: RollAdjustment, rolls: bool) -> Backtest {
+        let (ctx, series_rolls) = series(adjustment);
+        let mut bt = Backtest::new(ctx, BacktestConfig::default());
+        if rolls {
+            bt.set_rolls(series_rolls);
+        }
+        bt.signal_list(signals());
+        return bt;
+    }
+
+    #[test]
+    pub fn backtest_roll_trades() {
+        let adjusted = backtest(RollAdjustment::Difference, false);
+        let rolled = backtest(RollAdjustment::None, true);
+        assert_eq!(adjusted.equity(), adjusted.initial_capital() + 18.0);
+        assert_eq!(rolled.equity(), adjusted.equity());
+        assert_eq!(rolled.closed_trades().len(), 1);
+        let exit = rolled.closed_trades()[0].exit().unwrap();
+        assert_eq!(exit.id().map(|x| x.as_str()), Some("roll"));
+        assert_eq!((exit.fill_bar_index(), exit.price()), (6, 105.0));
+        let entry = rolled.open_trades()[0].entry().unwrap();
+        assert_eq!(entry.price(), 115.0);
+        assert_eq!(entry.id().map(|x| x.as_str()), Some("long"));
+        assert_eq!(rolled.position_size(), 1.0);
+
+        // without rolls the gap between the contracts is counted as profit
+        let raw = backtest(RollAdjustment::None, false);
+        assert_eq!(raw.equity(), raw.initial_capital() + 28.0);
+
+        // a roll without prices is skipped instead of turning the equity into NaN
+        let (ctx, mut rolls) = series(RollAdjustment::None);
+        rolls[0].exit_price = f64::NAN;
+        let mut bt = Backtest::new(ctx, BacktestConfig::default());
+        bt.set_rolls(rolls);
+        bt.signal_list(signals());
+        assert!(bt.closed_trades().is_empty());
+        assert_eq!(bt.equity(), raw.equity());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    pub fn restore_keeps_pending_rolls() {
+        use crate::backtest::BacktestState;
+
+        let expected = backtest(RollAdjustment::None, true);
+        let (ctx, rolls) = series(RollAdjustment::None);
+        let mut bt = Backtest::new(ctx, BacktestConfig::default());
+        bt.set_rolls(rolls);
+        bt.signal_list(signals()[0..4].to_vec());
+        let state = BacktestState::from_json(&bt.snapshot().to_json().unwrap()).unwrap();
+
+        let (ctx, _) = series(RollAdjustment::None);
+        let mut bt = Backtest::restore(ctx, state).unwrap();
+        assert_eq!(bt.rolls().len(), 1);
+        bt.signal_list(signals()[4..].to_vec());
+        assert!(bt.rolls().is_empty());
+        assert_eq!(bt.closed_trades(), expected.closed_trades());
+        assert_eq!(bt.open_trades(), expected.open_trades());
+        assert_eq!(bt.equity(), expected.equity());
+    }
+}
@@ -0,0 +1,272 @@
+use chrono::{DateTime, Duration, Months, Utc};
+use chrono_tz::Tz;
+use std::{cell::RefCell, rc::Rc};
+use thiserror::Error;
+
+use crate::{
+    ctx::Ctx,
+    ohlcv::{floor_to_timeframe, next_timeframe_boundary, Ohlcv, OhlcvBar},
+    timeframe::Timeframe,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SecurityError {
+    #[error("Security timeframe must be time based, got {0}")]
+    Timeframe(Timeframe),
+}
+
+/// Same as `barmerge.lookahead_*` in Pine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lookahead {
+    /// Only higher timeframe bars that have already closed at the current base bar are visible.
+    #[default]
+    Off,
+    /// The higher timeframe bar containing the current base bar is returned in its final form,
+    /// built from base bars that have not happened yet. Useful for research, repaints in backtests.
+    On,
+}
+
+/// Higher timeframe view of a base `Ctx`, similar to `request.security` in Pine.
+///
+/// Higher timeframe bars are aggregated incrementally from the base bars visited by the `Ctx`,
+/// so nothing is read ahead of the current bar unless `Lookahead::On` is set.
+#[derive(Debug)]
+pub struct Security {
+    ctx: Rc<RefCell<Ctx>>,
+    timeframe: Timeframe,
+    lookahead: Lookahead,
+    completed: Vec<OhlcvBar>,
+    partial: Option<OhlcvBar>,
+    bucket: Option<DateTime<Utc>>,
+    bucket_end: Option<DateTime<Utc>>,
+    next_bar_index: usize,
+    // state before the current base bar was pushed, so it can be re-aggregated when updated in place
+    rollback: Option<SecurityRollback>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SecurityRollback {
+    completed_len: usize,
+    partial: Option<OhlcvBar>,
+    bucket: Option<DateTime<Utc>>,
+    bucket_end: Option<DateTime<Utc>>,
+}
+
+impl Security {
+    /// Fails for ticks, volume, range and other timeframes that have no time boundaries.
+    #[inline]
+    pub fn new(ctx: Rc<RefCell<Ctx>>, timeframe: Timeframe) -> Result<Self, SecurityError> {
+        if TryInto::<Duration>::try_into(timeframe).is_err() {
+            return Err(SecurityError::Timeframe(timeframe));
+        }
+        Ok(Self {
+            ctx,
+            timeframe,
+            lookahead: Lookahead::Off,
+            completed: vec![],
+            partial: None,
+            bucket: None,
+            bucket_end: None,
+            next_bar_index: 0,
+            rollback: None,
+        })
+    }
+
+    #[inline]
+    pub fn with_lookahead(mut self, lookahead: Lookahead) -> Self {
+        self.lookahead = lookahead;
+        self
+    }
+
+    #[inline]
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    #[inline]
+    pub fn lookahead(&self) -> Lookahead {
+        self.lookahead
+    }
+
+    #[inline]
+    pub fn set_lookahead(&mut self, lookahead: Lookahead) {
+        self.lookahead = lookahead;
+    }
+
+    #[inline]
+    pub fn ctx(&self) -> Rc<RefCell<Ctx>> {
+        self.ctx.clone()
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.completed.clear();
+        self.partial = None;
+        self.bucket = None;
+        self.bucket_end = None;
+        self.next_bar_index = 0;
+        self.rollback = None;
+    }
+
+    /// Catches up with the `Ctx` by aggregating every base bar up to and including the current one.
+    /// The current base bar is re-aggregated on every call, so in-place updates of a realtime bar are reflected.
+    /// Base bars without open time belong to no higher timeframe bar and are skipped.
+    pub fn sync(&mut self) {
+        let ctx = self.ctx.clone();
+        let ctx = ctx.borrow();
+        if !ctx.is_initialized() {
+            if self.next_bar_index > 0 {
+                self.reset();
+            }
+            return;
+        }
+        let bar_index = ctx.bar_index();
+        if bar_index + 1 < self.next_bar_index {
+            self.reset();
+        }
+        if self.next_bar_index == bar_index + 1 {
+            if let Some(rollback) = self.rollback {
+                self.completed.truncate(rollback.completed_len);
+                self.partial = rollback.partial;
+                self.bucket = rollback.bucket;
+                self.bucket_end = rollback.bucket_end;
+                self.next_bar_index = bar_index;
+            }
+        }
+        let base_timeframe = ctx.ohlcv().timeframe();
+        let timezone = ctx.timezone();
+        while self.next_bar_index <= bar_index {
+            if self.next_bar_index == bar_index {
+                self.rollback = Some(SecurityRollback {
+                    completed_len: self.completed.len(),
+                    partial: self.partial,
+                    bucket: self.bucket,
+                    bucket_end: self.bucket_end,
+                });
+            }
+            let bar = ctx.ohlcv().get(self.next_bar_index).unwrap();
+            self.push(&bar, base_timeframe, &timezone);
+            self.next_bar_index += 1;
+        }
+    }
+
+    fn push(&mut self, bar: &OhlcvBar, base_timeframe: Timeframe, timezone: &Tz) {
+        let Some(open_time) = bar.open_time().copied() else {
+            return;
+        };
+        let bucket = floor_to_timeframe(open_time, &self.timeframe, timezone);
+
+        if self.bucket != Some(bucket) || self.partial.is_none() {
+            // base data had a gap and the previous bucket never saw its last bar
+            if let Some(mut partial) = self.partial.take() {
+                partial.set_close_time(self.bucket_end);
+                self.completed.push(partial);
+            }
+            let mut partial = *bar;
+            partial.set_open_time(Some(bucket));
+            self.partial = Some(partial);
+            self.bucket = Some(bucket);
+            self.bucket_end = Some(next_timeframe_boundary(bucket, &self.timeframe, timezone));
+        } else {
+            self.partial = self.partial.map(|partial| partial.merge(bar));
+        }
+
+        if let Some(bar_end) = base_bar_end(bar, base_timeframe) {
+            if bar_end >= self.bucket_end.unwrap() {
+                let mut bar = self.partial.take().unwrap();
+                bar.set_close_time(self.bucket_end);
+                self.completed.push(bar);
+            }
+        }
+    }
+
+    /// Higher timeframe bar visible at the current base bar, respecting `Lookahead`.
+    #[inline]
+    pub fn bar(&mut self) -> Option<OhlcvBar> {
+        match self.lookahead {
+            Lookahead::Off => self.completed(0),
+            Lookahead::On => self.lookahead_bar(),
+        }
+    }
+
+    /// Latest completed higher timeframe bar, `offset` bars back. Same as `[offset]` in Pine.
+    #[inline]
+    pub fn completed(&mut self, offset: usize) -> Option<OhlcvBar> {
+        self.sync();
+        let len = self.completed.len();
+        if offset >= len {
+            return None;
+        }
+        self.completed.get(len - 1 - offset).copied()
+    }
+
+    /// Higher timeframe bar that is still being formed, aggregated up to the current base bar only.
+    /// Its close time is the close time of the current base bar.
+    /// `None` when the current base bar closed the higher timeframe bar.
+    #[inline]
+    pub fn partial(&mut self) -> Option<OhlcvBar> {
+        self.sync();
+        self.partial
+    }
+
+    #[inline]
+    pub fn completed_len(&mut self) -> usize {
+        self.sync();
+        self.completed.len()
+    }
+
+    /// All higher timeframe bars completed so far.
+    #[inline]
+    pub fn ohlcv(&mut self) -> Ohlcv {
+        self.sync();
+        let mut ohlcv = Ohlcv::from_bars(self.completed.clone());
+        ohlcv.set_timeframe(self.timeframe);
+        ohlcv.set_timezone(self.ctx.borrow().timezone());
+        ohlcv
+    }
+
+    fn lookahead_bar(&mut self) -> Option<OhlcvBar> {
+        self.sync();
+        let partial = match self.partial {
+            Some(partial) => partial,
+            None => return self.completed.last().copied(),
+        };
+        let ctx = self.ctx.borrow();
+        let ohlcv = ctx.ohlcv();
+        let mut bar = partial;
+        for bar_index in self.next_bar_index..ohlcv.len() {
+            let next = ohlcv.get(bar_index).unwrap();
+            let open_time = match next.open_time() {
+                Some(open_time) => *open_time,
+                None => break,
+            };
+            if floor_to_timeframe(open_time, &self.timeframe, &ctx.timezone())
+                != partial.open_time().copied().unwrap()
+            {
+                break;
+            }
+            bar = bar.merge(&next);
+        }
+        bar.set_close_time(self.bucket_end);
+        Some(bar)
+    }
+}
+
+/// End of a base bar. Prefers the base timeframe, because close time is often the open time of
+/// the last tick (or equal to open time) in exported data.
+#[inline]
+fn base_bar_end(bar: &OhlcvBar, base_timeframe: Timeframe) -> Option<DateTime<Utc>> {
+    let open_time = *bar.open_time()?;
+    match base_timeframe {
+        Timeframe::Years(n) => return open_time.checked_add_months(Months::new((n as u32) * 12)),
+        Timeframe::Months(n) => return open_time.checked_add_months(Months::new(n as u32)),
+        _ => {}
+    }
+    if let Ok(duration) = TryInto::<Duration>::try_into(base_timeframe) {
+        return Some(open_time + duration);
+    }
+    match bar.close_time() {
+        Some(close_time) if *close_time > open_time => Some(*close_time),
+        _ => None,
+    }
+}
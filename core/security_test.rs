@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use crate::{
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader},
+        security::{Lookahead, Security, SecurityError},
+        timeframe::Timeframe,
+    };
+
+    fn hour(i: i64) -> DateTime<Utc> {
+        return Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(i);
+    }
+
+    /// Hourly bars at the given hours since 2024-01-01, `close = hour`.
+    fn ctx(hours: impl Iterator<Item = i64>) -> Rc<RefCell<Ctx>> {
+        let mut ohlcv = Ohlcv::from_bars(
+            hours
+                .map(|i| {
+                    let close = i as f64;
+                    OhlcvBar::new(
+                        Some(hour(i)),
+                        Some(hour(i + 1)),
+                        close,
+                        close + 0.5,
+                        close - 0.5,
+                        close,
+                        1.0,
+                    )
+                })
+                .collect(),
+        );
+        ohlcv.set_timeframe(Timeframe::Hours(1));
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        return Rc::new(RefCell::new(ctx));
+    }
+
+    #[test]
+    pub fn daily_on_hourly() {
+        let ctx = ctx(0..48);
+        let mut security = Security::new(ctx.clone(), Timeframe::Days(1)).unwrap();
+        let mut lookahead = Security::new(ctx.clone(), Timeframe::Days(1))
+            .unwrap()
+            .with_lookahead(Lookahead::On);
+
+        for _ in 0..6 {
+            ctx.borrow_mut().next();
+        }
+        assert_eq!(security.bar(), None);
+        let partial = security.partial().unwrap();
+        assert_eq!(partial.open_time(), Some(&hour(0)));
+        assert_eq!(partial.close_time(), Some(&hour(6)));
+        assert_eq!(partial.close(), 5.0);
+        assert_eq!(partial.volume(), 6.0);
+        // the whole day, including the 18 bars that have not happened yet
+        let future = lookahead.bar().unwrap();
+        assert_eq!(future.close_time(), Some(&hour(24)));
+        assert_eq!(future.high(), 23.5);
+        assert_eq!(future.close(), 23.0);
+        assert_eq!(future.volume(), 24.0);
+
+        while ctx.borrow().bar_index() < 23 {
+            ctx.borrow_mut().next();
+        }
+        assert_eq!(security.partial(), None);
+        let day = security.bar().unwrap();
+        assert_eq!(day.open_time(), Some(&hour(0)));
+        assert_eq!(day.close_time(), Some(&hour(24)));
+        assert_eq!(
+            (day.open(), day.high(), day.low(), day.close()),
+            (0.0, 23.5, -0.5, 23.0)
+        );
+        assert_eq!(lookahead.bar(), Some(day));
+
+        ctx.borrow_mut().next();
+        assert_eq!(security.bar(), Some(day));
+        assert_eq!(security.completed(1), None);
+        assert_eq!(lookahead.bar().unwrap().close(), 47.0);
+
+        while ctx.borrow_mut().next().is_some() {}
+        assert_eq!(security.completed_len(), 2);
+        assert_eq!(security.completed(1), Some(day));
+        let ohlcv = security.ohlcv();
+        assert_eq!(ohlcv.len(), 2);
+        assert_eq!(ohlcv.timeframe(), Timeframe::Days(1));
+    }
+
+    #[test]
+    pub fn gaps() {
+        // the second day stops at 06:00, the third day is missing
+        let ctx = ctx((0..31).chain(72..80));
+        let mut security = Security::new(ctx.clone(), Timeframe::Days(1)).unwrap();
+
+        while ctx.borrow().bar_index() < 30 {
+            ctx.borrow_mut().next();
+        }
+        assert_eq!(security.completed_len(), 1);
+        assert_eq!(security.partial().unwrap().close(), 30.0);
+
+        ctx.borrow_mut().next();
+        // the unfinished day is completed once a bar of a later day arrives
+        assert_eq!(security.completed_len(), 2);
+        let day = security.bar().unwrap();
+        assert_eq!(day.open_time(), Some(&hour(24)));
+        assert_eq!(day.close_time(), Some(&hour(48)));
+        assert_eq!(day.close(), 30.0);
+        assert_eq!(day.volume(), 7.0);
+        let partial = security.partial().unwrap();
+        assert_eq!(partial.open_time(), Some(&hour(72)));
+    }
+
+    #[test]
+    pub fn bars_without_open_time() {
+        let bar = |i: Option<i64>, close: f64| {
+            let open_time = i.map(hour);
+            let close_time = i.map(|i| hour(i + 1));
+            OhlcvBar::new(open_time, close_time, close, close, close, close, 1.0)
+        };
+        let mut ohlcv =
+            Ohlcv::from_bars(vec![bar(Some(0), 1.0), bar(None, 9.0), bar(Some(2), 2.0)]);
+        ohlcv.set_timeframe(Timeframe::Hours(1));
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        let ctx = Rc::new(RefCell::new(ctx));
+        let mut security = Security::new(ctx.clone(), Timeframe::Days(1)).unwrap();
+        while ctx.borrow_mut().next().is_some() {}
+        let partial = security.partial().unwrap();
+        assert_eq!((partial.high(), partial.close()), (2.0, 2.0));
+        assert_eq!(partial.volume(), 2.0);
+    }
+
+    #[test]
+    pub fn time_based_only() {
+        let ctx = ctx(0..2);
+        for timeframe in [
+            Timeframe::Ticks(10),
+            Timeframe::Volume(100),
+            Timeframe::Ranges(5),
+            Timeframe::Unknown(),
+        ] {
+            assert_eq!(
+                Security::new(ctx.clone(), timeframe).unwrap_err(),
+                SecurityError::Timeframe(timeframe)
+            );
+        }
+        assert!(Security::new(ctx, Timeframe::Months(1)).is_ok());
+    }
+}
@@ -0,0 +1,437 @@
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::timeframe::Timeframe;
+
+/// Daily trading hours in exchange local time.
+/// `end <= start` is an overnight session that opens on the previous calendar day, like 18:00-17:00 futures.
+/// `00:00-00:00` is open all day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl SessionHours {
+    #[inline]
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    #[inline]
+    pub fn all_day() -> Self {
+        Self::new(NaiveTime::MIN, NaiveTime::MIN)
+    }
+
+    #[inline]
+    pub fn start(&self) -> NaiveTime {
+        self.start
+    }
+
+    #[inline]
+    pub fn end(&self) -> NaiveTime {
+        self.end
+    }
+
+    #[inline]
+    pub fn is_all_day(&self) -> bool {
+        self.start == NaiveTime::MIN && self.end == NaiveTime::MIN
+    }
+
+    #[inline]
+    pub fn is_overnight(&self) -> bool {
+        self.end <= self.start && !self.is_all_day()
+    }
+
+    /// Nominal length, ignoring DST transitions.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        if self.end > self.start {
+            return self.end - self.start;
+        }
+        return Duration::days(1) - (self.start - self.end);
+    }
+
+    /// Local open and close of the session that trades on `date`.
+    #[inline]
+    fn local_bounds(&self, date: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        if self.is_overnight() {
+            return (
+                date.pred_opt().unwrap().and_time(self.start),
+                date.and_time(self.end),
+            );
+        }
+        if self.end <= self.start {
+            return (
+                date.and_time(self.start),
+                date.succ_opt().unwrap().and_time(self.end),
+            );
+        }
+        return (date.and_time(self.start), date.and_time(self.end));
+    }
+}
+
+/// Exchange trading calendar: time zone, regular and extended hours, trading weekdays and holidays.
+/// Timestamps are assigned to the trading date their session ends on.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session {
+    timezone: Tz,
+    regular: SessionHours,
+    #[cfg_attr(feature = "json", serde(default))]
+    extended: Option<SessionHours>,
+    weekdays: Vec<Weekday>,
+    /// Sorted and deduplicated, lookups use binary search.
+    #[cfg_attr(
+        feature = "json",
+        serde(default, deserialize_with = "deserialize_holidays")
+    )]
+    holidays: Vec<NaiveDate>,
+}
+
+#[cfg(feature = "json")]
+fn deserialize_holidays<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<NaiveDate>, D::Error> {
+    let mut holidays: Vec<NaiveDate> = serde::Deserialize::deserialize(deserializer)?;
+    holidays.sort();
+    holidays.dedup();
+    return Ok(holidays);
+}
+
+impl Default for Session {
+    #[inline]
+    fn default() -> Self {
+        Self::always_open()
+    }
+}
+
+impl Session {
+    /// Monday to Friday, no holidays and no extended hours.
+    #[inline]
+    pub fn new(timezone: Tz, regular: SessionHours) -> Self {
+        Self {
+            timezone,
+            regular,
+            extended: None,
+            weekdays: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            holidays: vec![],
+        }
+    }
+
+    /// 24/7 UTC, like crypto.
+    #[inline]
+    pub fn always_open() -> Self {
+        let mut session = Self::new(Tz::UTC, SessionHours::all_day());
+        session.set_weekdays(vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ]);
+        return session;
+    }
+
+    /// NYSE / NASDAQ: 09:30-16:00 New York, extended 04:00-20:00. Holidays need to be added.
+    #[inline]
+    pub fn us_equities() -> Self {
+        let mut session = Self::new(
+            chrono_tz::America::New_York,
+            SessionHours::new(hm(9, 30), hm(16, 0)),
+        );
+        session.set_extended(Some(SessionHours::new(hm(4, 0), hm(20, 0))));
+        return session;
+    }
+
+    /// CME Globex: 17:00-16:00 Chicago, Sunday evening to Friday afternoon.
+    #[inline]
+    pub fn cme_globex() -> Self {
+        Self::new(
+            chrono_tz::America::Chicago,
+            SessionHours::new(hm(17, 0), hm(16, 0)),
+        )
+    }
+
+    /// Spot FX: 17:00-17:00 New York, Sunday evening to Friday afternoon.
+    #[inline]
+    pub fn forex() -> Self {
+        Self::new(
+            chrono_tz::America::New_York,
+            SessionHours::new(hm(17, 0), hm(17, 0)),
+        )
+    }
+
+    /// `always_open`, `us_equities`, `cme_globex` or `forex`.
+    #[inline]
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "always_open" => Some(Self::always_open()),
+            "us_equities" => Some(Self::us_equities()),
+            "cme_globex" => Some(Self::cme_globex()),
+            "forex" => Some(Self::forex()),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    #[inline]
+    pub fn set_timezone(&mut self, timezone: Tz) -> &mut Self {
+        self.timezone = timezone;
+        self
+    }
+
+    #[inline]
+    pub fn regular(&self) -> SessionHours {
+        self.regular
+    }
+
+    #[inline]
+    pub fn set_regular(&mut self, regular: SessionHours) -> &mut Self {
+        self.regular = regular;
+        self
+    }
+
+    /// Pre and post market hours, which must contain the regular ones.
+    #[inline]
+    pub fn extended(&self) -> Option<SessionHours> {
+        self.extended
+    }
+
+    #[inline]
+    pub fn set_extended(&mut self, extended: Option<SessionHours>) -> &mut Self {
+        self.extended = extended;
+        self
+    }
+
+    #[inline]
+    pub fn weekdays(&self) -> &[Weekday] {
+        &self.weekdays
+    }
+
+    #[inline]
+    pub fn set_weekdays(&mut self, weekdays: Vec<Weekday>) -> &mut Self {
+        self.weekdays = weekdays;
+        self
+    }
+
+    #[inline]
+    pub fn holidays(&self) -> &[NaiveDate] {
+        &self.holidays
+    }
+
+    #[inline]
+    pub fn set_holidays(&mut self, mut holidays: Vec<NaiveDate>) -> &mut Self {
+        holidays.sort();
+        holidays.dedup();
+        self.holidays = holidays;
+        self
+    }
+
+    #[inline]
+    pub fn add_holiday(&mut self, date: NaiveDate) -> &mut Self {
+        if let Err(i) = self.holidays.binary_search(&date) {
+            self.holidays.insert(i, date);
+        }
+        self
+    }
+
+    #[inline]
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.binary_search(&date).is_ok()
+    }
+
+    #[inline]
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        self.weekdays.contains(&date.weekday()) && !self.is_holiday(date)
+    }
+
+    #[inline]
+    fn hours(&self, extended: bool) -> SessionHours {
+        if extended {
+            return self.extended.unwrap_or(self.regular);
+        }
+        return self.regular;
+    }
+
+    /// Trading date `time` belongs to. Times after an overnight open belong to the next day.
+    #[inline]
+    pub fn trading_date(&self, time: DateTime<Utc>) -> NaiveDate {
+        let local = time.with_timezone(&self.timezone).naive_local();
+        let hours = self.hours(true);
+        if hours.is_overnight() && local.time() >= hours.start {
+            return local.date().succ_opt().unwrap();
+        }
+        return local.date();
+    }
+
+    /// Open and close of the session trading on `date`, `None` on weekends and holidays.
+    #[inline]
+    pub fn bounds(
+        &self,
+        date: NaiveDate,
+        extended: bool,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.is_trading_day(date) {
+            return None;
+        }
+        let (open, close) = self.hours(extended).local_bounds(date);
+        return Some((self.to_utc(open), self.to_utc(close)));
+    }
+
+    #[inline]
+    pub fn is_open(&self, time: DateTime<Utc>, extended: bool) -> bool {
+        return self
+            .bounds(self.trading_date(time), extended)
+            .is_some_and(|(open, close)| time >= open && time < close);
+    }
+
+    /// Average number of trading days per year: trading weekdays minus the average number of holidays
+    /// falling on them over the years that have any.
+    pub fn trading_days_per_year(&self) -> f64 {
+        let days = self.weekdays.len() as f64 * 365.0 / 7.0;
+        let holidays: Vec<&NaiveDate> = self
+            .holidays
+            .iter()
+            .filter(|d| self.weekdays.contains(&d.weekday()))
+            .collect();
+        if holidays.is_empty() {
+            return days;
+        }
+        let mut years: Vec<i32> = holidays.iter().map(|d| d.year()).collect();
+        years.dedup();
+        return days - holidays.len() as f64 / years.len() as f64;
+    }
+
+    /// Number of `timeframe` bars per year. Intraday bars are counted per session, the last one can be shorter.
+    pub fn annualization_factor(&self, timeframe: Timeframe, extended: bool) -> f64 {
+        let days = self.trading_days_per_year();
+        return match timeframe {
+            Timeframe::Years(n) => 1.0 / n as f64,
+            Timeframe::Months(n) => 12.0 / n as f64,
+            Timeframe::Weeks(n) => 365.0 / 7.0 / n as f64,
+            Timeframe::Days(n) => days / n as f64,
+            Timeframe::Hours(_) | Timeframe::Minutes(_) | Timeframe::Seconds(_) => {
+                let duration: Duration = timeframe.try_into().unwrap();
+                let session = self.hours(extended).duration().num_seconds() as f64;
+                days * (session / duration.num_seconds() as f64).ceil()
+            }
+            _ => f64::NAN,
+        };
+    }
+
+    /// Bar of `timeframe` that `time` falls into, as open and close times.
+    /// Intraday bars are aligned to the session open and cut at its close, daily and longer bars span
+    /// from the open of their first trading day to the close of the last one.
+    /// `None` when `time` is outside of the session or `timeframe` is not time based.
+    pub fn bucket(
+        &self,
+        time: DateTime<Utc>,
+        timeframe: Timeframe,
+        extended: bool,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let date = self.trading_date(time);
+        let (open, close) = self.bounds(date, extended)?;
+        if time < open || time >= close {
+            return None;
+        }
+        let (first, last) = match timeframe {
+            Timeframe::Hours(_) | Timeframe::Minutes(_) | Timeframe::Seconds(_) => {
+                let duration: Duration = timeframe.try_into().unwrap();
+                let n = (time - open).num_seconds() / duration.num_seconds();
+                let start = open + duration * n as i32;
+                return Some((start, (start + duration).min(close)));
+            }
+            Timeframe::Days(n) => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+                let offset = (date - epoch).num_days().rem_euclid(n.max(1) as i64);
+                let first = date - Duration::days(offset);
+                (first, first + Duration::days(n.max(1) as i64 - 1))
+            }
+            Timeframe::Weeks(n) => {
+                let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                // 1970-01-05 is a Monday
+                let anchor = NaiveDate::from_ymd_opt(1970, 1, 5).unwrap();
+                let weeks = (monday - anchor).num_weeks().rem_euclid(n.max(1) as i64);
+                let first = monday - Duration::weeks(weeks);
+                (
+                    first,
+                    first + Duration::weeks(n.max(1) as i64) - Duration::days(1),
+                )
+            }
+            Timeframe::Months(n) => {
+                let months = date.year() * 12 + date.month0() as i32;
+                let start = months - months.rem_euclid(n.max(1) as i32);
+                let end = start + n.max(1) as i32;
+                let first = NaiveDate::from_ymd_opt(
+                    start.div_euclid(12),
+                    start.rem_euclid(12) as u32 + 1,
+                    1,
+                )?;
+                let next =
+                    NaiveDate::from_ymd_opt(end.div_euclid(12), end.rem_euclid(12) as u32 + 1, 1)?;
+                (first, next.pred_opt()?)
+            }
+            Timeframe::Years(n) => {
+                let start = date.year() - date.year().rem_euclid(n.max(1) as i32);
+                (
+                    NaiveDate::from_ymd_opt(start, 1, 1)?,
+                    NaiveDate::from_ymd_opt(start + n.max(1) as i32 - 1, 12, 31)?,
+                )
+            }
+            _ => return None,
+        };
+        let open = first
+            .iter_days()
+            .take_while(|d| *d <= last)
+            .find_map(|d| self.bounds(d, extended))?
+            .0;
+        let mut day = last;
+        let close = loop {
+            if let Some((_, close)) = self.bounds(day, extended) {
+                break close;
+            }
+            day = day.pred_opt()?;
+        };
+        return Some((open, close));
+    }
+
+    #[inline]
+    fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        return local_to_utc(&self.timezone, local);
+    }
+}
+
+/// Local time in `tz` to UTC. Ambiguous times take the earlier instant, times skipped by DST the first valid one after.
+pub(crate) fn local_to_utc(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    let mut local = local;
+    loop {
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => {
+                return dt.with_timezone(&Utc)
+            }
+            LocalResult::None => local += Duration::minutes(1),
+        }
+    }
+}
+
+#[inline]
+fn hm(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
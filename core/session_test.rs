@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+    use crate::{
+        ctx::Ctx,
+        ohlcv::{Ohlcv, OhlcvBar, OhlcvReader, OhlcvReaderOps},
+        session::Session,
+        sym::Sym,
+        timeframe::Timeframe,
+    };
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        return Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap();
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    }
+
+    /// 30 minute bars from 08:00 to 22:00 UTC on the given days.
+    fn bars(days: &[NaiveDate]) -> Ohlcv {
+        let mut bars = vec![];
+        for day in days {
+            let start = Utc.from_utc_datetime(&day.and_hms_opt(8, 0, 0).unwrap());
+            for i in 0..28 {
+                let open_time = start + Duration::minutes(30 * i);
+                let price = 100.0 + i as f64;
+                bars.push(OhlcvBar::new(
+                    Some(open_time),
+                    Some(open_time + Duration::minutes(30)),
+                    price,
+                    price + 0.5,
+                    price - 0.5,
+                    price + 0.25,
+                    1.0,
+                ));
+            }
+        }
+        let mut ohlcv = Ohlcv::from_bars(bars);
+        ohlcv.set_timeframe(Timeframe::Minutes(30));
+        return ohlcv;
+    }
+
+    #[test]
+    pub fn us_equities_bounds_follow_dst() {
+        let session = Session::us_equities();
+        // EST, UTC-5
+        assert_eq!(
+            session.bounds(date(2024, 1, 2), false),
+            Some((utc(2024, 1, 2, 14, 30), utc(2024, 1, 2, 21, 0)))
+        );
+        // EDT, UTC-4
+        assert_eq!(
+            session.bounds(date(2024, 7, 2), false),
+            Some((utc(2024, 7, 2, 13, 30), utc(2024, 7, 2, 20, 0)))
+        );
+        assert_eq!(session.bounds(date(2024, 7, 6), false), None);
+        assert!(session.is_open(utc(2024, 7, 2, 13, 30), false));
+        assert!(!session.is_open(utc(2024, 7, 2, 20, 0), false));
+        assert!(session.is_open(utc(2024, 7, 2, 20, 0), true));
+    }
+
+    #[test]
+    pub fn overnight_trading_date() {
+        let mut session = Session::cme_globex();
+        // Sunday 17:00 Chicago opens Monday
+        assert_eq!(
+            session.trading_date(utc(2024, 7, 7, 22, 0)),
+            date(2024, 7, 8)
+        );
+        assert!(session.is_open(utc(2024, 7, 7, 22, 0), false));
+        assert!(!session.is_open(utc(2024, 7, 8, 21, 30), false));
+        session.add_holiday(date(2024, 7, 4));
+        assert!(!session.is_open(utc(2024, 7, 4, 15, 0), false));
+    }
+
+    #[test]
+    pub fn trading_days_per_year() {
+        assert_eq!(Session::always_open().trading_days_per_year(), 365.0);
+        let mut session = Session::us_equities();
+        session.set_holidays(vec![
+            date(2023, 12, 25),
+            date(2024, 1, 1),
+            date(2024, 7, 4),
+            // Saturday
+            date(2023, 7, 8),
+        ]);
+        let expected = 5.0 * 365.0 / 7.0 - 3.0 / 2.0;
+        assert!((session.trading_days_per_year() - expected).abs() < 1e-9);
+
+        #[cfg(feature = "json")]
+        {
+            // catalogs may list holidays in any order
+            let mut json = serde_json::to_value(Session::us_equities()).unwrap();
+            json["holidays"] =
+                serde_json::json!(["2024-07-04", "2023-12-25", "2024-01-01", "2024-07-04"]);
+            let session: Session = serde_json::from_value(json).unwrap();
+            assert_eq!(
+                session.holidays(),
+                &[date(2023, 12, 25), date(2024, 1, 1), date(2024, 7, 4)]
+            );
+            assert!(session.is_holiday(date(2024, 7, 4)));
+            assert!(session.is_holiday(date(2023, 12, 25)));
+        }
+    }
+
+    #[test]
+    pub fn resample_daily_closes_at_session_close() {
+        let session = Session::us_equities();
+        // Friday, Saturday, Monday
+        let ohlcv = bars(&[date(2024, 7, 5), date(2024, 7, 6), date(2024, 7, 8)]);
+        let daily = ohlcv
+            .resample_session(Timeframe::Days(1), &session, false)
+            .unwrap();
+        assert_eq!(daily.len(), 2);
+        let bar = daily.get(0).unwrap();
+        assert_eq!(bar.open_time(), Some(&utc(2024, 7, 5, 13, 30)));
+        assert_eq!(bar.close_time(), Some(&utc(2024, 7, 5, 20, 0)));
+        // 13:30 is bar 11, 19:30 bar 23
+        assert_eq!(bar.open(), 111.0);
+        assert_eq!(bar.close(), 123.25);
+        assert_eq!(bar.volume(), 13.0);
+
+        let hourly = ohlcv
+            .resample_session(Timeframe::Hours(1), &session, false)
+            .unwrap();
+        assert_eq!(hourly.len(), 14);
+        assert_eq!(
+            hourly.get(6).unwrap().open_time(),
+            Some(&utc(2024, 7, 5, 19, 30))
+        );
+        assert_eq!(
+            hourly.get(6).unwrap().close_time(),
+            Some(&utc(2024, 7, 5, 20, 0))
+        );
+
+        let weekly = ohlcv
+            .resample_session(Timeframe::Weeks(1), &session, true)
+            .unwrap();
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(
+            weekly.get(0).unwrap().open_time(),
+            Some(&utc(2024, 7, 1, 8, 0))
+        );
+        assert_eq!(
+            weekly.get(0).unwrap().close_time(),
+            Some(&utc(2024, 7, 6, 0, 0))
+        );
+    }
+
+    #[test]
+    pub fn resample_aligns_in_timezone() {
+        let mut ohlcv = bars(&[date(2024, 7, 5), date(2024, 7, 6), date(2024, 7, 8)]);
+        ohlcv.set_timezone(chrono_tz::Asia::Tokyo);
+        // Monday 00:00 in Tokyo is Sunday 15:00 UTC
+        let weekly = ohlcv.resample(Timeframe::Weeks(1), true).unwrap();
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(weekly.timezone(), chrono_tz::Asia::Tokyo);
+        assert_eq!(
+            weekly.get(0).unwrap().open_time(),
+            Some(&utc(2024, 6, 30, 15, 0))
+        );
+        assert_eq!(
+            weekly.get(1).unwrap().open_time(),
+            Some(&utc(2024, 7, 7, 15, 0))
+        );
+
+        ohlcv.set_timezone(chrono_tz::America::New_York);
+        let daily = ohlcv.resample(Timeframe::Days(1), true).unwrap();
+        assert_eq!(
+            daily.get(0).unwrap().open_time(),
+            Some(&utc(2024, 7, 5, 4, 0))
+        );
+        assert_eq!(
+            daily.get(0).unwrap().close_time(),
+            Some(&utc(2024, 7, 6, 4, 0))
+        );
+    }
+
+    #[test]
+    pub fn ctx_session_helpers() {
+        let ohlcv = bars(&[date(2024, 7, 5), date(2024, 7, 8)]);
+        let mut sym = Sym::btc_usd();
+        sym.set_session(Some(Session::us_equities()));
+        let mut ctx = Ctx::new();
+        ctx.set_ohlcv(ohlcv.into_box());
+        ctx.set_sym(sym);
+        let mut open = 0;
+        let mut first = vec![];
+        let mut last = vec![];
+        while let Some(bar_index) = ctx.next() {
+            if ctx.is_session_open() {
+                open += 1;
+            }
+            if ctx.is_first_bar_of_session() {
+                first.push(bar_index);
+            }
+            if ctx.is_last_bar_of_session() {
+                last.push(bar_index);
+            }
+        }
+        assert_eq!(open, 26);
+        assert_eq!(first, vec![0, 28]);
+        // the last bar closes at 22:00 UTC, before the extended close
+        assert_eq!(last, vec![27]);
+        let factor = ctx.annualization_factor();
+        assert!((factor - 5.0 * 365.0 / 7.0 * 13.0).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,9 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use thiserror::Error;
+
+use crate::{metrics::annualization_factor, session::Session, timeframe::Timeframe};
+
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "json", serde(try_from = "String", into = "String"))]
@@ -64,6 +70,175 @@ impl From<String> for SymKind {
     }
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum SymError {
+    #[error("{sym}: {field} must be a positive number, got {value}")]
+    NotPositive {
+        sym: String,
+        field: &'static str,
+        value: f64,
+    },
+    #[error("{sym}: {field} fee must be a non-negative number, got {value}")]
+    InvalidFee {
+        sym: String,
+        field: &'static str,
+        value: f64,
+    },
+    #[error("{sym}: {message}")]
+    InvalidDerivative { sym: String, message: String },
+}
+
+/// Trading costs. `maker` and `taker` are fractions of the traded notional, `per_qty` is charged per unit of quantity
+/// in the quote currency. Backtest orders fill as market orders and pay `taker`, `maker` is informational.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(default))]
+pub struct SymFees {
+    pub maker: f64,
+    pub taker: f64,
+    pub per_qty: f64,
+}
+
+impl SymFees {
+    /// Taker fee of a market fill of `qty` at `price`.
+    #[inline]
+    pub fn fill_fee(&self, qty: f64, price: f64) -> f64 {
+        return qty.abs() * (price * self.taker + self.per_qty);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "lowercase"))]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl Into<String> for OptionType {
+    #[inline]
+    fn into(self) -> String {
+        match self {
+            OptionType::Call => "call".to_string(),
+            OptionType::Put => "put".to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for OptionType {
+    type Error = String;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "call" | "c" => Ok(OptionType::Call),
+            "put" | "p" => Ok(OptionType::Put),
+            _ => Err(format!("Invalid option type {:?}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "lowercase"))]
+pub enum SettlementType {
+    #[default]
+    Cash,
+    Physical,
+}
+
+impl Into<String> for SettlementType {
+    #[inline]
+    fn into(self) -> String {
+        match self {
+            SettlementType::Cash => "cash".to_string(),
+            SettlementType::Physical => "physical".to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for SettlementType {
+    type Error = String;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "cash" => Ok(SettlementType::Cash),
+            "physical" => Ok(SettlementType::Physical),
+            _ => Err(format!("Invalid settlement type {:?}", value)),
+        }
+    }
+}
+
+/// Contract specification of futures and options.
+/// At `expiry` a backtest closes all open trades: cash settled contracts at `settlement_price`, or the last close if it
+/// is unknown, physically settled ones at the last close.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(default))]
+pub struct SymDerivative {
+    pub expiry: Option<DateTime<Utc>>,
+    /// Id of the underlying sym.
+    pub underlying: Option<String>,
+    pub strike: Option<f64>,
+    pub option_type: Option<OptionType>,
+    /// Units of the underlying per contract, the point value of a sym without one.
+    pub multiplier: f64,
+    pub settlement: SettlementType,
+    pub settlement_price: Option<f64>,
+}
+
+impl Default for SymDerivative {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            expiry: None,
+            underlying: None,
+            strike: None,
+            option_type: None,
+            multiplier: 1.0,
+            settlement: SettlementType::default(),
+            settlement_price: None,
+        }
+    }
+}
+
+impl SymDerivative {
+    #[inline]
+    pub fn future(expiry: DateTime<Utc>, underlying: Option<String>, multiplier: f64) -> Self {
+        Self {
+            expiry: Some(expiry),
+            underlying,
+            multiplier,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn option(
+        expiry: DateTime<Utc>,
+        underlying: Option<String>,
+        strike: f64,
+        option_type: OptionType,
+        multiplier: f64,
+    ) -> Self {
+        Self {
+            expiry: Some(expiry),
+            underlying,
+            strike: Some(strike),
+            option_type: Some(option_type),
+            multiplier,
+            ..Default::default()
+        }
+    }
+
+    /// Whether a bar ending at `time` trades at or after expiry.
+    #[inline]
+    pub fn is_expired(&self, time: &DateTime<Utc>) -> bool {
+        self.expiry.is_some_and(|expiry| *time >= expiry)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sym {
@@ -80,6 +255,14 @@ pub struct Sym {
     price_scale: f64,
     point_value: f64,
     metadata: Option<String>,
+    #[cfg_attr(feature = "json", serde(default))]
+    timezone: Option<Tz>,
+    #[cfg_attr(feature = "json", serde(default))]
+    session: Option<Session>,
+    #[cfg_attr(feature = "json", serde(default))]
+    fees: SymFees,
+    #[cfg_attr(feature = "json", serde(default))]
+    derivative: Option<SymDerivative>,
 }
 
 impl PartialEq for Sym {
@@ -109,6 +292,10 @@ impl Default for Sym {
             price_scale: f64::NAN,
             point_value: f64::NAN,
             metadata: None,
+            timezone: None,
+            session: None,
+            fees: SymFees::default(),
+            derivative: None,
         }
     }
 }
@@ -240,9 +427,13 @@ impl Sym {
         self
     }
 
+    /// Quote currency per point of price and contract. Falls back to the derivative multiplier when unset (NaN).
     #[inline]
     pub fn point_value(&self) -> f64 {
-        self.point_value
+        return match &self.derivative {
+            Some(derivative) if self.point_value.is_nan() => derivative.multiplier,
+            _ => self.point_value,
+        };
     }
 
     #[inline]
@@ -261,6 +452,150 @@ impl Sym {
         self.metadata = metadata;
     }
 
+    /// Exchange time zone, falls back to the session one.
+    #[inline]
+    pub fn timezone(&self) -> Option<Tz> {
+        self.timezone
+            .or_else(|| self.session.as_ref().map(|s| s.timezone()))
+    }
+
+    #[inline]
+    pub fn set_timezone(&mut self, timezone: Option<Tz>) -> &mut Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Trading calendar. Without one the symbol trades around the clock in UTC.
+    #[inline]
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    #[inline]
+    pub fn set_session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    #[inline]
+    pub fn fees(&self) -> &SymFees {
+        &self.fees
+    }
+
+    #[inline]
+    pub fn set_fees(&mut self, fees: SymFees) -> &mut Self {
+        self.fees = fees;
+        self
+    }
+
+    #[inline]
+    pub fn derivative(&self) -> Option<&SymDerivative> {
+        self.derivative.as_ref()
+    }
+
+    #[inline]
+    pub fn set_derivative(&mut self, derivative: Option<SymDerivative>) -> &mut Self {
+        self.derivative = derivative;
+        self
+    }
+
+    /// Creates a default derivative spec if there is none.
+    #[inline]
+    pub fn derivative_mut(&mut self) -> &mut SymDerivative {
+        self.derivative.get_or_insert_with(SymDerivative::default)
+    }
+
+    #[inline]
+    pub fn expiry(&self) -> Option<&DateTime<Utc>> {
+        self.derivative.as_ref().and_then(|d| d.expiry.as_ref())
+    }
+
+    /// Checks what a backtest needs: positive `min_tick` and `min_qty`. `price_scale` and `point_value` may be unset (NaN)
+    /// but otherwise have to be positive, fees can't be negative and options need a strike and an option type.
+    pub fn validate(&self) -> Result<(), SymError> {
+        let sym = || self.id().or(self.ticker_id()).unwrap_or("?").to_string();
+        for (field, value, required) in [
+            ("min_tick", self.min_tick, true),
+            ("min_qty", self.min_qty, true),
+            ("price_scale", self.price_scale, false),
+            ("point_value", self.point_value, false),
+        ] {
+            if (required || !value.is_nan()) && !(value.is_finite() && value > 0.0) {
+                return Err(SymError::NotPositive {
+                    sym: sym(),
+                    field,
+                    value,
+                });
+            }
+        }
+        for (field, value) in [
+            ("maker", self.fees.maker),
+            ("taker", self.fees.taker),
+            ("per_qty", self.fees.per_qty),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(SymError::InvalidFee {
+                    sym: sym(),
+                    field,
+                    value,
+                });
+            }
+        }
+        if let Some(derivative) = &self.derivative {
+            let invalid = |message: &str| {
+                Err(SymError::InvalidDerivative {
+                    sym: sym(),
+                    message: message.to_string(),
+                })
+            };
+            if !(derivative.multiplier.is_finite() && derivative.multiplier > 0.0) {
+                return invalid("multiplier must be a positive number");
+            }
+            if derivative
+                .strike
+                .is_some_and(|x| !(x.is_finite() && x >= 0.0))
+            {
+                return invalid("strike must be a non-negative number");
+            }
+            if derivative.strike.is_some() != derivative.option_type.is_some() {
+                return invalid("strike and option type must be set together");
+            }
+            if derivative.settlement_price.is_some_and(|x| !x.is_finite()) {
+                return invalid("settlement price must be finite");
+            }
+        }
+        if self.kind == SymKind::Option
+            && self
+                .derivative
+                .as_ref()
+                .is_none_or(|d| d.option_type.is_none())
+        {
+            return Err(SymError::InvalidDerivative {
+                sym: sym(),
+                message: "options need a strike and an option type".to_string(),
+            });
+        }
+        return Ok(());
+    }
+
+    /// From the session if there is one, otherwise `SymKind::periods`.
+    #[inline]
+    pub fn trading_days_per_year(&self) -> f64 {
+        match &self.session {
+            Some(session) => session.trading_days_per_year(),
+            None => self.kind.periods(),
+        }
+    }
+
+    /// Number of `timeframe` bars per year in the regular session.
+    #[inline]
+    pub fn annualization_factor(&self, timeframe: Timeframe) -> f64 {
+        match &self.session {
+            Some(session) => session.annualization_factor(timeframe, false),
+            None => annualization_factor(timeframe, self.kind.periods()),
+        }
+    }
+
     #[inline]
     pub fn qty_scale(&self) -> f64 {
         assert!(self.min_qty > 0.0, "min_qty must be greater than 0.0");
@@ -1,4 +1,5 @@
 use crate::sym::{Sym, SymKind};
+use chrono_tz::Tz;
 use napi::bindgen_prelude::*;
 use napi::{Error, Result, Status};
 use napi_derive::napi;
@@ -245,6 +246,21 @@ impl NodeSym {
         self.inner.set_metadata(v);
     }
 
+    #[napi(getter = timezone)]
+    pub fn node_timezone(&self) -> Option<String> {
+        self.inner.timezone().map(|tz| tz.name().to_string())
+    }
+
+    #[napi(setter = timezone)]
+    pub fn node_set_timezone(&mut self, v: Option<String>) -> Result<()> {
+        let timezone = v
+            .map(|tz| tz.parse::<Tz>())
+            .transpose()
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+        self.inner.set_timezone(timezone);
+        Ok(())
+    }
+
     #[napi(getter = qtyScale)]
     pub fn node_qty_scale(&self) -> f64 {
         self.inner.qty_scale()
@@ -1,4 +1,7 @@
-use crate::sym::{Sym, SymKind};
+use crate::sym::{OptionType, SettlementType, Sym, SymKind};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
@@ -371,6 +374,121 @@ impl PySym {
         self.inner.set_metadata(metadata);
     }
 
+    #[getter(timezone)]
+    #[inline]
+    pub fn py_timezone(&self) -> Option<String> {
+        self.inner.timezone().map(|tz| tz.name().to_string())
+    }
+
+    #[setter(timezone)]
+    #[inline]
+    pub fn py_set_timezone(&mut self, timezone: Option<String>) -> PyResult<()> {
+        let timezone = timezone
+            .map(|tz| tz.parse::<Tz>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.inner.set_timezone(timezone);
+        Ok(())
+    }
+
+    #[getter(expiry)]
+    #[inline]
+    pub fn py_expiry(&self) -> Option<DateTime<Utc>> {
+        self.inner.expiry().cloned()
+    }
+
+    #[setter(expiry)]
+    #[inline]
+    pub fn py_set_expiry(&mut self, expiry: Option<DateTime<Utc>>) {
+        self.inner.derivative_mut().expiry = expiry;
+    }
+
+    #[getter(underlying)]
+    #[inline]
+    pub fn py_underlying(&self) -> Option<String> {
+        self.inner.derivative().and_then(|d| d.underlying.clone())
+    }
+
+    #[setter(underlying)]
+    #[inline]
+    pub fn py_set_underlying(&mut self, underlying: Option<String>) {
+        self.inner.derivative_mut().underlying = underlying;
+    }
+
+    #[getter(strike)]
+    #[inline]
+    pub fn py_strike(&self) -> Option<f64> {
+        self.inner.derivative().and_then(|d| d.strike)
+    }
+
+    #[setter(strike)]
+    #[inline]
+    pub fn py_set_strike(&mut self, strike: Option<f64>) {
+        self.inner.derivative_mut().strike = strike;
+    }
+
+    #[getter(option_type)]
+    #[inline]
+    pub fn py_option_type(&self) -> Option<String> {
+        self.inner
+            .derivative()
+            .and_then(|d| d.option_type)
+            .map(|x| x.into())
+    }
+
+    #[setter(option_type)]
+    #[inline]
+    pub fn py_set_option_type(&mut self, option_type: Option<String>) -> PyResult<()> {
+        let option_type = option_type
+            .map(|x| OptionType::try_from(x.as_str()))
+            .transpose()
+            .map_err(PyValueError::new_err)?;
+        self.inner.derivative_mut().option_type = option_type;
+        Ok(())
+    }
+
+    #[getter(multiplier)]
+    #[inline]
+    pub fn py_multiplier(&self) -> f64 {
+        self.inner.derivative().map_or(1.0, |d| d.multiplier)
+    }
+
+    #[setter(multiplier)]
+    #[inline]
+    pub fn py_set_multiplier(&mut self, multiplier: f64) {
+        self.inner.derivative_mut().multiplier = multiplier;
+    }
+
+    #[getter(settlement)]
+    #[inline]
+    pub fn py_settlement(&self) -> String {
+        self.inner
+            .derivative()
+            .map(|d| d.settlement)
+            .unwrap_or_default()
+            .into()
+    }
+
+    #[setter(settlement)]
+    #[inline]
+    pub fn py_set_settlement(&mut self, settlement: String) -> PyResult<()> {
+        self.inner.derivative_mut().settlement =
+            SettlementType::try_from(settlement.as_str()).map_err(PyValueError::new_err)?;
+        Ok(())
+    }
+
+    #[getter(settlement_price)]
+    #[inline]
+    pub fn py_settlement_price(&self) -> Option<f64> {
+        self.inner.derivative().and_then(|d| d.settlement_price)
+    }
+
+    #[setter(settlement_price)]
+    #[inline]
+    pub fn py_set_settlement_price(&mut self, settlement_price: Option<f64>) {
+        self.inner.derivative_mut().settlement_price = settlement_price;
+    }
+
     #[getter(qty_scale)]
     #[inline]
     pub fn py_qty_scale(&self) -> f64 {
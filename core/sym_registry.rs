@@ -0,0 +1,265 @@
+use std::{collections::HashMap, path::Path};
+
+use chrono_tz::Tz;
+use thiserror::Error;
+
+use crate::{
+    session::Session,
+    sym::{Sym, SymDerivative, SymError, SymFees, SymKind},
+};
+
+#[derive(Debug, Error)]
+pub enum SymRegistryError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "toml")]
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[error("Unsupported catalog format {0:?}")]
+    UnsupportedFormat(String),
+    #[error("Sym spec #{0} has no id, ticker_id or ticker")]
+    MissingId(usize),
+    #[error("Unknown session preset {0:?}")]
+    UnknownSession(String),
+    #[error("Duplicate sym {0:?}")]
+    Duplicate(String),
+    #[error(transparent)]
+    Invalid(#[from] SymError),
+}
+
+/// Session preset name, see `Session::preset`, or a full session.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum SymSessionSpec {
+    Preset(String),
+    Custom(Session),
+}
+
+/// Catalog entry. Only `min_tick`, `min_qty` and one of `id`, `ticker_id` or `ticker` are required:
+/// - `ticker_id` defaults to `prefix:ticker`, `prefix` and `ticker` are split from it otherwise
+/// - `id` defaults to `ticker_id`
+/// - `price_scale` defaults to `1 / min_tick` and `point_value` to the derivative multiplier or `1`
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SymSpec {
+    pub id: Option<String>,
+    pub ticker_id: Option<String>,
+    pub prefix: Option<String>,
+    pub ticker: Option<String>,
+    pub kind: SymKind,
+    pub min_tick: Option<f64>,
+    pub min_qty: Option<f64>,
+    pub price_scale: Option<f64>,
+    pub point_value: Option<f64>,
+    pub currency: Option<String>,
+    pub base_currency: Option<String>,
+    pub country: Option<String>,
+    pub timezone: Option<Tz>,
+    pub session: Option<SymSessionSpec>,
+    pub fees: SymFees,
+    pub derivative: Option<SymDerivative>,
+    pub metadata: Option<String>,
+}
+
+impl SymSpec {
+    /// `index` is only used for error messages.
+    pub fn to_sym(&self, index: usize) -> Result<Sym, SymRegistryError> {
+        let ticker_id = self
+            .ticker_id
+            .clone()
+            .or_else(|| match (&self.prefix, &self.ticker) {
+                (Some(prefix), Some(ticker)) => Some(format!("{}:{}", prefix, ticker)),
+                _ => None,
+            });
+        let (prefix, ticker) = match ticker_id.as_deref().and_then(|x| x.split_once(':')) {
+            Some((prefix, ticker)) => (
+                self.prefix.clone().or(Some(prefix.to_string())),
+                self.ticker.clone().or(Some(ticker.to_string())),
+            ),
+            None => (self.prefix.clone(), self.ticker.clone()),
+        };
+        let id = self
+            .id
+            .clone()
+            .or_else(|| ticker_id.clone())
+            .or_else(|| ticker.clone())
+            .ok_or(SymRegistryError::MissingId(index))?;
+        let session = match &self.session {
+            Some(SymSessionSpec::Preset(name)) => Some(
+                Session::preset(name)
+                    .ok_or_else(|| SymRegistryError::UnknownSession(name.clone()))?,
+            ),
+            Some(SymSessionSpec::Custom(session)) => Some(session.clone()),
+            None => None,
+        };
+        let min_tick = self.min_tick.unwrap_or(f64::NAN);
+        let mut sym = Sym::default();
+        sym.set_id(Some(id));
+        sym.set_ticker_id(ticker_id);
+        sym.set_prefix(prefix);
+        sym.set_ticker(ticker);
+        sym.set_kind(self.kind.clone());
+        sym.set_min_tick(min_tick);
+        sym.set_min_qty(self.min_qty.unwrap_or(f64::NAN));
+        sym.set_price_scale(
+            self.price_scale
+                .unwrap_or_else(|| (1.0 / min_tick).round().max(1.0)),
+        );
+        sym.set_point_value(
+            self.point_value
+                .unwrap_or_else(|| self.derivative.as_ref().map_or(1.0, |d| d.multiplier)),
+        );
+        sym.set_currency(self.currency.clone());
+        sym.set_base_currency(self.base_currency.clone());
+        sym.set_country(self.country.clone());
+        sym.set_timezone(self.timezone);
+        sym.set_session(session);
+        sym.set_fees(self.fees);
+        sym.set_derivative(self.derivative.clone());
+        sym.set_metadata(self.metadata.clone());
+        sym.validate()?;
+        return Ok(sym);
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SymCatalog {
+    syms: Vec<SymSpec>,
+}
+
+/// Validated symbols, unique by `id` and `ticker_id`.
+/// JSON catalogs are a list of specs or `{ "syms": [...] }`, TOML catalogs use `[[syms]]` tables.
+#[derive(Debug, Clone, Default)]
+pub struct SymRegistry {
+    syms: Vec<Sym>,
+    by_id: HashMap<String, usize>,
+    by_ticker_id: HashMap<String, usize>,
+}
+
+impl SymRegistry {
+    #[inline]
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// `Sym::btc_usd`, `eth_usd`, `sol_usd` and `doge_usd`.
+    pub fn with_presets() -> Self {
+        let mut registry = Self::new();
+        for sym in [
+            Sym::btc_usd(),
+            Sym::eth_usd(),
+            Sym::sol_usd(),
+            Sym::doge_usd(),
+        ] {
+            registry.insert(sym).unwrap();
+        }
+        return registry;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        return self.syms.len();
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.syms.is_empty();
+    }
+
+    #[inline]
+    pub fn syms(&self) -> &[Sym] {
+        return &self.syms;
+    }
+
+    /// Validates the sym, fails on an `id` or `ticker_id` that is already registered.
+    pub fn insert(&mut self, sym: Sym) -> Result<(), SymRegistryError> {
+        sym.validate()?;
+        let id = sym
+            .id()
+            .or(sym.ticker_id())
+            .ok_or(SymRegistryError::MissingId(self.syms.len()))?
+            .to_string();
+        if self.by_id.contains_key(&id) {
+            return Err(SymRegistryError::Duplicate(id));
+        }
+        if let Some(ticker_id) = sym.ticker_id() {
+            if self.by_ticker_id.contains_key(ticker_id) {
+                return Err(SymRegistryError::Duplicate(ticker_id.to_string()));
+            }
+            self.by_ticker_id
+                .insert(ticker_id.to_string(), self.syms.len());
+        }
+        self.by_id.insert(id, self.syms.len());
+        self.syms.push(sym);
+        return Ok(());
+    }
+
+    /// Adds all specs of the catalog or none of them.
+    pub fn extend(&mut self, specs: &[SymSpec]) -> Result<(), SymRegistryError> {
+        let mut registry = self.clone();
+        for (i, spec) in specs.iter().enumerate() {
+            registry.insert(spec.to_sym(i)?)?;
+        }
+        *self = registry;
+        return Ok(());
+    }
+
+    pub fn load_json(&mut self, json: &str) -> Result<(), SymRegistryError> {
+        let specs: Vec<SymSpec> = if json.trim_start().starts_with('[') {
+            serde_json::from_str(json)?
+        } else {
+            serde_json::from_str::<SymCatalog>(json)?.syms
+        };
+        return self.extend(&specs);
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn load_toml(&mut self, toml: &str) -> Result<(), SymRegistryError> {
+        let catalog: SymCatalog = toml::from_str(toml)?;
+        return self.extend(&catalog.syms);
+    }
+
+    /// Format from the `json` or `toml` extension.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<(), SymRegistryError> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let content = std::fs::read_to_string(path)?;
+        match ext.as_str() {
+            "json" => return self.load_json(&content),
+            #[cfg(feature = "toml")]
+            "toml" => return self.load_toml(&content),
+            _ => return Err(SymRegistryError::UnsupportedFormat(ext)),
+        }
+    }
+
+    #[inline]
+    pub fn by_id(&self, id: &str) -> Option<&Sym> {
+        return self.by_id.get(id).map(|&i| &self.syms[i]);
+    }
+
+    #[inline]
+    pub fn by_ticker_id(&self, ticker_id: &str) -> Option<&Sym> {
+        return self.by_ticker_id.get(ticker_id).map(|&i| &self.syms[i]);
+    }
+
+    /// By `id`, then by `ticker_id`.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Sym> {
+        return self.by_id(key).or_else(|| self.by_ticker_id(key));
+    }
+
+    /// All syms of an exchange prefix, case insensitive, in insertion order.
+    pub fn by_prefix(&self, prefix: &str) -> Vec<&Sym> {
+        return self
+            .syms
+            .iter()
+            .filter(|sym| sym.prefix().is_some_and(|x| x.eq_ignore_ascii_case(prefix)))
+            .collect();
+    }
+}
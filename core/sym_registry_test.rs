@@ -0,0 +1,146 @@
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        backtest::{Backtest, BacktestConfig},
+        ctx::Ctx,
+        session::Session,
+        sym::{Sym, SymError, SymKind},
+        sym_registry::{SymRegistry, SymRegistryError},
+    };
+
+    const CATALOG: &str = r#"{
+        "syms": [
+            {
+                "ticker_id": "CME:ES",
+                "kind": "future",
+                "min_tick": 0.25,
+                "min_qty": 1,
+                "point_value": 50,
+                "currency": "USD",
+                "session": "cme_globex",
+                "fees": { "per_qty": 2.5 }
+            },
+            {
+                "id": "aapl",
+                "prefix": "NASDAQ",
+                "ticker": "AAPL",
+                "kind": "stock",
+                "min_tick": 0.01,
+                "min_qty": 1,
+                "fees": { "taker": 0.0005 }
+            }
+        ]
+    }"#;
+
+    #[test]
+    pub fn load_and_lookup() {
+        let mut registry = SymRegistry::with_presets();
+        registry.load_json(CATALOG).unwrap();
+        assert_eq!(registry.len(), 6);
+
+        let es = registry.get("CME:ES").unwrap();
+        assert_eq!(es.id(), Some("CME:ES"));
+        assert_eq!((es.prefix(), es.ticker()), (Some("CME"), Some("ES")));
+        assert_eq!(es.kind(), &SymKind::Future);
+        assert_eq!((es.point_value(), es.price_scale()), (50.0, 4.0));
+        assert_eq!(es.session(), Some(&Session::cme_globex()));
+        assert_eq!(es.fees().per_qty, 2.5);
+
+        let aapl = registry.by_ticker_id("NASDAQ:AAPL").unwrap();
+        assert_eq!(registry.get("aapl").unwrap().ticker_id(), aapl.ticker_id());
+        assert_eq!(aapl.price_scale(), 100.0);
+        assert_eq!(registry.by_prefix("binance").len(), 2);
+        assert!(registry.get("NASDAQ:MSFT").is_none());
+
+        // duplicates are rejected and leave the registry untouched
+        assert!(matches!(
+            registry.load_json(CATALOG),
+            Err(SymRegistryError::Duplicate(id)) if id == "CME:ES"
+        ));
+        assert_eq!(registry.len(), 6);
+    }
+
+    #[test]
+    pub fn rejects_invalid_specs() {
+        let mut registry = SymRegistry::new();
+        assert!(matches!(
+            registry.load_json(r#"[{ "ticker_id": "X:Y", "min_tick": 0.1 }]"#),
+            Err(SymRegistryError::Invalid(SymError::NotPositive {
+                field: "min_qty",
+                ..
+            }))
+        ));
+        assert!(matches!(
+            registry.load_json(r#"[{ "kind": "stock", "min_tick": 0.1, "min_qty": 1 }]"#),
+            Err(SymRegistryError::MissingId(0))
+        ));
+        assert!(matches!(
+            registry.load_json(
+                r#"[{ "ticker": "Y", "min_tick": 0.1, "min_qty": 1, "session": "lse" }]"#
+            ),
+            Err(SymRegistryError::UnknownSession(_))
+        ));
+        assert!(matches!(
+            registry.load_json(
+                r#"[{ "ticker": "Y", "min_tick": 0.1, "min_qty": 1, "fees": { "maker": -1 } }]"#
+            ),
+            Err(SymRegistryError::Invalid(SymError::InvalidFee {
+                field: "maker",
+                ..
+            }))
+        ));
+        assert!(registry.is_empty());
+
+        let mut ctx = Ctx::new();
+        ctx.set_sym(Sym::default());
+        let error = Backtest::try_new(Rc::new(RefCell::new(ctx)), BacktestConfig::default())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            SymError::NotPositive {
+                field: "min_tick",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    pub fn load_toml() {
+        let mut registry = SymRegistry::new();
+        registry
+            .load_toml(
+                r#"
+                [[syms]]
+                ticker_id = "OANDA:EURUSD"
+                kind = "forex"
+                min_tick = 0.00001
+                min_qty = 1000
+                session = "forex"
+
+                [[syms]]
+                ticker_id = "NYSE:IBM"
+                min_tick = 0.01
+                min_qty = 1
+
+                [syms.session]
+                timezone = "America/New_York"
+                regular = { start = "09:30:00", end = "16:00:00" }
+                weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri"]
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            registry.get("OANDA:EURUSD").unwrap().price_scale(),
+            100000.0
+        );
+        assert_eq!(
+            registry.get("NYSE:IBM").unwrap().timezone(),
+            Some(chrono_tz::America::New_York)
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::sym::{Sym, SymKind};
+use chrono_tz::Tz;
 use js_sys::{Object, Reflect};
 use wasm_bindgen::prelude::*;
 
@@ -409,6 +410,23 @@ impl WasmSym {
         self.inner.set_metadata(metadata);
     }
 
+    #[wasm_bindgen(getter = timezone)]
+    #[inline]
+    pub fn wasm_timezone(&self) -> Option<String> {
+        self.inner.timezone().map(|tz| tz.name().to_string())
+    }
+
+    #[wasm_bindgen(setter = timezone)]
+    #[inline]
+    pub fn wasm_set_timezone(&mut self, timezone: Option<String>) -> Result<(), JsError> {
+        let timezone = timezone
+            .map(|tz| tz.parse::<Tz>())
+            .transpose()
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        self.inner.set_timezone(timezone);
+        Ok(())
+    }
+
     #[wasm_bindgen(getter = qtyScale)]
     #[inline]
     pub fn wasm_qty_scale(&self) -> f64 {
@@ -0,0 +1,11 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+/// Time fixture, `minutes` after 2024-01-01 UTC.
+pub fn time(minutes: i64) -> DateTime<Utc> {
+    return time_seconds(minutes * 60);
+}
+
+/// Time fixture, `seconds` after 2024-01-01 UTC.
+pub fn time_seconds(seconds: i64) -> DateTime<Utc> {
+    return Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(seconds);
+}
@@ -0,0 +1,473 @@
+use chrono::{DateTime, Utc};
+cfg_if::cfg_if! { if #[cfg(feature = "polars")] {
+    use polars::frame::DataFrame;
+    use polars::series::Series;
+    use polars::error::PolarsError;
+    use polars::prelude::NamedFrom;
+    use std::path::Path;
+    use crate::ohlcv::OhlcvReader;
+    use crate::utils::{read_df, SeriesCastUtils};
+    use crate::ohlcv_io::{polars_time, OhlcvTimeFormat};
+}}
+
+use crate::{
+    bar_builder::{BarBuilder, BarBuilderError, BarStats},
+    ohlcv::Ohlcv,
+    timeframe::Timeframe,
+};
+
+/// Single trade print.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tick {
+    time: DateTime<Utc>,
+    price: f64,
+    volume: f64,
+}
+
+impl Tick {
+    #[inline]
+    pub fn new(time: DateTime<Utc>, price: f64, volume: f64) -> Self {
+        Self {
+            time,
+            price,
+            volume,
+        }
+    }
+
+    #[inline]
+    pub fn time(&self) -> &DateTime<Utc> {
+        &self.time
+    }
+
+    #[inline]
+    pub fn price(&self) -> f64 {
+        self.price
+    }
+
+    #[inline]
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+}
+
+/// Top of book quote. Sizes are `NaN` when unknown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    time: DateTime<Utc>,
+    bid: f64,
+    ask: f64,
+    bid_size: f64,
+    ask_size: f64,
+}
+
+impl Quote {
+    #[inline]
+    pub fn new(time: DateTime<Utc>, bid: f64, ask: f64, bid_size: f64, ask_size: f64) -> Self {
+        Self {
+            time,
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+        }
+    }
+
+    #[inline]
+    pub fn time(&self) -> &DateTime<Utc> {
+        &self.time
+    }
+
+    #[inline]
+    pub fn bid(&self) -> f64 {
+        self.bid
+    }
+
+    #[inline]
+    pub fn ask(&self) -> f64 {
+        self.ask
+    }
+
+    #[inline]
+    pub fn bid_size(&self) -> f64 {
+        self.bid_size
+    }
+
+    #[inline]
+    pub fn ask_size(&self) -> f64 {
+        self.ask_size
+    }
+
+    #[inline]
+    pub fn mid(&self) -> f64 {
+        return (self.bid + self.ask) / 2.0;
+    }
+
+    #[inline]
+    pub fn spread(&self) -> f64 {
+        return self.ask - self.bid;
+    }
+}
+
+/// Columnar storage of ticks. Ticks are expected to be ordered by time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ticks {
+    time: Vec<DateTime<Utc>>,
+    price: Vec<f64>,
+    volume: Vec<f64>,
+}
+
+impl Ticks {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            time: Vec::with_capacity(capacity),
+            price: Vec::with_capacity(capacity),
+            volume: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    pub fn from_ticks(ticks: &[Tick]) -> Self {
+        let mut result = Self::with_capacity(ticks.len());
+        for tick in ticks {
+            result.push(*tick);
+        }
+        return result;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    #[inline]
+    pub fn push(&mut self, tick: Tick) {
+        self.time.push(tick.time);
+        self.price.push(tick.price);
+        self.volume.push(tick.volume);
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<Tick> {
+        if index >= self.len() {
+            return None;
+        }
+        return Some(Tick::new(
+            self.time[index],
+            self.price[index],
+            self.volume[index],
+        ));
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Tick> + '_ {
+        (0..self.len()).map(|i| self.get(i).unwrap())
+    }
+
+    #[inline]
+    pub fn time(&self) -> &[DateTime<Utc>] {
+        &self.time
+    }
+
+    #[inline]
+    pub fn price(&self) -> &[f64] {
+        &self.price
+    }
+
+    #[inline]
+    pub fn volume(&self) -> &[f64] {
+        &self.volume
+    }
+
+    /// Builds bars of `timeframe`. `min_tick` is only used by `Ranges` and `Renko`, see `BarBuilder`.
+    pub fn aggregate(
+        &self,
+        timeframe: Timeframe,
+        min_tick: f64,
+    ) -> Result<TickBars, BarBuilderError> {
+        let mut builder = BarBuilder::try_new(timeframe, min_tick)?;
+        for i in 0..self.len() {
+            builder.push_trade(self.time[i], self.price[i], self.volume[i]);
+        }
+        let (ohlcv, stats) = builder.finish_with_stats();
+        return Ok(TickBars::new(ohlcv, stats));
+    }
+
+    /// Reads `time`, `price` and `volume` columns. `timestamp` is accepted for time and `size`, `qty` or `amount` for volume.
+    /// `time_unit` is one of `auto`, `s`, `ms`, `us`, `ns` or `iso8601`.
+    #[cfg(feature = "polars")]
+    pub fn from_polars(df: &DataFrame, time_unit: &str) -> Result<Self, String> {
+        let time = read_time_column(df, &["time", "timestamp"], time_unit)?;
+        let price = find_column(df, &["price"])?.to_f64();
+        let volume = find_column(df, &["volume", "size", "qty", "amount"])?.to_f64();
+        return Ok(Self {
+            time,
+            price,
+            volume,
+        });
+    }
+
+    /// Reads a CSV or parquet file, see `from_polars`.
+    #[cfg(feature = "polars")]
+    #[inline]
+    pub fn read(path: &Path, time_unit: &str) -> Result<Self, String> {
+        let df = read_df(path)?;
+        return Self::from_polars(&df, time_unit);
+    }
+
+    /// `time` is written in milliseconds.
+    #[cfg(feature = "polars")]
+    pub fn to_polars(&self) -> Result<DataFrame, PolarsError> {
+        let time: Vec<i64> = self.time.iter().map(|x| x.timestamp_millis()).collect();
+        return DataFrame::new(vec![
+            Series::new("time", time),
+            Series::new("price", self.price.clone()),
+            Series::new("volume", self.volume.clone()),
+        ]);
+    }
+}
+
+impl FromIterator<Tick> for Ticks {
+    fn from_iter<T: IntoIterator<Item = Tick>>(iter: T) -> Self {
+        let mut ticks = Ticks::new();
+        for tick in iter {
+            ticks.push(tick);
+        }
+        return ticks;
+    }
+}
+
+/// Columnar storage of quotes. Quotes are expected to be ordered by time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Quotes {
+    time: Vec<DateTime<Utc>>,
+    bid: Vec<f64>,
+    ask: Vec<f64>,
+    bid_size: Vec<f64>,
+    ask_size: Vec<f64>,
+}
+
+impl Quotes {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn from_quotes(quotes: &[Quote]) -> Self {
+        let mut result = Self::new();
+        for quote in quotes {
+            result.push(*quote);
+        }
+        return result;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    #[inline]
+    pub fn push(&mut self, quote: Quote) {
+        self.time.push(quote.time);
+        self.bid.push(quote.bid);
+        self.ask.push(quote.ask);
+        self.bid_size.push(quote.bid_size);
+        self.ask_size.push(quote.ask_size);
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<Quote> {
+        if index >= self.len() {
+            return None;
+        }
+        return Some(Quote::new(
+            self.time[index],
+            self.bid[index],
+            self.ask[index],
+            self.bid_size[index],
+            self.ask_size[index],
+        ));
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Quote> + '_ {
+        (0..self.len()).map(|i| self.get(i).unwrap())
+    }
+
+    #[inline]
+    pub fn time(&self) -> &[DateTime<Utc>] {
+        &self.time
+    }
+
+    #[inline]
+    pub fn bid(&self) -> &[f64] {
+        &self.bid
+    }
+
+    #[inline]
+    pub fn ask(&self) -> &[f64] {
+        &self.ask
+    }
+
+    #[inline]
+    pub fn bid_size(&self) -> &[f64] {
+        &self.bid_size
+    }
+
+    #[inline]
+    pub fn ask_size(&self) -> &[f64] {
+        &self.ask_size
+    }
+
+    /// Mid prices as ticks with zero volume, so quotes can be aggregated into bars.
+    pub fn mid_ticks(&self) -> Ticks {
+        return self
+            .iter()
+            .map(|quote| Tick::new(quote.time, quote.mid(), 0.0))
+            .collect();
+    }
+
+    /// Reads `time`, `bid` and `ask` columns with optional `bid_size` and `ask_size`. `timestamp` is accepted for time.
+    /// `time_unit` is one of `auto`, `s`, `ms`, `us`, `ns` or `iso8601`.
+    #[cfg(feature = "polars")]
+    pub fn from_polars(df: &DataFrame, time_unit: &str) -> Result<Self, String> {
+        let time = read_time_column(df, &["time", "timestamp"], time_unit)?;
+        let bid = find_column(df, &["bid"])?.to_f64();
+        let ask = find_column(df, &["ask"])?.to_f64();
+        let optional = |name: &str| match find_column(df, &[name]) {
+            Ok(col) => col.to_f64(),
+            Err(_) => vec![f64::NAN; df.height()],
+        };
+        return Ok(Self {
+            time,
+            bid,
+            ask,
+            bid_size: optional("bid_size"),
+            ask_size: optional("ask_size"),
+        });
+    }
+
+    /// Reads a CSV or parquet file, see `from_polars`.
+    #[cfg(feature = "polars")]
+    #[inline]
+    pub fn read(path: &Path, time_unit: &str) -> Result<Self, String> {
+        let df = read_df(path)?;
+        return Self::from_polars(&df, time_unit);
+    }
+
+    /// `time` is written in milliseconds.
+    #[cfg(feature = "polars")]
+    pub fn to_polars(&self) -> Result<DataFrame, PolarsError> {
+        let time: Vec<i64> = self.time.iter().map(|x| x.timestamp_millis()).collect();
+        return DataFrame::new(vec![
+            Series::new("time", time),
+            Series::new("bid", self.bid.clone()),
+            Series::new("ask", self.ask.clone()),
+            Series::new("bid_size", self.bid_size.clone()),
+            Series::new("ask_size", self.ask_size.clone()),
+        ]);
+    }
+}
+
+impl FromIterator<Quote> for Quotes {
+    fn from_iter<T: IntoIterator<Item = Quote>>(iter: T) -> Self {
+        let mut quotes = Quotes::new();
+        for quote in iter {
+            quotes.push(quote);
+        }
+        return quotes;
+    }
+}
+
+/// Bars aggregated from ticks, with VWAP and trade count of every bar.
+#[derive(Debug, Clone)]
+pub struct TickBars {
+    ohlcv: Ohlcv,
+    vwap: Vec<f64>,
+    trades: Vec<usize>,
+}
+
+impl TickBars {
+    #[inline]
+    fn new(ohlcv: Ohlcv, stats: Vec<BarStats>) -> Self {
+        Self {
+            ohlcv,
+            vwap: stats.iter().map(|x| x.vwap).collect(),
+            trades: stats.iter().map(|x| x.trades).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn ohlcv(&self) -> &Ohlcv {
+        &self.ohlcv
+    }
+
+    #[inline]
+    pub fn into_ohlcv(self) -> Ohlcv {
+        self.ohlcv
+    }
+
+    /// `NaN` for bars without volume.
+    #[inline]
+    pub fn vwap(&self) -> &[f64] {
+        &self.vwap
+    }
+
+    #[inline]
+    pub fn trades(&self) -> &[usize] {
+        &self.trades
+    }
+
+    /// `Ohlcv::to_polars` with `vwap` and `trades` columns.
+    #[cfg(feature = "polars")]
+    pub fn to_polars(&self) -> Result<DataFrame, PolarsError> {
+        let mut df = self.ohlcv.to_polars()?;
+        let trades: Vec<u64> = self.trades.iter().map(|x| *x as u64).collect();
+        df.with_column(Series::new("vwap", self.vwap.clone()))?;
+        df.with_column(Series::new("trades", trades))?;
+        return Ok(df);
+    }
+}
+
+#[cfg(feature = "polars")]
+fn find_column<'a>(df: &'a DataFrame, names: &[&str]) -> Result<&'a Series, String> {
+    for name in names {
+        if let Ok(col) = df.column(name) {
+            return Ok(col);
+        }
+    }
+    return Err(format!("Missing column: {}", names.join(" | ")));
+}
+
+/// Datetime columns keep their own unit, see `OhlcvTimeFormat` for numbers and text.
+#[cfg(feature = "polars")]
+fn read_time_column(
+    df: &DataFrame,
+    names: &[&str],
+    time_unit: &str,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    let col = find_column(df, names)?;
+    let time_format = OhlcvTimeFormat::try_from(time_unit)?;
+    let time = polars_time(col, time_format).map_err(|e| e.to_string())?;
+    return time
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| x.ok_or_else(|| format!("Invalid time at row {}", i)))
+        .collect();
+}
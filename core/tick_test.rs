@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test {
+    use crate::{
+        ohlcv::OhlcvReader,
+        test_utils::time_seconds,
+        tick::{Tick, Ticks},
+        timeframe::Timeframe,
+    };
+
+    fn ticks() -> Ticks {
+        return [
+            (0, 100.0, 1.0),
+            (10, 102.0, 3.0),
+            (50, 101.0, 1.0),
+            (61, 99.0, 2.0),
+            (119, 100.0, 2.0),
+            (185, 103.0, 1.0),
+        ]
+        .iter()
+        .map(|(t, price, volume)| Tick::new(time_seconds(*t), *price, *volume))
+        .collect();
+    }
+
+    #[test]
+    pub fn aggregate_vwap_and_trades() {
+        let bars = ticks().aggregate(Timeframe::Minutes(1), f64::NAN).unwrap();
+        let ohlcv = bars.ohlcv();
+        assert_eq!(ohlcv.timeframe(), Timeframe::Minutes(1));
+        assert_eq!(ohlcv.len(), 3);
+        let bar = ohlcv.get(0).unwrap();
+        assert_eq!(
+            (bar.open(), bar.high(), bar.low(), bar.close(), bar.volume()),
+            (100.0, 102.0, 100.0, 101.0, 5.0)
+        );
+        assert_eq!(bar.open_time(), Some(&time_seconds(0)));
+        assert_eq!(bar.close_time(), Some(&time_seconds(60)));
+        assert_eq!(bars.trades(), &[3, 2, 1]);
+        assert_eq!(bars.vwap(), &[(100.0 + 306.0 + 101.0) / 5.0, 99.5, 103.0]);
+
+        let bars = ticks().aggregate(Timeframe::Volume(4), f64::NAN).unwrap();
+        assert_eq!(bars.trades(), &[2, 3, 1]);
+        assert_eq!(bars.vwap()[0], 101.5);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    pub fn read_csv() {
+        use crate::utils::write_df_csv;
+
+        let path = std::env::temp_dir().join("qpace_tick_test_read_csv.csv");
+        let mut df = ticks().to_polars().unwrap();
+        df.rename("volume", "qty").unwrap();
+        write_df_csv(&path, &mut df).unwrap();
+        let actual = Ticks::read(&path, "ms").unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(actual, ticks());
+
+        assert!(Ticks::from_polars(&df, "minutes").is_err());
+        df.rename("price", "last").unwrap();
+        assert_eq!(
+            Ticks::from_polars(&df, "ms").unwrap_err(),
+            "Missing column: price"
+        );
+    }
+}
@@ -14,7 +14,14 @@ pub enum Timeframe {
     Minutes(usize),
     Seconds(usize),
     Ticks(usize),
+    /// Range bars, `n` is the bar range in ticks of the symbol.
     Ranges(usize),
+    /// Bars with `n` traded volume.
+    Volume(usize),
+    /// Bars with `n` traded notional, `price * volume`.
+    Dollars(usize),
+    /// Renko bricks, `n` is the brick size in ticks of the symbol.
+    Renko(usize),
     Unknown(),
 }
 
@@ -38,6 +45,9 @@ impl Into<String> for &Timeframe {
             Timeframe::Seconds(value) => format!("{}s", value),
             Timeframe::Ticks(value) => format!("{}T", value),
             Timeframe::Ranges(value) => format!("{}R", value),
+            Timeframe::Volume(value) => format!("{}V", value),
+            Timeframe::Dollars(value) => format!("{}$", value),
+            Timeframe::Renko(value) => format!("{}B", value),
             Timeframe::Unknown() => String::from("?"),
         };
     }
@@ -87,6 +97,9 @@ impl From<String> for Timeframe {
             "s" => Timeframe::Seconds(num),
             "T" => Timeframe::Ticks(num),
             "R" => Timeframe::Ranges(num),
+            "V" => Timeframe::Volume(num),
+            "$" => Timeframe::Dollars(num),
+            "B" => Timeframe::Renko(num),
             _ => Timeframe::Unknown(),
         };
     }
@@ -216,6 +229,45 @@ impl Timeframe {
         }
     }
 
+    #[inline]
+    pub fn volume(&self) -> Option<usize> {
+        match self {
+            Timeframe::Volume(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn dollars(&self) -> Option<usize> {
+        match self {
+            Timeframe::Dollars(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn renko(&self) -> Option<usize> {
+        match self {
+            Timeframe::Renko(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// `true` for calendar or clock based timeframes.
+    #[inline]
+    pub fn is_time_based(&self) -> bool {
+        matches!(
+            self,
+            Timeframe::Years(_)
+                | Timeframe::Months(_)
+                | Timeframe::Weeks(_)
+                | Timeframe::Days(_)
+                | Timeframe::Hours(_)
+                | Timeframe::Minutes(_)
+                | Timeframe::Seconds(_)
+        )
+    }
+
     #[inline]
     pub fn unknown(&self) -> bool {
         matches!(self, Timeframe::Unknown())
@@ -88,6 +88,24 @@ impl NodeTimeframe {
         Timeframe::Ranges(value as usize).into()
     }
 
+    #[napi(factory, js_name = Volume)]
+    #[inline]
+    pub fn node_from_volume(value: i32) -> Self {
+        Timeframe::Volume(value as usize).into()
+    }
+
+    #[napi(factory, js_name = Dollars)]
+    #[inline]
+    pub fn node_from_dollars(value: i32) -> Self {
+        Timeframe::Dollars(value as usize).into()
+    }
+
+    #[napi(factory, js_name = Renko)]
+    #[inline]
+    pub fn node_from_renko(value: i32) -> Self {
+        Timeframe::Renko(value as usize).into()
+    }
+
     #[napi(factory, js_name = Unknown)]
     #[inline]
     pub fn node_from_unknown() -> Self {
@@ -148,6 +166,24 @@ impl NodeTimeframe {
         self.inner.ranges().map(|v| v as i32)
     }
 
+    #[napi(getter = volume)]
+    #[inline]
+    pub fn node_volume(&self) -> Option<i32> {
+        self.inner.volume().map(|v| v as i32)
+    }
+
+    #[napi(getter = dollars)]
+    #[inline]
+    pub fn node_dollars(&self) -> Option<i32> {
+        self.inner.dollars().map(|v| v as i32)
+    }
+
+    #[napi(getter = renko)]
+    #[inline]
+    pub fn node_renko(&self) -> Option<i32> {
+        self.inner.renko().map(|v| v as i32)
+    }
+
     #[napi(getter = unknown)]
     #[inline]
     pub fn node_unknown(&self) -> bool {
@@ -117,6 +117,27 @@ impl PyTimeframe {
         Timeframe::Ranges(value).into()
     }
 
+    #[staticmethod]
+    #[pyo3(name = "Volume")]
+    #[inline]
+    pub fn py_from_volume(value: usize) -> Self {
+        Timeframe::Volume(value).into()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "Dollars")]
+    #[inline]
+    pub fn py_from_dollars(value: usize) -> Self {
+        Timeframe::Dollars(value).into()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "Renko")]
+    #[inline]
+    pub fn py_from_renko(value: usize) -> Self {
+        Timeframe::Renko(value).into()
+    }
+
     #[staticmethod]
     #[pyo3(name = "Unknown")]
     #[inline]
@@ -178,6 +199,24 @@ impl PyTimeframe {
         self.inner.ranges()
     }
 
+    #[getter(volume)]
+    #[inline]
+    pub fn py_volume(&self) -> Option<usize> {
+        self.inner.volume()
+    }
+
+    #[getter(dollars)]
+    #[inline]
+    pub fn py_dollars(&self) -> Option<usize> {
+        self.inner.dollars()
+    }
+
+    #[getter(renko)]
+    #[inline]
+    pub fn py_renko(&self) -> Option<usize> {
+        self.inner.renko()
+    }
+
     #[getter(unknown)]
     #[inline]
     pub fn py_unknown(&self) -> bool {
@@ -89,6 +89,24 @@ impl WasmTimeframe {
         Timeframe::Ranges(value).into()
     }
 
+    #[wasm_bindgen(js_name = Volume)]
+    #[inline]
+    pub fn wasm_from_volume(value: usize) -> Self {
+        Timeframe::Volume(value).into()
+    }
+
+    #[wasm_bindgen(js_name = Dollars)]
+    #[inline]
+    pub fn wasm_from_dollars(value: usize) -> Self {
+        Timeframe::Dollars(value).into()
+    }
+
+    #[wasm_bindgen(js_name = Renko)]
+    #[inline]
+    pub fn wasm_from_renko(value: usize) -> Self {
+        Timeframe::Renko(value).into()
+    }
+
     #[wasm_bindgen(js_name = Unknown)]
     #[inline]
     pub fn wasm_from_unknown() -> Self {
@@ -149,6 +167,24 @@ impl WasmTimeframe {
         self.inner.ranges()
     }
 
+    #[wasm_bindgen(getter = volume)]
+    #[inline]
+    pub fn wasm_volume(&self) -> Option<usize> {
+        self.inner.volume()
+    }
+
+    #[wasm_bindgen(getter = dollars)]
+    #[inline]
+    pub fn wasm_dollars(&self) -> Option<usize> {
+        self.inner.dollars()
+    }
+
+    #[wasm_bindgen(getter = renko)]
+    #[inline]
+    pub fn wasm_renko(&self) -> Option<usize> {
+        self.inner.renko()
+    }
+
     #[wasm_bindgen(getter = unknown)]
     #[inline]
     pub fn wasm_unknown(&self) -> bool {
@@ -41,11 +41,14 @@ impl Into<f64> for TradeDirection {
 #[cfg_attr(feature = "bindings_py", pyclass(name = "TradeEvent"))]
 #[cfg_attr(feature = "bindings_wasm", wasm_bindgen(js_name = "TradeEvent"))]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct TradeEvent {
     id: Option<String>,
     order_bar_index: usize,
     fill_bar_index: usize,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     price: f64,
+    #[cfg_attr(feature = "json", serde(default))]
     comment: Option<String>,
 }
 
@@ -127,6 +130,12 @@ impl TradeEvent {
         self.price = price;
         return self;
     }
+
+    #[inline]
+    pub fn set_comment(&mut self, comment: Option<String>) -> &mut Self {
+        self.comment = comment;
+        return self;
+    }
 }
 
 #[derive(Debug, Error, Clone)]
@@ -149,11 +158,16 @@ pub enum TradeError {
 #[cfg_attr(feature = "bindings_py", pyclass(name = "Trade"))]
 #[cfg_attr(feature = "bindings_wasm", wasm_bindgen(js_name = "Trade"))]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct Trade {
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     size: f64,
     entry: Option<TradeEvent>,
     exit: Option<TradeEvent>,
+    #[cfg_attr(feature = "json", serde(with = "crate::utils::serde_nan"))]
     pnl: f64,
+    #[cfg_attr(feature = "json", serde(default))]
+    commission: f64,
 }
 
 impl Default for Trade {
@@ -163,6 +177,7 @@ impl Default for Trade {
             entry: None,
             exit: None,
             pnl: 0.0,
+            commission: 0.0,
         }
     }
 }
@@ -174,6 +189,7 @@ impl Trade {
             entry: None,
             exit: None,
             pnl: f64::NAN,
+            commission: 0.0,
         }
     }
 
@@ -192,11 +208,18 @@ impl Trade {
         self.exit.as_ref()
     }
 
+    /// Net of `commission`.
     #[inline]
     pub fn pnl(&self) -> f64 {
         self.pnl
     }
 
+    /// Fees of the entry, and of the exit once closed.
+    #[inline]
+    pub fn commission(&self) -> f64 {
+        self.commission
+    }
+
     #[inline]
     pub fn direction(&self) -> TradeDirection {
         return TradeDirection::from(self.size);
@@ -255,12 +278,18 @@ impl Trade {
     }
 
     #[inline]
-    pub fn set_pnl_from_price(&mut self, price: f64) -> Result<(), TradeError> {
+    pub fn set_commission(&mut self, commission: f64) {
+        self.commission = commission;
+    }
+
+    /// `point_value` is the quote currency per point of price and contract, see `Sym::point_value`.
+    #[inline]
+    pub fn set_pnl_from_price(&mut self, price: f64, point_value: f64) -> Result<(), TradeError> {
         let pnl = pnl(
             self.size,
             self.entry.as_ref().map(|x| x.price).unwrap_or(f64::NAN),
             price,
         );
-        return self.set_pnl(pnl);
+        return self.set_pnl(pnl * point_value - self.commission);
     }
 }
@@ -147,6 +147,12 @@ impl NodeTrade {
         self.inner.pnl()
     }
 
+    #[napi(getter = commission)]
+    #[inline]
+    pub fn node_commission(&self) -> f64 {
+        self.inner.commission()
+    }
+
     #[napi(getter = direction)]
     #[inline]
     pub fn node_direction(&self) -> NodeTradeDirection {
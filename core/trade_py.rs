@@ -77,6 +77,12 @@ impl Trade {
         self.pnl()
     }
 
+    #[getter(commission)]
+    #[inline]
+    pub fn py_commission(&self) -> f64 {
+        self.commission()
+    }
+
     #[getter(direction)]
     #[inline]
     pub fn py_direction(&self) -> TradeDirection {
@@ -101,6 +107,7 @@ impl Trade {
         let dict = PyDict::new_bound(py);
         dict.set_item("size", self.size())?;
         dict.set_item("pnl", self.pnl())?;
+        dict.set_item("commission", self.commission())?;
         dict.set_item(
             "entry",
             self.entry().as_ref().map(|r| r.py_to_dict(py).unwrap()),
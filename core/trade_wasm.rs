@@ -107,6 +107,12 @@ impl Trade {
         self.pnl()
     }
 
+    #[wasm_bindgen(getter = commission)]
+    #[inline]
+    pub fn wasm_commission(&self) -> f64 {
+        self.commission()
+    }
+
     #[wasm_bindgen(getter = direction)]
     #[inline]
     pub fn wasm_direction(&self) -> WasmTradeDirection {
@@ -22,6 +22,47 @@ pub fn get_oldest_possible_datetime() -> DateTime<Utc> {
     return Utc.ymd(1, 1, 1).and_hms(0, 0, 0);
 }
 
+/// Serde helpers for `f64` fields that may be NaN. NaN is written as `null`, which JSON can represent.
+/// Use with `#[serde(with = "crate::utils::serde_nan")]`, or `serde_nan::vec` for `Vec<f64>`.
+#[cfg(feature = "json")]
+pub(crate) mod serde_nan {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[inline]
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_nan() {
+            return serializer.serialize_none();
+        }
+        return serializer.serialize_some(value);
+    }
+
+    #[inline]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        return Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN));
+    }
+
+    pub mod vec {
+        use super::*;
+
+        #[inline]
+        pub fn serialize<S: Serializer>(values: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+            let values: Vec<Option<f64>> = values
+                .iter()
+                .map(|v| if v.is_nan() { None } else { Some(*v) })
+                .collect();
+            return values.serialize(serializer);
+        }
+
+        #[inline]
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<f64>, D::Error> {
+            let values = Vec::<Option<f64>>::deserialize(deserializer)?;
+            return Ok(values.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect());
+        }
+    }
+}
+
 #[cfg(feature = "bindings_py")]
 #[inline]
 pub fn pyslice_to_range(pyslice: &Bound<'_, PySlice>, max_length: usize) -> Range<usize> {